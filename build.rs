@@ -0,0 +1,28 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::env;
+
+// Captures build information reported by '--version --verbose'.
+fn main() {
+    let mut features = env::vars()
+        .filter_map(|(k, _)| {
+            k.strip_prefix("CARGO_FEATURE_")
+                .map(|f| f.to_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<String>>();
+
+    features.sort();
+
+    for (name, value) in [
+        ("FINDR_BUILD_TARGET", env::var("TARGET").unwrap_or_default()),
+        (
+            "FINDR_BUILD_PROFILE",
+            env::var("PROFILE").unwrap_or_default(),
+        ),
+        ("FINDR_BUILD_FEATURES", features.join(",")),
+    ] {
+        println!("cargo:rustc-env={name}={value}");
+    }
+}
@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::entry::Entry;
+use crate::filter::{OctalFilter, OwnerFilter, OwnerSpec};
+use crate::i18n::Lang;
+use crate::nss::IdDatabase;
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, ValueEnum)]
+pub enum AuditKind {
+    Ownership,
+}
+
+/// Unresolved expectation as given on the command line.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExpectationSpec {
+    Owner(OwnerSpec),
+    Mode(u32),
+}
+
+impl ExpectationSpec {
+    pub fn has_names(&self) -> bool {
+        match self {
+            Self::Owner(spec) => spec.has_names(),
+            Self::Mode(_) => false,
+        }
+    }
+    pub fn resolve(&self, db: &IdDatabase) -> anyhow::Result<Expectation> {
+        Ok(match self {
+            Self::Owner(spec) => Expectation::Owner(spec.resolve(db)?),
+            Self::Mode(m) => Expectation::Mode(OctalFilter::Equal(*m)),
+        })
+    }
+}
+
+impl FromStr for ExpectationSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.split_once('=') {
+            Some(("owner", owner)) => Self::Owner(owner.parse()?),
+            Some(("mode", mode)) => Self::Mode(u32::from_str_radix(mode, 8)?),
+            _ => anyhow::bail!("invalid expectation '{}'", s),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expectation {
+    Owner(OwnerFilter),
+    Mode(OctalFilter),
+}
+
+impl Expectation {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Owner(_) => "owner",
+            Self::Mode(_) => "mode",
+        }
+    }
+    fn is_met(&self, ent: &impl Entry) -> anyhow::Result<bool> {
+        Ok(match self {
+            Self::Owner(f) => f.matches(ent.uid()?, ent.gid()?),
+            Self::Mode(f) => f.matches(ent.mode()?),
+        })
+    }
+}
+
+/// Counts entries checked against a set of expectations
+/// and the deviations found per kind of expectation.
+#[derive(Debug, Default)]
+pub struct AuditReport {
+    checked: usize,
+    deviating: usize,
+    deviations: BTreeMap<&'static str, usize>,
+}
+
+impl AuditReport {
+    /// Checks the entry and returns whether it deviates
+    /// from any of the expectations.
+    pub fn check(
+        &mut self,
+        expectations: &[Expectation],
+        ent: &impl Entry,
+    ) -> anyhow::Result<bool> {
+        let mut deviates = false;
+
+        for e in expectations {
+            if !e.is_met(ent)? {
+                *self.deviations.entry(e.name()).or_default() += 1;
+                deviates = true;
+            }
+        }
+
+        self.checked += 1;
+
+        if deviates {
+            self.deviating += 1;
+        }
+
+        Ok(deviates)
+    }
+    pub fn write_summary(&self, out: &mut impl Write, lang: Lang) -> io::Result<()> {
+        let counts = self
+            .deviations
+            .iter()
+            .map(|(name, n)| format!("{name}: {n}"))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        write!(
+            out,
+            "{}: {}",
+            clap::crate_name!(),
+            lang.audited(self.checked, self.deviating)
+        )?;
+
+        if counts.is_empty() {
+            writeln!(out)
+        } else {
+            writeln!(out, " ({counts})")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExpectationSpec;
+    use crate::filter::testing::*;
+    use anyhow::{anyhow, Result};
+    use test_case::test_case;
+
+    #[test_case("mode=0644", Ok(ExpectationSpec::Mode(0o644)) ; "mode")]
+    #[test_case("owner=4242:4343", Ok(ExpectationSpec::Owner("4242:4343".parse().unwrap())) ; "owner")]
+    #[test_case("mode=0999", Err(anyhow!("")) ; "invalid mode")]
+    #[test_case("size=1", Err(anyhow!("")) ; "unknown key")]
+    #[test_case("owner", Err(anyhow!("")) ; "missing value")]
+    fn from_str(s: &str, expected: Result<ExpectationSpec>) {
+        assert_from_str(s, expected)
+    }
+}
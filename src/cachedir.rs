@@ -0,0 +1,88 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use walkdir::DirEntry;
+
+const TAG_NAME: &str = "CACHEDIR.TAG";
+const TAG_SIGNATURE: &[u8] = b"Signature: 8a477f597d28d172789f06886806bc55";
+
+/// Tracks which directories are tagged as caches according to the
+/// Cache Directory Tagging Standard (https://bford.info/cachedir/).
+/// Each directory is probed at most once.
+#[derive(Default)]
+pub struct CacheDirProbe {
+    tagged: HashMap<PathBuf, bool>,
+}
+
+impl CacheDirProbe {
+    /// Reports whether the given entry lives inside a tagged cache
+    /// directory. Like GNU tar's '--exclude-caches' the tag file
+    /// itself is never excluded.
+    pub fn excludes(&mut self, ent: &DirEntry) -> bool {
+        if ent.depth() == 0 || ent.file_name() == TAG_NAME {
+            return false;
+        }
+
+        match ent.path().parent() {
+            Some(dir) => self.is_tagged(dir),
+            None => false,
+        }
+    }
+    fn is_tagged(&mut self, dir: &Path) -> bool {
+        if let Some(tagged) = self.tagged.get(dir) {
+            return *tagged;
+        }
+
+        let tagged = has_cachedir_tag(dir);
+
+        self.tagged.insert(dir.to_path_buf(), tagged);
+
+        tagged
+    }
+}
+
+fn has_cachedir_tag(dir: &Path) -> bool {
+    read_signature(&dir.join(TAG_NAME)).is_ok_and(|sig| sig == TAG_SIGNATURE)
+}
+
+fn read_signature(path: &Path) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0; TAG_SIGNATURE.len()];
+
+    fs::File::open(path)?.read_exact(&mut buf)?;
+
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::has_cachedir_tag;
+    use anyhow::Result;
+    use std::fs;
+    use test_case::test_case;
+
+    #[test_case(Some("Signature: 8a477f597d28d172789f06886806bc55"), true ; "valid signature")]
+    #[test_case(
+        Some("Signature: 8a477f597d28d172789f06886806bc55\n# created by some tool\n"),
+        true
+        ; "valid signature with trailing comments"
+    )]
+    #[test_case(Some("Signature: 0000"), false ; "invalid signature")]
+    #[test_case(Some(""), false ; "empty tag file")]
+    #[test_case(None, false ; "missing tag file")]
+    fn has_tag(contents: Option<&str>, expected: bool) -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+
+        if let Some(c) = contents {
+            fs::write(dir.path().join("CACHEDIR.TAG"), c)?;
+        }
+
+        assert_eq!(expected, has_cachedir_tag(dir.path()));
+
+        Ok(dir.close()?)
+    }
+}
@@ -0,0 +1,202 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::found::FoundEntry;
+use anyhow::Result;
+use std::path::Path;
+
+pub use imp::ResultFile;
+
+#[cfg(feature = "analytics")]
+mod imp {
+    use super::*;
+    use anyhow::Context;
+    use arrow_array::builder::{
+        StringBuilder, TimestampSecondBuilder, UInt32Builder, UInt64Builder,
+    };
+    use arrow_array::{ArrayRef, RecordBatch};
+    use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+    use parquet::arrow::ArrowWriter;
+    use std::fs::File;
+    use std::sync::Arc;
+
+    /// Number of rows buffered before a record batch is written.
+    const BATCH_SIZE: usize = 8192;
+
+    /// Writes results as rows of a Parquet file with one
+    /// typed column per metadata field.
+    pub struct ResultFile {
+        writer: Option<ArrowWriter<File>>,
+        schema: SchemaRef,
+        columns: Columns,
+    }
+
+    #[derive(Default)]
+    struct Columns {
+        path: StringBuilder,
+        file_type: StringBuilder,
+        size: UInt64Builder,
+        mode: UInt32Builder,
+        uid: UInt32Builder,
+        gid: UInt32Builder,
+        mtime: TimestampSecondBuilder,
+        security: StringBuilder,
+        len: usize,
+    }
+
+    impl ResultFile {
+        pub fn create(path: &Path) -> Result<Self> {
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("path", DataType::Utf8, false),
+                Field::new("type", DataType::Utf8, false),
+                Field::new("size", DataType::UInt64, false),
+                Field::new("mode", DataType::UInt32, false),
+                Field::new("uid", DataType::UInt32, false),
+                Field::new("gid", DataType::UInt32, false),
+                Field::new(
+                    "mtime",
+                    DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+                    false,
+                ),
+                Field::new("security", DataType::Utf8, false),
+            ]));
+            let writer = File::create(path)
+                .map_err(anyhow::Error::from)
+                .and_then(|f| Ok(ArrowWriter::try_new(f, Arc::clone(&schema), None)?))
+                .with_context(|| format!("invalid results file '{}'", path.display()))?;
+
+            Ok(Self {
+                writer: Some(writer),
+                schema,
+                columns: Columns::default(),
+            })
+        }
+        pub fn insert(&mut self, ent: &FoundEntry) -> Result<()> {
+            let c = &mut self.columns;
+
+            c.path.append_value(&ent.path);
+            c.file_type.append_value(ent.file_type.name());
+            c.size.append_value(ent.size);
+            c.mode.append_value(ent.mode);
+            c.uid.append_value(ent.uid);
+            c.gid.append_value(ent.gid);
+            c.mtime.append_value(i64::try_from(ent.mtime)?);
+            c.security.append_option(ent.security.as_deref());
+            c.len += 1;
+
+            if c.len == BATCH_SIZE {
+                self.write_batch()?;
+            }
+
+            Ok(())
+        }
+        /// Writes any buffered results and finalizes the file.
+        pub fn flush(&mut self) -> Result<()> {
+            self.write_batch()?;
+
+            if let Some(w) = self.writer.take() {
+                w.close()?;
+            }
+
+            Ok(())
+        }
+        fn write_batch(&mut self) -> Result<()> {
+            let c = &mut self.columns;
+
+            if c.len == 0 {
+                return Ok(());
+            }
+
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(c.path.finish()),
+                Arc::new(c.file_type.finish()),
+                Arc::new(c.size.finish()),
+                Arc::new(c.mode.finish()),
+                Arc::new(c.uid.finish()),
+                Arc::new(c.gid.finish()),
+                Arc::new(c.mtime.finish().with_timezone("UTC")),
+                Arc::new(c.security.finish()),
+            ];
+
+            c.len = 0;
+
+            let batch = RecordBatch::try_new(Arc::clone(&self.schema), columns)?;
+
+            match self.writer.as_mut() {
+                Some(w) => Ok(w.write(&batch)?),
+                None => anyhow::bail!("results file already closed"),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::ResultFile;
+        use crate::entry::FileKind;
+        use crate::found::FoundEntry;
+        use anyhow::Result;
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use std::fs::File;
+
+        #[test]
+        fn insert() -> Result<()> {
+            let file = tempfile::NamedTempFile::new()?;
+            let ent = FoundEntry {
+                path: "./a.txt".to_string(),
+                file_type: FileKind::File,
+                size: 8,
+                mode: 0o644,
+                uid: 1000,
+                gid: 1000,
+                mtime: 1677000000,
+                security: Some("-".to_string()),
+            };
+
+            let mut results = ResultFile::create(file.path())?;
+
+            for _ in 0..8193 {
+                results.insert(&ent)?;
+            }
+
+            results.flush()?;
+
+            let reader = SerializedFileReader::new(File::open(file.path())?)?;
+            let meta = reader.metadata();
+
+            assert_eq!(8193, meta.file_metadata().num_rows());
+            assert_eq!(
+                vec!["path", "type", "size", "mode", "uid", "gid", "mtime", "security"],
+                meta.file_metadata()
+                    .schema_descr()
+                    .columns()
+                    .iter()
+                    .map(|c| c.name())
+                    .collect::<Vec<&str>>()
+            );
+
+            Ok(file.close()?)
+        }
+    }
+}
+
+#[cfg(not(feature = "analytics"))]
+mod imp {
+    use super::*;
+
+    /// Stands in for the Parquet writer when findr is built
+    /// without the 'analytics' feature.
+    pub enum ResultFile {}
+
+    impl ResultFile {
+        pub fn create(_: &Path) -> Result<Self> {
+            anyhow::bail!("parquet output requires findr to be built with the 'analytics' feature")
+        }
+        pub fn insert(&mut self, _: &FoundEntry) -> Result<()> {
+            match *self {}
+        }
+        pub fn flush(&mut self) -> Result<()> {
+            match *self {}
+        }
+    }
+}
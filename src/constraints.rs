@@ -0,0 +1,536 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::filter::{TypeFilter, TypeSelector};
+use crate::options::Options;
+use crate::output::OutputFormat;
+use std::io::{self, Write};
+
+/// A rule restricting how two options may be combined.
+/// Options are referred to by their argument ids.
+#[derive(Clone, Copy, Debug)]
+pub enum Constraint {
+    Conflicts(&'static str, &'static str, &'static str),
+    Requires(&'static str, &'static str, &'static str),
+}
+
+/// Every constraint between options along with the reason
+/// it exists. Rules are registered with the argument parser
+/// so violations are rejected before a search begins.
+pub const CONSTRAINTS: &[Constraint] = &[
+    Constraint::Requires(
+        "audit",
+        "expectations",
+        "an audit has nothing to check without expectations",
+    ),
+    Constraint::Requires(
+        "expectations",
+        "audit",
+        "expectations are only checked when auditing",
+    ),
+    Constraint::Conflicts(
+        "emit_cmd",
+        "output",
+        "commands are written in place of formatted results",
+    ),
+    Constraint::Conflicts(
+        "escape",
+        "emit_cmd",
+        "command arguments are already shell quoted",
+    ),
+    Constraint::Requires(
+        "missing",
+        "reference",
+        "missing paths are taken from the reference list",
+    ),
+    Constraint::Requires(
+        "null",
+        "reference",
+        "only the reference list is read NUL separated",
+    ),
+    Constraint::Requires(
+        "max_link_depth",
+        "resolvable",
+        "only chains followed by '--resolvable' are limited",
+    ),
+    Constraint::Conflicts(
+        "from_db",
+        "dirs",
+        "stored results are searched instead of root directories",
+    ),
+    Constraint::Conflicts("from_db", "disk_usage", "allocated sizes are not stored"),
+    Constraint::Conflicts("from_db", "estimate", "directories are not walked"),
+    Constraint::Conflicts("from_db", "exclude_caches", "directories are not walked"),
+    Constraint::Conflicts("from_db", "excludes", "directories are not walked"),
+    Constraint::Conflicts("from_db", "no_hidden", "directories are not walked"),
+    Constraint::Conflicts("from_db", "exclude_from", "directories are not walked"),
+    Constraint::Conflicts("from_db", "interleave_roots", "directories are not walked"),
+    Constraint::Conflicts("from_db", "max_depth", "depth is not stored"),
+    Constraint::Conflicts("from_db", "one_file_system", "directories are not walked"),
+    Constraint::Conflicts("from_db", "open", "inode numbers are not stored"),
+    Constraint::Conflicts("from_db", "min_depth", "depth is not stored"),
+    Constraint::Conflicts("from_db", "newer_than_parent", "depth is not stored"),
+    Constraint::Conflicts(
+        "from_db",
+        "subtree_modified_within",
+        "subtrees are not stored",
+    ),
+    Constraint::Conflicts("from_db", "unique_inodes", "inode numbers are not stored"),
+    Constraint::Conflicts("from_db", "visit_order", "directories are not walked"),
+    Constraint::Conflicts(
+        "estimate",
+        "min_depth",
+        "subdirectories are sampled before the minimum depth is reached",
+    ),
+    Constraint::Conflicts(
+        "estimate",
+        "output",
+        "estimates are written in place of results",
+    ),
+    Constraint::Conflicts(
+        "estimate",
+        "emit_cmd",
+        "estimates are written in place of results",
+    ),
+    Constraint::Conflicts(
+        "estimate",
+        "split_output",
+        "estimates are written in place of results",
+    ),
+    Constraint::Conflicts(
+        "estimate",
+        "missing",
+        "unvisited directories cannot be reported as missing",
+    ),
+    Constraint::Conflicts("estimate", "policy", "violations are not estimated"),
+    Constraint::Conflicts(
+        "duplicates",
+        "estimate",
+        "estimates are written in place of results",
+    ),
+    Constraint::Conflicts("duplicates", "score_cleanup", "duplicates are not ranked"),
+    Constraint::Conflicts(
+        "duplicates",
+        "policy",
+        "violations are written in place of results",
+    ),
+    Constraint::Conflicts(
+        "duplicates",
+        "audit",
+        "deviations are written in place of results",
+    ),
+    Constraint::Conflicts(
+        "duplicates",
+        "missing",
+        "missing paths have no contents to compare",
+    ),
+    Constraint::Conflicts(
+        "duplicates",
+        "output",
+        "duplicate sets are written in place of results",
+    ),
+    Constraint::Conflicts(
+        "duplicates",
+        "emit_cmd",
+        "duplicate sets are written in place of results",
+    ),
+    Constraint::Conflicts(
+        "duplicates",
+        "split_output",
+        "duplicate sets are written in place of results",
+    ),
+    Constraint::Conflicts("estimate", "audit", "deviations are not estimated"),
+    Constraint::Requires(
+        "min_score",
+        "score_cleanup",
+        "only ranked results have a score",
+    ),
+    Constraint::Requires("top", "score_cleanup", "only ranked results have a score"),
+    Constraint::Conflicts(
+        "score_cleanup",
+        "missing",
+        "missing paths have no metadata to score",
+    ),
+    Constraint::Conflicts(
+        "score_cleanup",
+        "estimate",
+        "estimates are written in place of results",
+    ),
+    Constraint::Conflicts(
+        "score_cleanup",
+        "policy",
+        "violations are written in place of results",
+    ),
+    Constraint::Conflicts(
+        "fixed_strings",
+        "regex_engine",
+        "fixed strings are not matched by a regex engine",
+    ),
+    Constraint::Conflicts(
+        "ignore_case",
+        "smart_case",
+        "smart case decides case sensitivity per pattern",
+    ),
+    Constraint::Requires("on_locked", "lock", "there is no lock to wait for"),
+    Constraint::Conflicts(
+        "full_content",
+        "max_read",
+        "whole files are read regardless of their size",
+    ),
+    Constraint::Requires(
+        "split_every",
+        "split_output",
+        "only split output is written in chunks",
+    ),
+    Constraint::Conflicts(
+        "missing",
+        "audit",
+        "missing paths have no metadata to audit",
+    ),
+    Constraint::Conflicts(
+        "policy",
+        "audit",
+        "policies and audits both replace results with deviations",
+    ),
+    Constraint::Conflicts("policy", "emit_cmd", "violations are written as JSON lines"),
+    Constraint::Conflicts("policy", "missing", "violations are written as JSON lines"),
+    Constraint::Conflicts("policy", "output", "violations are written as JSON lines"),
+    Constraint::Conflicts(
+        "no_nss",
+        "group_of",
+        "groups are never looked up through NSS",
+    ),
+    Constraint::Conflicts(
+        "no_nss",
+        "group_members",
+        "groups are never looked up through NSS",
+    ),
+    Constraint::Conflicts("no_nss", "nouser", "ids are never looked up through NSS"),
+    Constraint::Conflicts("no_nss", "nogroup", "ids are never looked up through NSS"),
+    Constraint::Conflicts(
+        "has_acl",
+        "no_acl",
+        "results cannot both carry and lack ACLs",
+    ),
+    Constraint::Requires(
+        "group_members",
+        "owner",
+        "only groups given with '--owner' have members",
+    ),
+    Constraint::Requires(
+        "verbose",
+        "version",
+        "only version information has a verbose form",
+    ),
+];
+
+/// Options which read the contents of files encountered
+/// during a search rather than just their metadata along
+/// with what they read. All of them conflict with
+/// '--metadata-only'.
+pub const CONTENT_READERS: &[(&str, &str)] = &[
+    ("exclude_caches", "reads the contents of CACHEDIR.TAG files"),
+    ("contains", "reads the contents of files"),
+    ("hashes", "hashes the contents of files"),
+    (
+        "duplicates",
+        "hashes the contents of files sharing their size",
+    ),
+];
+
+/// Values of '--type' which cannot be used together with
+/// an option along with the reason.
+pub struct TypeConflict {
+    pub id: &'static str,
+    pub is_set: fn(&Options) -> bool,
+    pub types: &'static [TypeFilter],
+    pub reason: &'static str,
+}
+
+/// Every conflict between options and '--type' values. Types
+/// are values rather than options so they cannot be
+/// registered with the argument parser and are checked
+/// once options are parsed instead.
+pub const TYPE_CONFLICTS: &[TypeConflict] = &[
+    TypeConflict {
+        id: "metadata_only",
+        is_set: |o| o.metadata_only,
+        types: &[TypeFilter::Text, TypeFilter::Binary],
+        reason: "reads the contents of files",
+    },
+    TypeConflict {
+        id: "from_db",
+        is_set: |o| o.from_db.is_some(),
+        types: &[TypeFilter::Text, TypeFilter::Binary],
+        reason: "file contents are not stored",
+    },
+    TypeConflict {
+        id: "from_db",
+        is_set: |o| o.from_db.is_some(),
+        types: &[
+            TypeFilter::Empty,
+            TypeFilter::EmptyDir,
+            TypeFilter::NonEmptyDir,
+        ],
+        reason: "directory contents are not stored",
+    },
+    TypeConflict {
+        id: "from_db",
+        is_set: |o| o.from_db.is_some(),
+        types: &[TypeFilter::BrokenSymLink],
+        reason: "link targets are not stored",
+    },
+    TypeConflict {
+        id: "from_db",
+        is_set: |o| o.from_db.is_some(),
+        types: &[TypeFilter::Executable],
+        reason: "access is only known for live entries",
+    },
+    TypeConflict {
+        id: "from_db",
+        is_set: |o| o.from_db.is_some(),
+        types: &[TypeFilter::MountPoint],
+        reason: "devices are not stored",
+    },
+];
+
+/// Rejects '--type' values which conflict with any of the
+/// given options.
+pub fn check_types(options: &Options) -> anyhow::Result<()> {
+    for c in TYPE_CONFLICTS.iter().filter(|c| (c.is_set)(options)) {
+        let conflicting = options.type_filters.iter().find(|s| match s {
+            TypeSelector::Is(t) | TypeSelector::IsNot(t) => c.types.contains(t),
+        });
+
+        if let Some(t) = conflicting {
+            anyhow::bail!(
+                "'--type={}' cannot be used with '--{}': {}",
+                t,
+                c.id.replace('_', "-"),
+                c.reason
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Values of '--output' which cannot be used together with
+/// an option along with the reason.
+pub struct OutputConflict {
+    pub id: &'static str,
+    pub is_set: fn(&Options) -> bool,
+    pub formats: &'static [OutputFormat],
+    pub reason: &'static str,
+}
+
+/// Every conflict between options and '--output' values
+/// which are checked once options are parsed like those
+/// of '--type'.
+pub const OUTPUT_CONFLICTS: &[OutputConflict] = &[
+    OutputConflict {
+        id: "split_output",
+        is_set: |o| o.split_output.is_some(),
+        formats: &[OutputFormat::Events],
+        reason: "events form a single stream which must not be split",
+    },
+    OutputConflict {
+        id: "split_output",
+        is_set: |o| o.split_output.is_some(),
+        formats: &[OutputFormat::Quickfix, OutputFormat::QuickfixJson],
+        reason: "editors load a single quickfix list",
+    },
+];
+
+/// Rejects '--output' values which conflict with any of the
+/// given options.
+pub fn check_outputs(options: &Options) -> anyhow::Result<()> {
+    for c in OUTPUT_CONFLICTS.iter().filter(|c| (c.is_set)(options)) {
+        if let Some(f) = options.output.iter().find(|f| c.formats.contains(f)) {
+            anyhow::bail!(
+                "'--output={}' cannot be used with '--{}': {}",
+                f,
+                c.id.replace('_', "-"),
+                c.reason
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns every constraint including those derived from
+/// the options which read file contents.
+pub fn constraints() -> impl Iterator<Item = Constraint> {
+    CONSTRAINTS.iter().copied().chain(
+        CONTENT_READERS
+            .iter()
+            .map(|(id, reason)| Constraint::Conflicts("metadata_only", id, reason)),
+    )
+}
+
+/// Registers all constraints with the given command.
+pub fn apply(cmd: clap::Command) -> clap::Command {
+    constraints().fold(cmd, |cmd, c| match c {
+        Constraint::Conflicts(id, other, _) => cmd.mut_arg(id, |a| a.conflicts_with(other)),
+        Constraint::Requires(id, other, _) => cmd.mut_arg(id, |a| a.requires(other)),
+    })
+}
+
+/// Writes one line per constraint describing which options
+/// cannot be combined or depend on each other and why.
+pub fn write_explanation(out: &mut impl Write, cmd: &clap::Command) -> io::Result<()> {
+    for c in constraints() {
+        match c {
+            Constraint::Conflicts(id, other, reason) => writeln!(
+                out,
+                "{} cannot be used with {}: {}",
+                flag(cmd, id),
+                flag(cmd, other),
+                reason
+            )?,
+            Constraint::Requires(id, other, reason) => writeln!(
+                out,
+                "{} requires {}: {}",
+                flag(cmd, id),
+                flag(cmd, other),
+                reason
+            )?,
+        }
+    }
+    for c in TYPE_CONFLICTS {
+        let types = c
+            .types
+            .iter()
+            .map(|t| TypeSelector::Is(t.clone()).to_string())
+            .collect::<Vec<String>>();
+
+        writeln!(
+            out,
+            "{} cannot be used with --type={}: {}",
+            flag(cmd, c.id),
+            types.join(","),
+            c.reason
+        )?;
+    }
+    for c in OUTPUT_CONFLICTS {
+        let formats = c
+            .formats
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>();
+
+        writeln!(
+            out,
+            "{} cannot be used with --output={}: {}",
+            flag(cmd, c.id),
+            formats.join(","),
+            c.reason
+        )?;
+    }
+
+    Ok(())
+}
+
+fn flag(cmd: &clap::Command, id: &str) -> String {
+    cmd.get_arguments()
+        .find(|a| a.get_id() == id)
+        .and_then(|a| a.get_long())
+        .map_or_else(|| id.to_string(), |l| format!("--{l}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply, check_outputs, check_types, constraints, write_explanation, Constraint,
+        OUTPUT_CONFLICTS, TYPE_CONFLICTS,
+    };
+    use crate::options::Options;
+    use anyhow::Result;
+    use clap::{CommandFactory, Parser};
+    use test_case::test_case;
+
+    #[test]
+    fn constraints_reference_known_options() {
+        let cmd = Options::command();
+
+        for c in constraints() {
+            let (Constraint::Conflicts(id, other, _) | Constraint::Requires(id, other, _)) = c;
+
+            for id in [id, other]
+                .into_iter()
+                .chain(TYPE_CONFLICTS.iter().map(|c| c.id))
+                .chain(OUTPUT_CONFLICTS.iter().map(|c| c.id))
+            {
+                assert!(
+                    cmd.get_arguments().any(|a| a.get_id() == id),
+                    "unknown option '{id}'"
+                );
+            }
+        }
+    }
+
+    #[test_case(&["--metadata-only", "--type=text"], false ; "metadata only reading contents")]
+    #[test_case(&["--metadata-only", "--type=f,!binary"], false ; "metadata only negated")]
+    #[test_case(&["--from-db=results.db", "--type=empty-dir"], false ; "from database empty dir")]
+    #[test_case(&["--from-db=results.db", "--type=!x"], false ; "from database executable")]
+    #[test_case(&["--from-db=results.db", "--type=f,l"], true ; "from database stored type")]
+    #[test_case(&["--type=empty-dir"], true ; "live entries")]
+    fn types(args: &[&str], expected: bool) -> Result<()> {
+        let options = Options::try_parse_from(["findr"].iter().chain(args))?;
+
+        assert_eq!(expected, check_types(&options).is_ok());
+
+        Ok(())
+    }
+
+    #[test_case(&["--output=events", "--split-output=o-%d"], false ; "split events")]
+    #[test_case(&["--output=plain", "--output=events", "--split-output=o-%d"], false ; "split with events")]
+    #[test_case(&["--output=quickfix-json", "--split-output=o-%d"], false ; "split quickfix")]
+    #[test_case(&["--output=events", "--max-output=10"], true ; "limited events")]
+    #[test_case(&["--split-output=o-%d"], true ; "split plain")]
+    fn outputs(args: &[&str], expected: bool) -> Result<()> {
+        let options = Options::try_parse_from(["findr"].iter().chain(args))?;
+
+        assert_eq!(expected, check_outputs(&options).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn applied_conflicts() {
+        let cmd = apply(Options::command());
+        let escape = cmd
+            .get_arguments()
+            .find(|a| a.get_id() == "escape")
+            .unwrap();
+
+        assert!(cmd
+            .get_arg_conflicts_with(escape)
+            .iter()
+            .any(|a| a.get_id() == "emit_cmd"));
+    }
+
+    #[test]
+    fn explanation() -> Result<()> {
+        let mut out = Vec::new();
+
+        write_explanation(&mut out, &Options::command())?;
+
+        let out = String::from_utf8(out)?;
+
+        assert_eq!(
+            constraints().count() + TYPE_CONFLICTS.len() + OUTPUT_CONFLICTS.len(),
+            out.lines().count()
+        );
+        assert!(out.contains(
+            "--emit-cmd cannot be used with --output: commands are written in place of formatted results"
+        ));
+        assert!(out.contains("--missing requires --reference"));
+        assert!(out.contains("--metadata-only cannot be used with --exclude-caches"));
+        assert!(out.contains("--from-db cannot be used with --type=empty,empty-dir,non-empty-dir"));
+
+        Ok(())
+    }
+}
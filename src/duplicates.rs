@@ -0,0 +1,251 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::entry::Entry;
+use crate::escape::json_escape;
+use crate::filter::HashAlgorithm;
+use clap::ValueEnum;
+use std::collections::{hash_map, HashMap};
+use std::path::{Path, PathBuf};
+
+/// How sets of duplicates are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum DuplicatesFormat {
+    /// One path per line with sets separated by a blank
+    /// line like 'fdupes'.
+    #[default]
+    Plain,
+    /// One JSON object per line and set.
+    Json,
+}
+
+/// Files with identical contents.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuplicateSet {
+    pub size: u64,
+    pub digest: Vec<u8>,
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateSet {
+    /// Formats the set with each path passed through 'display'
+    /// so that sets are written like any other result.
+    pub fn lines(
+        &self,
+        format: DuplicatesFormat,
+        display: impl Fn(&Path) -> String,
+    ) -> Vec<String> {
+        let paths = self.paths.iter().map(|p| display(p));
+
+        match format {
+            DuplicatesFormat::Plain => paths.collect(),
+            DuplicatesFormat::Json => vec![format!(
+                r#"{{"size":{},"blake3":"{}","paths":[{}]}}"#,
+                self.size,
+                self.digest
+                    .iter()
+                    .map(|b| format!("{b:02x}"))
+                    .collect::<String>(),
+                paths.map(|p| json_escape(&p)).collect::<Vec<_>>().join(","),
+            )],
+        }
+    }
+}
+
+/// Collects files by size so that only files sharing their
+/// size with another are hashed. Hard links to the same file
+/// are collapsed into their first path by name as they cannot
+/// be deduplicated any further.
+#[derive(Debug, Default)]
+pub struct DuplicateFinder {
+    // path and size keyed by (dev, ino)
+    files: HashMap<(u64, u64), (PathBuf, u64)>,
+}
+
+impl DuplicateFinder {
+    pub fn record<E: Entry>(&mut self, ent: &E) -> anyhow::Result<()> {
+        let id = (ent.dev()?, ent.ino()?);
+
+        self.insert(ent.fs_path().to_path_buf(), id, ent.size()?);
+
+        Ok(())
+    }
+    fn insert(&mut self, path: PathBuf, id: (u64, u64), size: u64) {
+        match self.files.entry(id) {
+            hash_map::Entry::Occupied(mut e) if path < e.get().0 => e.get_mut().0 = path,
+            hash_map::Entry::Occupied(_) => (),
+            hash_map::Entry::Vacant(e) => {
+                e.insert((path, size));
+            }
+        }
+    }
+    /// Hashes the candidates and groups them by digest.
+    /// Files which cannot be read are passed to 'report' and
+    /// left out. Sets are ordered by descending size and
+    /// their paths by name.
+    pub fn sets(
+        self,
+        mut report: impl FnMut(anyhow::Error) -> anyhow::Result<()>,
+    ) -> anyhow::Result<Vec<DuplicateSet>> {
+        let mut sets = Vec::new();
+        let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+
+        for (path, size) in self.files.into_values() {
+            by_size.entry(size).or_default().push(path);
+        }
+
+        for (size, paths) in by_size.into_iter().filter(|(_, p)| p.len() > 1) {
+            let mut by_digest: HashMap<Vec<u8>, Vec<PathBuf>> = HashMap::new();
+
+            for path in paths {
+                // empty files are identical without reading them
+                let digest = match size {
+                    0 => Ok(blake3::hash(&[]).as_bytes().to_vec()),
+                    _ => HashAlgorithm::Blake3.digest(&path, None),
+                };
+
+                match digest {
+                    Ok(d) => by_digest.entry(d).or_default().push(path),
+                    Err(e) => report(anyhow::anyhow!(
+                        "unable to read '{}': {}",
+                        path.display(),
+                        e
+                    ))?,
+                }
+            }
+
+            for (digest, mut paths) in by_digest.into_iter().filter(|(_, p)| p.len() > 1) {
+                paths.sort();
+                sets.push(DuplicateSet {
+                    size,
+                    digest,
+                    paths,
+                });
+            }
+        }
+
+        sets.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.paths.cmp(&b.paths)));
+
+        Ok(sets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DuplicateFinder, DuplicateSet, DuplicatesFormat};
+    use anyhow::Result;
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::{Path, PathBuf};
+
+    fn record(finder: &mut DuplicateFinder, path: PathBuf) -> Result<()> {
+        let md = fs::symlink_metadata(&path)?;
+
+        finder.insert(path, (md.dev(), md.ino()), md.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sets() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let mut finder = DuplicateFinder::default();
+
+        for (name, contents) in [
+            ("a", "same"),
+            ("b", "same"),
+            ("c", "diff"),
+            ("d", "unique size"),
+            ("e", ""),
+            ("f", ""),
+        ] {
+            let path = dir.path().join(name);
+
+            fs::write(&path, contents)?;
+            record(&mut finder, path)?;
+        }
+
+        let path = |n: &str| dir.path().join(n);
+        let sets = finder.sets(Err)?;
+
+        assert_eq!(2, sets.len());
+        assert_eq!(vec![path("a"), path("b")], sets[0].paths);
+        assert_eq!(vec![path("e"), path("f")], sets[1].paths);
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn hard_links() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let mut finder = DuplicateFinder::default();
+
+        fs::write(dir.path().join("b"), "same")?;
+        fs::hard_link(dir.path().join("b"), dir.path().join("a"))?;
+
+        for name in ["b", "a"] {
+            record(&mut finder, dir.path().join(name))?;
+        }
+
+        assert!(finder.sets(Err)?.is_empty());
+
+        let mut finder = DuplicateFinder::default();
+
+        fs::write(dir.path().join("c"), "same")?;
+
+        for name in ["b", "a", "c"] {
+            record(&mut finder, dir.path().join(name))?;
+        }
+
+        let sets = finder.sets(Err)?;
+
+        assert_eq!(1, sets.len());
+        assert_eq!(
+            vec![dir.path().join("a"), dir.path().join("c")],
+            sets[0].paths
+        );
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn unreadable() -> Result<()> {
+        let mut finder = DuplicateFinder::default();
+        let mut errors = 0;
+
+        finder.insert("dne_a".into(), (0, 1), 1);
+        finder.insert("dne_b".into(), (0, 2), 1);
+
+        let sets = finder.sets(|_| {
+            errors += 1;
+
+            Ok(())
+        })?;
+
+        assert!(sets.is_empty());
+        assert_eq!(2, errors);
+
+        Ok(())
+    }
+
+    #[test]
+    fn json() {
+        let set = DuplicateSet {
+            size: 4,
+            digest: vec![0x0a, 0xff],
+            paths: vec!["./a".into(), "./\"b\"".into()],
+        };
+
+        let display = |p: &Path| p.to_string_lossy().to_string();
+
+        assert_eq!(
+            vec![r#"{"size":4,"blake3":"0aff","paths":["./a","./\"b\""]}"#],
+            set.lines(DuplicatesFormat::Json, display)
+        );
+        assert_eq!(
+            vec!["./a", "./\"b\""],
+            set.lines(DuplicatesFormat::Plain, display)
+        );
+    }
+}
@@ -2,14 +2,26 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::retry::RetryPolicy;
+use crate::security;
 use anyhow::Result;
 use std::fs;
-use std::os::unix::fs::MetadataExt;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::Path;
 use std::time;
-use walkdir::DirEntry;
+use walkdir::{DirEntry, DirEntryExt};
 
 pub trait Entry {
+    /// Returns the path lossily converted to UTF-8 for
+    /// matching and display.
     fn path(&self) -> String;
+    /// Returns the path as found on the filesystem. Only
+    /// this path may be passed to system calls since names
+    /// which are not valid UTF-8 do not survive 'path'.
+    fn fs_path(&self) -> &Path;
+    /// Returns the number of directories between the entry
+    /// and the root it was found under.
+    fn depth(&self) -> Result<usize>;
     fn uid(&self) -> Result<u32>;
     fn gid(&self) -> Result<u32>;
     fn atime(&self) -> Result<u64>;
@@ -17,17 +29,115 @@ pub trait Entry {
     fn created_time(&self) -> Result<u64>;
     fn mtime(&self) -> Result<u64>;
     fn mode(&self) -> Result<u32>;
+    fn ino(&self) -> Result<u64>;
+    fn dev(&self) -> Result<u64>;
+    fn nlink(&self) -> Result<u64>;
+    /// Returns the number of 512 byte blocks allocated to
+    /// the entry.
+    fn blocks(&self) -> Result<u64>;
     fn size(&self) -> Result<u64>;
-    fn file_type(&self) -> fs::FileType;
+    fn target_size(&self) -> Result<u64>;
+    fn target_mtime(&self) -> Result<u64>;
+    /// Returns the target of symlinks or 'None' for any
+    /// other entry.
+    fn link_target(&self) -> Result<Option<String>>;
+    fn subtree_mtime(&self) -> Option<u64>;
+    fn file_type(&self) -> FileKind;
+    fn security(&self) -> Result<String>;
+}
+
+/// The type of an entry independent of whether it was read
+/// from the filesystem or from previously stored results.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileKind {
+    Dir,
+    File,
+    SymLink,
+    Pipe,
+    Socket,
+    BlockDevice,
+    CharDevice,
+    Unknown,
+}
+
+impl FileKind {
+    pub const ALL: [Self; 8] = [
+        Self::Dir,
+        Self::File,
+        Self::SymLink,
+        Self::Pipe,
+        Self::Socket,
+        Self::BlockDevice,
+        Self::CharDevice,
+        Self::Unknown,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Dir => "dir",
+            Self::File => "file",
+            Self::SymLink => "sym-link",
+            Self::Pipe => "pipe",
+            Self::Socket => "socket",
+            Self::BlockDevice => "block-device",
+            Self::CharDevice => "char-device",
+            Self::Unknown => "unknown",
+        }
+    }
+    pub fn from_name(name: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|k| k.name() == name)
+            .unwrap_or(Self::Unknown)
+    }
+}
+
+impl From<fs::FileType> for FileKind {
+    fn from(ftype: fs::FileType) -> Self {
+        if ftype.is_dir() {
+            Self::Dir
+        } else if ftype.is_file() {
+            Self::File
+        } else if ftype.is_symlink() {
+            Self::SymLink
+        } else if ftype.is_fifo() {
+            Self::Pipe
+        } else if ftype.is_socket() {
+            Self::Socket
+        } else if ftype.is_block_device() {
+            Self::BlockDevice
+        } else if ftype.is_char_device() {
+            Self::CharDevice
+        } else {
+            Self::Unknown
+        }
+    }
 }
 
 pub struct EntryImpl {
     ent: DirEntry,
+    retry: RetryPolicy,
+    subtree_mtime: Option<u64>,
 }
 
-impl From<walkdir::DirEntry> for EntryImpl {
-    fn from(ent: walkdir::DirEntry) -> Self {
-        Self { ent }
+impl EntryImpl {
+    pub fn new(ent: DirEntry, retry: RetryPolicy) -> Self {
+        Self {
+            ent,
+            retry,
+            subtree_mtime: None,
+        }
+    }
+    pub fn with_subtree_mtime(mut self, mtime: Option<u64>) -> Self {
+        self.subtree_mtime = mtime;
+
+        self
+    }
+    fn metadata(&self) -> Result<fs::Metadata> {
+        Ok(self.retry.run(|| fs::symlink_metadata(self.ent.path()))?)
+    }
+    fn target_metadata(&self) -> Result<fs::Metadata> {
+        Ok(self.retry.run(|| fs::metadata(self.ent.path()))?)
     }
 }
 
@@ -35,36 +145,74 @@ impl Entry for EntryImpl {
     fn path(&self) -> String {
         self.ent.path().to_string_lossy().to_string()
     }
+    fn fs_path(&self) -> &Path {
+        self.ent.path()
+    }
+    fn depth(&self) -> Result<usize> {
+        Ok(self.ent.depth())
+    }
     fn uid(&self) -> Result<u32> {
-        Ok(self.ent.metadata()?.uid())
+        Ok(self.metadata()?.uid())
     }
     fn gid(&self) -> Result<u32> {
-        Ok(self.ent.metadata()?.gid())
+        Ok(self.metadata()?.gid())
     }
     fn atime(&self) -> Result<u64> {
-        Ok(self.ent.metadata()?.atime().try_into()?)
+        Ok(self.metadata()?.atime().try_into()?)
     }
     fn ctime(&self) -> Result<u64> {
-        Ok(self.ent.metadata()?.ctime().try_into()?)
+        Ok(self.metadata()?.ctime().try_into()?)
     }
     fn mtime(&self) -> Result<u64> {
-        Ok(self.ent.metadata()?.mtime().try_into()?)
+        Ok(self.metadata()?.mtime().try_into()?)
     }
     fn created_time(&self) -> Result<u64> {
         Ok(self
-            .ent
             .metadata()?
             .created()?
             .duration_since(time::UNIX_EPOCH)?
             .as_secs())
     }
     fn mode(&self) -> Result<u32> {
-        Ok(self.ent.metadata()?.mode())
+        Ok(self.metadata()?.mode())
+    }
+    fn ino(&self) -> Result<u64> {
+        Ok(self.ent.ino())
+    }
+    fn dev(&self) -> Result<u64> {
+        Ok(self.metadata()?.dev())
+    }
+    fn nlink(&self) -> Result<u64> {
+        Ok(self.metadata()?.nlink())
+    }
+    fn blocks(&self) -> Result<u64> {
+        Ok(self.metadata()?.blocks())
     }
     fn size(&self) -> Result<u64> {
-        Ok(self.ent.metadata()?.size())
+        Ok(self.metadata()?.size())
+    }
+    fn target_size(&self) -> Result<u64> {
+        Ok(self.target_metadata()?.size())
+    }
+    fn target_mtime(&self) -> Result<u64> {
+        Ok(self.target_metadata()?.mtime().try_into()?)
+    }
+    fn link_target(&self) -> Result<Option<String>> {
+        if self.file_type() != FileKind::SymLink {
+            return Ok(None);
+        }
+
+        let target = self.retry.run(|| fs::read_link(self.ent.path()))?;
+
+        Ok(Some(target.to_string_lossy().to_string()))
+    }
+    fn subtree_mtime(&self) -> Option<u64> {
+        self.subtree_mtime
+    }
+    fn file_type(&self) -> FileKind {
+        self.ent.file_type().into()
     }
-    fn file_type(&self) -> fs::FileType {
-        self.ent.file_type()
+    fn security(&self) -> Result<String> {
+        Ok(security::label(self.ent.path(), self.mode()?)?)
     }
 }
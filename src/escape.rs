@@ -0,0 +1,116 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::ValueEnum;
+
+#[derive(Clone, Debug, PartialEq, ValueEnum)]
+pub enum Escape {
+    Url,
+    Json,
+    C,
+}
+
+impl Escape {
+    pub fn apply(&self, s: &str) -> String {
+        match self {
+            Self::Url => url_encode(s),
+            Self::Json => json_escape(s),
+            Self::C => c_escape(s),
+        }
+    }
+}
+
+/// Percent-encodes everything but unreserved characters
+/// (RFC 3986) and '/' so that paths remain readable.
+pub fn url_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{b:02X}")),
+        }
+    }
+
+    encoded
+}
+
+/// Returns the given value as a quoted JSON string.
+pub fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+
+    escaped.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str(r#"\""#),
+            '\\' => escaped.push_str(r"\\"),
+            '\n' => escaped.push_str(r"\n"),
+            '\r' => escaped.push_str(r"\r"),
+            '\t' => escaped.push_str(r"\t"),
+            '\u{08}' => escaped.push_str(r"\b"),
+            '\u{0c}' => escaped.push_str(r"\f"),
+            c if c.is_control() => escaped.push_str(&format!(r"\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+
+    escaped
+}
+
+/// Returns the given value as a quoted C string literal.
+/// Bytes outside of printable ASCII are written as octal
+/// escapes.
+pub fn c_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+
+    escaped.push('"');
+
+    for b in s.bytes() {
+        match b {
+            b'"' => escaped.push_str(r#"\""#),
+            b'\\' => escaped.push_str(r"\\"),
+            b'\n' => escaped.push_str(r"\n"),
+            b'\r' => escaped.push_str(r"\r"),
+            b'\t' => escaped.push_str(r"\t"),
+            b' '..=b'~' => escaped.push(b as char),
+            b => escaped.push_str(&format!(r"\{b:03o}")),
+        }
+    }
+
+    escaped.push('"');
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{c_escape, json_escape, url_encode};
+    use test_case::test_case;
+
+    #[test_case("./a.txt", "./a.txt" ; "unreserved")]
+    #[test_case("./a b.txt", "./a%20b.txt" ; "space")]
+    #[test_case("./ü?#%.txt", "./%C3%BC%3F%23%25.txt" ; "unicode and reserved")]
+    fn url(s: &str, expected: &str) {
+        assert_eq!(expected, url_encode(s))
+    }
+    #[test_case("./a.txt", r#""./a.txt""# ; "plain")]
+    #[test_case("./\"a\"\\b.txt", r#""./\"a\"\\b.txt""# ; "quotes and backslash")]
+    #[test_case("./a\nb\u{1}.txt", r#""./a\nb\u0001.txt""# ; "control characters")]
+    #[test_case("./ü.txt", r#""./ü.txt""# ; "unicode")]
+    fn json(s: &str, expected: &str) {
+        assert_eq!(expected, json_escape(s))
+    }
+    #[test_case("./a.txt", r#""./a.txt""# ; "plain")]
+    #[test_case("./\"a\"\\b.txt", r#""./\"a\"\\b.txt""# ; "quotes and backslash")]
+    #[test_case("./a\tb\u{1}.txt", r#""./a\tb\001.txt""# ; "control characters")]
+    #[test_case("./ü.txt", r#""./\303\274.txt""# ; "unicode")]
+    fn c(s: &str, expected: &str) {
+        assert_eq!(expected, c_escape(s))
+    }
+}
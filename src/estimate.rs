@@ -0,0 +1,370 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::hash::BuildHasher;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use walkdir::DirEntry;
+
+/// Quantile of the standard normal distribution used
+/// for 95% confidence intervals.
+const Z_95: f64 = 1.96;
+
+/// The fraction of subdirectories sampled when estimating,
+/// given either as a ratio, e.g. '0.1', or a percentage,
+/// e.g. '10%'.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampleFraction(f64);
+
+impl FromStr for SampleFraction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fraction = match s.strip_suffix('%') {
+            Some(pct) => pct.trim().parse::<f64>()? / 100.0,
+            None => s.trim().parse::<f64>()?,
+        };
+
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            anyhow::bail!(
+                "sample fraction '{}' must be greater than 0 and at most 1",
+                s
+            );
+        }
+
+        Ok(Self(fraction))
+    }
+}
+
+/// The immediate subdirectories of a root directory which
+/// were selected for sampling. Each subdirectory forms a
+/// cluster whose matches are either all visited or skipped.
+#[derive(Debug)]
+pub struct RootSample {
+    root: PathBuf,
+    total: usize,
+    sampled: HashSet<OsString>,
+}
+
+impl RootSample {
+    /// Selects a random fraction of the subdirectories of the
+    /// given root, rounded up so that at least one is sampled
+    /// if any exist. Unreadable roots have no subdirectories
+    /// and are reported once they are walked.
+    pub fn select(root: &Path, fraction: SampleFraction) -> Self {
+        let names = fs::read_dir(root)
+            .map(|rd| {
+                rd.filter_map(|r| r.ok())
+                    .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                    .map(|e| e.file_name())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let state = RandomState::new();
+
+        Self::from_names(root, names, fraction, |name| state.hash_one(name))
+    }
+    fn from_names(
+        root: &Path,
+        mut names: Vec<OsString>,
+        fraction: SampleFraction,
+        key: impl Fn(&OsStr) -> u64,
+    ) -> Self {
+        let total = names.len();
+        let count = (total as f64 * fraction.0).ceil() as usize;
+
+        names.sort_by_cached_key(|n| key(n));
+        names.truncate(count);
+
+        Self {
+            root: root.to_path_buf(),
+            total,
+            sampled: names.into_iter().collect(),
+        }
+    }
+    /// Reports whether the given entry is a subdirectory of
+    /// the root which was not selected for sampling.
+    pub fn excludes(&self, ent: &DirEntry) -> bool {
+        ent.depth() == 1 && ent.file_type().is_dir() && !self.sampled.contains(ent.file_name())
+    }
+    /// Returns the sampled subdirectory containing the given
+    /// path or 'None' if it lies directly within the root.
+    fn cluster<'p>(&self, path: &'p Path) -> Option<Option<&'p OsStr>> {
+        let mut components = path.strip_prefix(&self.root).ok()?.components();
+
+        Some(match components.next() {
+            Some(Component::Normal(name)) if self.sampled.contains(name) => Some(name),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Tally {
+    count: u64,
+    size: u64,
+}
+
+impl Tally {
+    fn add(&mut self, size: u64) {
+        self.count += 1;
+        self.size += size;
+    }
+}
+
+/// Accumulates matches found in the sampled portion of a
+/// search in order to extrapolate totals for every root.
+pub struct Estimator<'a> {
+    samples: &'a [RootSample],
+    direct: Vec<Tally>,
+    clusters: Vec<HashMap<OsString, Tally>>,
+}
+
+impl<'a> Estimator<'a> {
+    pub fn new(samples: &'a [RootSample]) -> Self {
+        Self {
+            samples,
+            direct: vec![Tally::default(); samples.len()],
+            clusters: vec![HashMap::new(); samples.len()],
+        }
+    }
+    /// Records a match of the given size.
+    pub fn record(&mut self, path: &Path, size: u64) {
+        let found = self
+            .samples
+            .iter()
+            .enumerate()
+            .find_map(|(i, s)| s.cluster(path).map(|c| (i, c)));
+
+        match found {
+            Some((i, Some(name))) => self.clusters[i]
+                .entry(name.to_os_string())
+                .or_default()
+                .add(size),
+            Some((i, None)) => self.direct[i].add(size),
+            None => (),
+        }
+    }
+    /// Extrapolates the total number and size of matches.
+    /// Matches directly within a root are counted exactly
+    /// while those in subdirectories are estimated from the
+    /// sampled subdirectories.
+    pub fn estimate(&self) -> Estimate {
+        let mut est = Estimate {
+            count: Interval::exact(0.0),
+            size: Interval::exact(0.0),
+            sampled: 0,
+            total: 0,
+        };
+
+        for (i, sample) in self.samples.iter().enumerate() {
+            let tallies: Vec<Tally> = sample
+                .sampled
+                .iter()
+                .map(|n| self.clusters[i].get(n).copied().unwrap_or_default())
+                .collect();
+
+            est.count = est.count.add(
+                self.direct[i].count as f64,
+                extrapolate(sample.total, tallies.iter().map(|t| t.count as f64)),
+            );
+            est.size = est.size.add(
+                self.direct[i].size as f64,
+                extrapolate(sample.total, tallies.iter().map(|t| t.size as f64)),
+            );
+            est.sampled += tallies.len();
+            est.total += sample.total;
+        }
+
+        est
+    }
+}
+
+/// Estimates the total of 'total' clusters from the totals
+/// of a simple random sample of them along with the variance
+/// of the estimate. The variance is unknown when fewer than
+/// two of several clusters were sampled.
+fn extrapolate(total: usize, sampled: impl Iterator<Item = f64>) -> (f64, Option<f64>) {
+    let values: Vec<f64> = sampled.collect();
+    let n = values.len() as f64;
+    let big_n = total as f64;
+
+    if values.is_empty() {
+        return (0.0, Some(0.0));
+    }
+
+    let mean = values.iter().sum::<f64>() / n;
+
+    if values.len() == total {
+        return (big_n * mean, Some(0.0));
+    }
+    if values.len() < 2 {
+        return (big_n * mean, None);
+    }
+
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+
+    (
+        big_n * mean,
+        Some(big_n * big_n * (1.0 - n / big_n) * var / n),
+    )
+}
+
+/// An estimated value along with the variance of the
+/// estimate if it is known.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    value: f64,
+    variance: Option<f64>,
+}
+
+impl Interval {
+    fn exact(value: f64) -> Self {
+        Self {
+            value,
+            variance: Some(0.0),
+        }
+    }
+    fn add(self, exact: f64, (value, variance): (f64, Option<f64>)) -> Self {
+        Self {
+            value: self.value + exact + value,
+            variance: self.variance.zip(variance).map(|(a, b)| a + b),
+        }
+    }
+    /// Returns the half width of the 95% confidence interval.
+    fn margin(&self) -> Option<f64> {
+        self.variance.map(|v| Z_95 * v.sqrt())
+    }
+}
+
+/// The result of extrapolating a sampled search.
+#[derive(Debug)]
+pub struct Estimate {
+    pub count: Interval,
+    pub size: Interval,
+    pub sampled: usize,
+    pub total: usize,
+}
+
+impl Estimate {
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        write_interval(out, "estimated matches", &self.count, "")?;
+        write_interval(out, "estimated size", &self.size, " bytes")?;
+        writeln!(
+            out,
+            "sampled {} of {} directories",
+            self.sampled, self.total
+        )
+    }
+}
+
+fn write_interval(
+    out: &mut impl Write,
+    label: &str,
+    interval: &Interval,
+    unit: &str,
+) -> io::Result<()> {
+    match interval.margin() {
+        Some(m) => writeln!(
+            out,
+            "{}: {:.0} ± {:.0}{} (95% confidence)",
+            label, interval.value, m, unit
+        ),
+        None => writeln!(
+            out,
+            "{}: {:.0}{} (too few directories sampled for a confidence interval)",
+            label, interval.value, unit
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extrapolate, Estimator, RootSample, SampleFraction};
+    use anyhow::Result;
+    use std::ffi::OsString;
+    use std::path::Path;
+    use test_case::test_case;
+
+    #[test_case("0.1", Some(0.1) ; "ratio")]
+    #[test_case("1", Some(1.0) ; "whole")]
+    #[test_case("25%", Some(0.25) ; "percentage")]
+    #[test_case("0", None ; "zero")]
+    #[test_case("1.5", None ; "more than whole")]
+    #[test_case("150%", None ; "more than whole percentage")]
+    #[test_case("NaN", None ; "not a number")]
+    #[test_case("some", None ; "invalid")]
+    fn sample_fraction(input: &str, expected: Option<f64>) {
+        assert_eq!(
+            expected.map(SampleFraction),
+            input.parse::<SampleFraction>().ok()
+        )
+    }
+
+    #[test_case(4, 0.5, 2 ; "half")]
+    #[test_case(3, 0.5, 2 ; "rounded up")]
+    #[test_case(10, 0.01, 1 ; "at least one")]
+    #[test_case(0, 0.5, 0 ; "no subdirectories")]
+    fn select(total: usize, fraction: f64, expected: usize) {
+        let names = (0..total).map(|i| OsString::from(i.to_string())).collect();
+        let sample =
+            RootSample::from_names(Path::new("root"), names, SampleFraction(fraction), |n| {
+                n.len() as u64
+            });
+
+        assert_eq!(total, sample.total);
+        assert_eq!(expected, sample.sampled.len());
+    }
+
+    #[test_case(&[], 4, (0.0, Some(0.0)) ; "nothing sampled")]
+    #[test_case(&[1.0, 3.0], 2, (4.0, Some(0.0)) ; "all sampled")]
+    #[test_case(&[2.0], 4, (8.0, None) ; "single sample")]
+    #[test_case(&[1.0, 3.0], 4, (8.0, Some(8.0)) ; "partial sample")]
+    fn extrapolation(sampled: &[f64], total: usize, expected: (f64, Option<f64>)) {
+        assert_eq!(expected, extrapolate(total, sampled.iter().copied()))
+    }
+
+    #[test]
+    fn estimate() -> Result<()> {
+        let names = ["a", "b", "c", "d"].map(OsString::from).to_vec();
+        let samples = [RootSample::from_names(
+            Path::new("root"),
+            names,
+            SampleFraction(0.5),
+            |n| u64::from(n != "a" && n != "b"),
+        )];
+        let mut estimator = Estimator::new(&samples);
+
+        estimator.record(Path::new("root"), 10);
+        estimator.record(Path::new("root/file"), 10);
+        estimator.record(Path::new("root/a"), 1);
+        estimator.record(Path::new("root/a/file"), 1);
+        estimator.record(Path::new("root/b/file"), 2);
+        estimator.record(Path::new("other/file"), 100);
+
+        let est = estimator.estimate();
+
+        assert_eq!(2.0 + 4.0 / 2.0 * 3.0, est.count.value);
+        assert_eq!(20.0 + 4.0 / 2.0 * 4.0, est.size.value);
+        assert_eq!((2, 4), (est.sampled, est.total));
+
+        let mut out = Vec::new();
+
+        est.write(&mut out)?;
+
+        assert_eq!(
+            "estimated matches: 8 ± 3 (95% confidence)\n\
+             estimated size: 28 ± 0 bytes (95% confidence)\n\
+             sampled 2 of 4 directories\n",
+            String::from_utf8(out)?
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::escape::json_escape;
+use crate::sink::{Budget, OutputSink, SinkEntry};
+use crate::Summary;
+use anyhow::Result;
+use std::io::Write;
+
+/// Version of the event protocol.
+pub const VERSION: u32 = 1;
+
+/// Writes every result, error, progress update and the
+/// summary of a search as newline delimited JSON events.
+/// Every line is an object whose 'type' field names one of
+/// the following events:
+///
+/// - '{"type":"start","version":1}' is always written first.
+///   The version is incremented whenever existing events or
+///   fields change meaning. New events and fields may be added
+///   without a version change and should be ignored by readers
+///   which do not know them.
+/// - '{"type":"match","path":"..."}' for every result along
+///   with a 'score' field when ranked with '--score-cleanup'.
+/// - '{"type":"error","message":"..."}' for every error which
+///   did not abort the search, regardless of '--show-errors'.
+/// - '{"type":"warning","message":"..."}' for conditions which
+///   did not fail the search but may affect its results, e.g.
+///   filesystems mounted during '--one-file-system' searches.
+/// - '{"type":"progress","visited":N}' at most once per second
+///   with the number of entries visited so far.
+/// - '{"type":"summary","matches":N,"errors":N,"truncated":N}'
+///   is always written last unless the search was aborted.
+///
+/// Only match events count towards '--max-output' so that
+/// errors and the summary are never omitted.
+pub struct EventSink<W: Write> {
+    out: W,
+    budget: Budget,
+}
+
+impl<W: Write> EventSink<W> {
+    pub(crate) fn new(out: W, budget: Budget) -> Self {
+        Self { out, budget }
+    }
+}
+
+impl<W: Write> OutputSink for EventSink<W> {
+    fn begin(&mut self) -> Result<()> {
+        Ok(writeln!(
+            self.out,
+            r#"{{"type":"start","version":{VERSION}}}"#
+        )?)
+    }
+    fn write_entry(&mut self, ent: &SinkEntry) -> Result<bool> {
+        let line = match ent.score {
+            Some(score) => format!(
+                r#"{{"type":"match","path":{},"score":{}}}"#,
+                json_escape(ent.path),
+                score
+            ),
+            None => format!(r#"{{"type":"match","path":{}}}"#, json_escape(ent.path)),
+        };
+
+        if !self.budget.charge(line.len() + 1) {
+            return Ok(false);
+        }
+
+        writeln!(self.out, "{line}")?;
+
+        Ok(true)
+    }
+    fn write_error(&mut self, message: &str) -> Result<()> {
+        Ok(writeln!(
+            self.out,
+            r#"{{"type":"error","message":{}}}"#,
+            json_escape(message)
+        )?)
+    }
+    fn write_warning(&mut self, message: &str) -> Result<()> {
+        Ok(writeln!(
+            self.out,
+            r#"{{"type":"warning","message":{}}}"#,
+            json_escape(message)
+        )?)
+    }
+    fn write_progress(&mut self, visited: usize) -> Result<()> {
+        writeln!(self.out, r#"{{"type":"progress","visited":{visited}}}"#)?;
+
+        // progress is only useful to readers as it happens
+        Ok(self.out.flush()?)
+    }
+    fn write_summary(&mut self, summary: &Summary) -> Result<()> {
+        Ok(writeln!(
+            self.out,
+            r#"{{"type":"summary","matches":{},"errors":{},"truncated":{}}}"#,
+            summary.matches, summary.errors, summary.truncated
+        )?)
+    }
+    fn finish(&mut self, _omitted: usize) -> Result<()> {
+        Ok(self.out.flush()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventSink;
+    use crate::sink::{Budget, OutputSink, SinkEntry};
+    use crate::Summary;
+    use anyhow::Result;
+
+    #[test]
+    fn events() -> Result<()> {
+        let mut out = Vec::new();
+        let mut sink = EventSink::new(&mut out, Budget::new(None));
+
+        sink.begin()?;
+        sink.write_entry(&SinkEntry {
+            path: "./a \"b\".txt",
+            metadata: None,
+            score: None,
+        })?;
+        sink.write_error("permission denied")?;
+        sink.write_warning("mount table changed")?;
+        sink.write_progress(10)?;
+        sink.write_summary(&Summary {
+            matches: 1,
+            errors: 1,
+            truncated: 0,
+        })?;
+        sink.finish(0)?;
+
+        assert_eq!(
+            concat!(
+                "{\"type\":\"start\",\"version\":1}\n",
+                "{\"type\":\"match\",\"path\":\"./a \\\"b\\\".txt\"}\n",
+                "{\"type\":\"error\",\"message\":\"permission denied\"}\n",
+                "{\"type\":\"warning\",\"message\":\"mount table changed\"}\n",
+                "{\"type\":\"progress\",\"visited\":10}\n",
+                "{\"type\":\"summary\",\"matches\":1,\"errors\":1,\"truncated\":0}\n",
+            ),
+            String::from_utf8(out)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_output() -> Result<()> {
+        let mut out = Vec::new();
+        let mut sink = EventSink::new(&mut out, Budget::new(Some(40)));
+        let written: Vec<bool> = ["./a", "./b", "./c"]
+            .into_iter()
+            .map(|path| {
+                sink.write_entry(&SinkEntry {
+                    path,
+                    metadata: None,
+                    score: None,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        sink.write_summary(&Summary {
+            matches: 3,
+            errors: 0,
+            truncated: 2,
+        })?;
+
+        assert_eq!(vec![true, false, false], written);
+        assert_eq!(
+            concat!(
+                "{\"type\":\"match\",\"path\":\"./a\"}\n",
+                "{\"type\":\"summary\",\"matches\":3,\"errors\":0,\"truncated\":2}\n",
+            ),
+            String::from_utf8(out)?
+        );
+
+        Ok(())
+    }
+}
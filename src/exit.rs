@@ -0,0 +1,144 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Summary;
+use clap::ValueEnum;
+use std::io::{self, Write};
+
+/// Offset added to the number of the signal which
+/// terminated a search to form the exit code.
+pub const SIGNAL_OFFSET: usize = 128;
+
+/// Exit codes reported when '--strict-exit' is enabled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExitCode {
+    Success,
+    NoMatches,
+    Usage,
+    PartialErrors,
+    Truncated,
+    Locked,
+}
+
+impl ExitCode {
+    pub const ALL: [Self; 6] = [
+        Self::Success,
+        Self::NoMatches,
+        Self::Usage,
+        Self::PartialErrors,
+        Self::Truncated,
+        Self::Locked,
+    ];
+
+    pub fn code(self) -> i32 {
+        match self {
+            Self::Success => 0,
+            Self::NoMatches => 1,
+            Self::Usage => 2,
+            Self::PartialErrors => 3,
+            Self::Truncated => 4,
+            Self::Locked => 5,
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::NoMatches => "no-matches",
+            Self::Usage => "usage",
+            Self::PartialErrors => "partial-errors",
+            Self::Truncated => "truncated",
+            Self::Locked => "locked",
+        }
+    }
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Success => "at least one match was found",
+            Self::NoMatches => "the search completed without any matches",
+            Self::Usage => "invalid arguments or root directories were given",
+            Self::PartialErrors => "errors were encountered during the search",
+            Self::Truncated => "results were omitted due to '--max-output'",
+            Self::Locked => "the lock given with '--lock' is held by another search",
+        }
+    }
+}
+
+impl From<&Summary> for ExitCode {
+    fn from(summary: &Summary) -> Self {
+        if summary.errors > 0 {
+            Self::PartialErrors
+        } else if summary.truncated > 0 {
+            Self::Truncated
+        } else if summary.matches == 0 {
+            Self::NoMatches
+        } else {
+            Self::Success
+        }
+    }
+}
+
+/// Returns the exit code used when terminated by the given signal.
+pub fn signal_code(sig: usize) -> i32 {
+    (sig + SIGNAL_OFFSET).try_into().unwrap()
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+pub enum ExitCodesFormat {
+    Json,
+    Text,
+}
+
+pub fn write_exit_codes(out: &mut impl Write, format: &ExitCodesFormat) -> io::Result<()> {
+    match format {
+        ExitCodesFormat::Json => {
+            let codes = ExitCode::ALL
+                .iter()
+                .map(|c| {
+                    format!(
+                        r#"{{"code":{},"name":"{}","description":"{}"}}"#,
+                        c.code(),
+                        c.name(),
+                        c.description()
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(",");
+
+            writeln!(
+                out,
+                r#"{{"codes":[{codes}],"signal_offset":{SIGNAL_OFFSET}}}"#
+            )
+        }
+        ExitCodesFormat::Text => {
+            for c in ExitCode::ALL {
+                writeln!(out, "{}\t{}\t{}", c.code(), c.name(), c.description())?
+            }
+
+            writeln!(out, "{SIGNAL_OFFSET}+N\tsignal\tterminated by signal N")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExitCode;
+    use crate::Summary;
+    use test_case::test_case;
+
+    #[test_case(summary(1, 0, 0), ExitCode::Success ; "matches found")]
+    #[test_case(summary(0, 0, 0), ExitCode::NoMatches ; "no matches")]
+    #[test_case(summary(1, 1, 0), ExitCode::PartialErrors ; "matches with errors")]
+    #[test_case(summary(0, 1, 0), ExitCode::PartialErrors ; "no matches with errors")]
+    #[test_case(summary(2, 0, 1), ExitCode::Truncated ; "truncated matches")]
+    #[test_case(summary(2, 1, 1), ExitCode::PartialErrors ; "truncated matches with errors")]
+    fn from_summary(summary: Summary, expected: ExitCode) {
+        assert_eq!(expected, ExitCode::from(&summary))
+    }
+    fn summary(matches: usize, errors: usize, truncated: usize) -> Summary {
+        Summary {
+            matches,
+            errors,
+            truncated,
+        }
+    }
+}
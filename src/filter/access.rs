@@ -0,0 +1,84 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::ffi::CString;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// Access rights of the invoking user as decided by the
+/// kernel with 'access(2)' so that ACLs, capabilities and
+/// read-only mounts are taken into account unlike when
+/// inspecting mode bits. Symlinks are resolved first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+}
+
+impl Access {
+    /// Entries which vanished are reported as errors while
+    /// dangling symlinks are not permitted any access.
+    pub fn permits(self, path: &Path) -> io::Result<bool> {
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        let mode = match self {
+            Self::Read => libc::R_OK,
+            Self::Write => libc::W_OK,
+            Self::Execute => libc::X_OK,
+        };
+
+        // SAFETY: the path is NUL terminated.
+        if unsafe { libc::access(c_path.as_ptr(), mode) } == 0 {
+            return Ok(true);
+        }
+
+        let err = io::Error::last_os_error();
+
+        match err.raw_os_error() {
+            Some(libc::EACCES | libc::EROFS | libc::ETXTBSY | libc::ELOOP) => Ok(false),
+            Some(libc::ENOENT) if path.symlink_metadata().is_ok() => Ok(false),
+            _ => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Access;
+    use anyhow::Result;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use test_case::test_case;
+
+    #[test_case(0o644, Access::Read, true ; "readable")]
+    #[test_case(0o644, Access::Write, true ; "writable")]
+    #[test_case(0o644, Access::Execute, false ; "not executable")]
+    #[test_case(0o755, Access::Execute, true ; "executable")]
+    fn permits(mode: u32, access: Access, expected: bool) -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+
+        fs::set_permissions(file.path(), fs::Permissions::from_mode(mode))?;
+
+        assert_eq!(expected, access.permits(file.path())?);
+
+        Ok(file.close()?)
+    }
+
+    #[test]
+    fn missing_path() {
+        assert!(Access::Read.permits("dne".as_ref()).is_err())
+    }
+
+    #[test]
+    fn dangling_symlink() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+
+        std::os::unix::fs::symlink("dne", dir.path().join("dangling"))?;
+
+        assert!(!Access::Read.permits(&dir.path().join("dangling"))?);
+
+        Ok(dir.close()?)
+    }
+}
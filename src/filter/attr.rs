@@ -0,0 +1,253 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+pub use imp::{AttrFilter, ProjectIdFilter};
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::ffi::CString;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use std::str::FromStr;
+
+    /// Inode flags by their 'lsattr' letter as defined in
+    /// 'linux/fs.h'.
+    const FLAGS: &[(char, u32)] = &[
+        ('s', 0x0000_0001),
+        ('u', 0x0000_0002),
+        ('c', 0x0000_0004),
+        ('S', 0x0000_0008),
+        ('i', 0x0000_0010),
+        ('a', 0x0000_0020),
+        ('d', 0x0000_0040),
+        ('A', 0x0000_0080),
+        ('E', 0x0000_0800),
+        ('I', 0x0000_1000),
+        ('j', 0x0000_4000),
+        ('t', 0x0000_8000),
+        ('D', 0x0001_0000),
+        ('T', 0x0002_0000),
+        ('e', 0x0008_0000),
+        ('V', 0x0010_0000),
+        ('C', 0x0080_0000),
+        ('x', 0x0200_0000),
+        ('N', 0x1000_0000),
+        ('P', 0x2000_0000),
+        ('F', 0x4000_0000),
+    ];
+
+    /// '_IOR('f', 1, long)' which reads the flags as an int
+    /// despite its declared size.
+    const FS_IOC_GETFLAGS: u64 = 0x8000_6601 | (std::mem::size_of::<libc::c_long>() as u64) << 16;
+
+    /// '_IOR('X', 31, struct fsxattr)'.
+    const FS_IOC_FSGETXATTR: u64 = 0x8000_581f | (std::mem::size_of::<FsXattr>() as u64) << 16;
+
+    /// 'struct fsxattr' as defined in 'linux/fs.h'.
+    #[repr(C)]
+    #[derive(Default)]
+    struct FsXattr {
+        xflags: u32,
+        extsize: u32,
+        nextents: u32,
+        projid: u32,
+        cowextsize: u32,
+        pad: [u8; 8],
+    }
+
+    /// Matches entries carrying all of the given inode flags
+    /// by their 'lsattr' letters, e.g. 'i' for immutable or
+    /// 'ia' for immutable and append-only.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct AttrFilter(u32);
+
+    impl AttrFilter {
+        /// Opens the given path so it must not refer to a
+        /// device or pipe. Symlinks never match.
+        pub fn matches(&self, path: &Path) -> io::Result<bool> {
+            let flags = ioctl_read::<libc::c_int>(path, FS_IOC_GETFLAGS)?;
+
+            Ok(flags.is_some_and(|f| f as u32 & self.0 == self.0))
+        }
+    }
+
+    /// Matches entries assigned to the given XFS or ext4
+    /// project quota id.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct ProjectIdFilter(u32);
+
+    impl ProjectIdFilter {
+        /// Opens the given path like 'AttrFilter::matches'.
+        pub fn matches(&self, path: &Path) -> io::Result<bool> {
+            let attr = ioctl_read::<FsXattr>(path, FS_IOC_FSGETXATTR)?;
+
+            Ok(attr.is_some_and(|a| a.projid == self.0))
+        }
+    }
+
+    impl FromStr for ProjectIdFilter {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Ok(Self(s.parse()?))
+        }
+    }
+
+    /// Issues an ioctl reading a 'T' from the given path
+    /// without following symlinks. Entries which do not
+    /// support the request are reported as 'None'.
+    fn ioctl_read<T: Default>(path: &Path, request: u64) -> io::Result<Option<T>> {
+        let path = CString::new(path.as_os_str().as_bytes())?;
+        // SAFETY: the path is NUL terminated.
+        let fd = unsafe {
+            libc::open(
+                path.as_ptr(),
+                libc::O_RDONLY | libc::O_NONBLOCK | libc::O_NOFOLLOW | libc::O_CLOEXEC,
+            )
+        };
+
+        if fd < 0 {
+            return unsupported();
+        }
+
+        let mut value = T::default();
+        // SAFETY: the descriptor is open and the request
+        // writes a single 'T'.
+        let res = unsafe { libc::ioctl(fd, request as _, &mut value as *mut T) };
+        let read = if res < 0 {
+            unsupported()
+        } else {
+            Ok(Some(value))
+        };
+
+        // SAFETY: the descriptor is open and closed only once.
+        unsafe { libc::close(fd) };
+
+        read
+    }
+
+    fn unsupported<T>() -> io::Result<Option<T>> {
+        let err = io::Error::last_os_error();
+
+        match err.raw_os_error() {
+            Some(libc::ELOOP | libc::ENOTTY | libc::EINVAL | libc::EOPNOTSUPP) => Ok(None),
+            _ => Err(err),
+        }
+    }
+
+    impl FromStr for AttrFilter {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            if s.is_empty() {
+                anyhow::bail!("invalid inode flags '{}'", s);
+            }
+
+            s.chars()
+                .map(|c| {
+                    FLAGS
+                        .iter()
+                        .find(|(l, _)| *l == c)
+                        .map(|(_, f)| f)
+                        .ok_or_else(|| anyhow::anyhow!("invalid inode flag '{}'", c))
+                })
+                .try_fold(0, |acc, f| Ok(acc | f?))
+                .map(Self)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{AttrFilter, ProjectIdFilter};
+        use crate::filter::testing::*;
+        use anyhow::{anyhow, Result};
+        use test_case::test_case;
+
+        #[test_case("i", Ok(AttrFilter(0x10)) ; "immutable")]
+        #[test_case("ia", Ok(AttrFilter(0x30)) ; "immutable and append-only")]
+        #[test_case("A", Ok(AttrFilter(0x80)) ; "upper case letter")]
+        #[test_case("", Err(anyhow!("")) ; "empty")]
+        #[test_case("iz", Err(anyhow!("")) ; "unknown letter")]
+        fn from_str(s: &str, expected: Result<AttrFilter>) {
+            assert_from_str(s, expected)
+        }
+
+        #[test]
+        fn unflagged() -> Result<()> {
+            let file = tempfile::NamedTempFile::new()?;
+
+            assert!(!"i".parse::<AttrFilter>()?.matches(file.path())?);
+
+            Ok(file.close()?)
+        }
+
+        #[test_case("0", Ok(ProjectIdFilter(0)) ; "project id")]
+        #[test_case("-1", Err(anyhow!("")) ; "negative project id")]
+        fn project_id_from_str(s: &str, expected: Result<ProjectIdFilter>) {
+            assert_from_str(s, expected)
+        }
+
+        #[test]
+        fn project_id() -> Result<()> {
+            let file = tempfile::NamedTempFile::new()?;
+
+            // files without a project are assigned id 0 where
+            // project quotas are supported
+            assert!(!ProjectIdFilter(4242).matches(file.path())?);
+
+            Ok(file.close()?)
+        }
+
+        #[test]
+        fn missing_path() {
+            assert!(AttrFilter(0x10).matches("dne".as_ref()).is_err())
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use std::io;
+    use std::path::Path;
+    use std::str::FromStr;
+
+    /// Stands in for the inode flag filter on platforms other
+    /// than Linux.
+    #[derive(Clone, Debug)]
+    pub enum AttrFilter {}
+
+    impl AttrFilter {
+        pub fn matches(&self, _: &Path) -> io::Result<bool> {
+            match *self {}
+        }
+    }
+
+    impl FromStr for AttrFilter {
+        type Err = anyhow::Error;
+
+        fn from_str(_: &str) -> Result<Self, Self::Err> {
+            anyhow::bail!("'--attr' is only supported on Linux")
+        }
+    }
+
+    /// Stands in for the project id filter on platforms other
+    /// than Linux.
+    #[derive(Clone, Debug)]
+    pub enum ProjectIdFilter {}
+
+    impl ProjectIdFilter {
+        pub fn matches(&self, _: &Path) -> io::Result<bool> {
+            match *self {}
+        }
+    }
+
+    impl FromStr for ProjectIdFilter {
+        type Err = anyhow::Error;
+
+        fn from_str(_: &str) -> Result<Self, Self::Err> {
+            anyhow::bail!("'--project-id' is only supported on Linux")
+        }
+    }
+}
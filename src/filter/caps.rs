@@ -0,0 +1,224 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::security::read_xattr;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Capability names indexed by their number as defined in
+/// 'linux/capability.h'.
+const NAMES: &[&str] = &[
+    "chown",
+    "dac_override",
+    "dac_read_search",
+    "fowner",
+    "fsetid",
+    "kill",
+    "setgid",
+    "setuid",
+    "setpcap",
+    "linux_immutable",
+    "net_bind_service",
+    "net_broadcast",
+    "net_admin",
+    "net_raw",
+    "ipc_lock",
+    "ipc_owner",
+    "sys_module",
+    "sys_rawio",
+    "sys_chroot",
+    "sys_ptrace",
+    "sys_pacct",
+    "sys_admin",
+    "sys_boot",
+    "sys_nice",
+    "sys_resource",
+    "sys_time",
+    "sys_tty_config",
+    "mknod",
+    "lease",
+    "audit_write",
+    "audit_control",
+    "setfcap",
+    "mac_override",
+    "mac_admin",
+    "syslog",
+    "wake_alarm",
+    "block_suspend",
+    "audit_read",
+    "perfmon",
+    "bpf",
+    "checkpoint_restore",
+];
+
+const REVISION_MASK: u32 = 0xff00_0000;
+const REVISION_1: u32 = 0x0100_0000;
+const REVISION_2: u32 = 0x0200_0000;
+const REVISION_3: u32 = 0x0300_0000;
+const FLAG_EFFECTIVE: u32 = 0x0000_0001;
+
+/// File capabilities as stored in the 'security.capability'
+/// extended attribute.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FileCaps {
+    permitted: u64,
+    inheritable: u64,
+    effective: bool,
+}
+
+impl FileCaps {
+    /// Reads the capabilities of the given path without
+    /// following symlinks. Entries without capabilities
+    /// are reported as 'None'.
+    pub fn read(path: &Path) -> io::Result<Option<Self>> {
+        read_xattr(path, "security.capability")?
+            .map(|data| Self::parse(&data))
+            .transpose()
+    }
+    fn parse(data: &[u8]) -> io::Result<Self> {
+        let words = data
+            .chunks_exact(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+            .collect::<Vec<u32>>();
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid file capabilities");
+        let magic = *words.first().ok_or_else(invalid)?;
+        // magic followed by permitted and inheritable words
+        // for each 32 capabilities and the root id for v3
+        let (sets, len) = match magic & REVISION_MASK {
+            REVISION_1 => (1, 3),
+            REVISION_2 => (2, 5),
+            REVISION_3 => (2, 6),
+            _ => return Err(invalid()),
+        };
+
+        if data.len() != len * 4 {
+            return Err(invalid());
+        }
+
+        let set = |offset: usize| {
+            (0..sets).fold(0u64, |acc, i| {
+                acc | u64::from(words[1 + offset + 2 * i]) << (32 * i)
+            })
+        };
+
+        Ok(Self {
+            permitted: set(0),
+            inheritable: set(1),
+            effective: magic & FLAG_EFFECTIVE != 0,
+        })
+    }
+}
+
+/// Matches files carrying the given capability, e.g.
+/// 'cap_net_raw' or 'net_raw', optionally requiring it to
+/// be in all of the given sets like 'cap_net_raw+ep' with
+/// 'e', 'p' and 'i' referring to the effective, permitted
+/// and inheritable sets respectively.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapsFilter {
+    cap: u32,
+    effective: bool,
+    permitted: bool,
+    inheritable: bool,
+}
+
+impl CapsFilter {
+    pub fn matches(&self, caps: &FileCaps) -> bool {
+        let bit = 1 << self.cap;
+        let permitted = caps.permitted & bit != 0;
+        let inheritable = caps.inheritable & bit != 0;
+
+        let sets = [
+            (self.permitted, permitted),
+            (self.inheritable, inheritable),
+            (self.effective, caps.effective && permitted),
+        ];
+
+        (permitted || inheritable) && sets.iter().all(|(required, set)| !required || *set)
+    }
+}
+
+impl FromStr for CapsFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_ascii_lowercase();
+        let (name, flags) = lower.split_once('+').unwrap_or((&lower, ""));
+        let name = name.strip_prefix("cap_").unwrap_or(name);
+        let cap = NAMES
+            .iter()
+            .position(|n| *n == name)
+            .ok_or_else(|| anyhow::anyhow!("invalid capability '{}'", s))?;
+
+        if flags.chars().any(|c| !"epi".contains(c)) {
+            anyhow::bail!("invalid capability flags in '{}'", s);
+        }
+
+        Ok(Self {
+            cap: cap as u32,
+            effective: flags.contains('e'),
+            permitted: flags.contains('p'),
+            inheritable: flags.contains('i'),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CapsFilter, FileCaps};
+    use crate::filter::testing::*;
+    use anyhow::{anyhow, Result};
+    use test_case::test_case;
+
+    fn filter(cap: u32, flags: &str) -> CapsFilter {
+        CapsFilter {
+            cap,
+            effective: flags.contains('e'),
+            permitted: flags.contains('p'),
+            inheritable: flags.contains('i'),
+        }
+    }
+
+    fn words(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+
+    #[test_case("cap_net_raw", Ok(filter(13, "")) ; "name")]
+    #[test_case("net_raw", Ok(filter(13, "")) ; "name without prefix")]
+    #[test_case("CAP_NET_RAW+ep", Ok(filter(13, "ep")) ; "upper case name with flags")]
+    #[test_case("cap_bpf+i", Ok(filter(39, "i")) ; "upper set")]
+    #[test_case("cap_dne", Err(anyhow!("")) ; "unknown name")]
+    #[test_case("cap_net_raw+x", Err(anyhow!("")) ; "unknown flag")]
+    fn from_str(s: &str, expected: Result<CapsFilter>) {
+        assert_from_str(s, expected)
+    }
+
+    #[test_case(&words(&[0x0200_0001, 1 << 13, 0, 0, 0]), Some((1 << 13, 0, true)) ; "v2 effective")]
+    #[test_case(&words(&[0x0300_0000, 0, 0, 1 << 7, 0, 0]), Some((1 << 39, 0, false)) ; "v3 upper set")]
+    #[test_case(&words(&[0x0100_0000, 1, 2]), Some((1, 2, false)) ; "v1")]
+    #[test_case(&words(&[0x0200_0000, 1]), None ; "truncated")]
+    #[test_case(&words(&[0x0400_0000, 0, 0, 0, 0]), None ; "unknown revision")]
+    #[test_case(&[], None ; "empty")]
+    fn parse(data: &[u8], expected: Option<(u64, u64, bool)>) {
+        assert_eq!(
+            expected,
+            FileCaps::parse(data)
+                .ok()
+                .map(|c| (c.permitted, c.inheritable, c.effective))
+        )
+    }
+
+    #[test_case("cap_net_raw", true ; "name")]
+    #[test_case("cap_net_raw+ep", true ; "effective and permitted")]
+    #[test_case("cap_net_raw+i", false ; "not inheritable")]
+    #[test_case("cap_chown", false ; "other capability")]
+    fn matches(spec: &str, expected: bool) -> Result<()> {
+        let caps = FileCaps::parse(&words(&[0x0200_0001, 1 << 13, 0, 0, 0]))?;
+
+        assert_eq!(expected, spec.parse::<CapsFilter>()?.matches(&caps));
+
+        Ok(())
+    }
+}
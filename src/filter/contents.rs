@@ -0,0 +1,115 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use regex::bytes::Regex;
+use std::fs;
+use std::io::{self, BufRead, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Longest piece of a file held in memory at once.
+const MAX_LINE: u64 = 64 * 1024;
+
+/// Matches files whose contents match a regular expression
+/// line by line like 'grep'. Lines longer than 'MAX_LINE'
+/// are matched in pieces so that memory use stays bounded
+/// regardless of the file, which means matches spanning
+/// the end of a piece are missed.
+#[derive(Clone, Debug)]
+pub struct ContentFilter(Regex);
+
+impl ContentFilter {
+    /// Reads the given file until the first match or until
+    /// 'limit' bytes were read when given.
+    pub fn matches(&self, path: &Path, limit: Option<u64>) -> io::Result<bool> {
+        let mut reader = io::BufReader::new(fs::File::open(path)?.take(limit.unwrap_or(u64::MAX)));
+        let mut line = Vec::new();
+
+        loop {
+            line.clear();
+
+            if (&mut reader).take(MAX_LINE).read_until(b'\n', &mut line)? == 0 {
+                return Ok(false);
+            }
+            if self.0.is_match(line.strip_suffix(b"\n").unwrap_or(&line)) {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+impl FromStr for ContentFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.parse()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ContentFilter, MAX_LINE};
+    use anyhow::Result;
+    use std::fs;
+    use test_case::test_case;
+
+    #[test_case("needle", b"hay\nneedle\nhay\n", true ; "matching line")]
+    #[test_case("needle", b"hay\nhay\n", false ; "no match")]
+    #[test_case("^needle$", b"hay\nneedle", true ; "anchored without trailing newline")]
+    #[test_case("hay.needle", b"hay\nneedle\n", false ; "lines matched separately")]
+    #[test_case("needle", b"\x00\xffneedle", true ; "binary contents")]
+    #[test_case("needle", b"", false ; "empty")]
+    fn matches(pattern: &str, contents: &[u8], expected: bool) -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+
+        fs::write(file.path(), contents)?;
+
+        assert_eq!(
+            expected,
+            pattern
+                .parse::<ContentFilter>()?
+                .matches(file.path(), None)?
+        );
+
+        Ok(file.close()?)
+    }
+
+    #[test]
+    fn long_line() -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        let mut contents = vec![b'x'; 3 * MAX_LINE as usize];
+
+        contents.extend_from_slice(b"needle");
+        fs::write(file.path(), contents)?;
+
+        assert!("needle"
+            .parse::<ContentFilter>()?
+            .matches(file.path(), None)?);
+
+        Ok(file.close()?)
+    }
+
+    #[test_case(Some(3), false ; "match beyond limit")]
+    #[test_case(Some(10), true ; "match within limit")]
+    #[test_case(Some(8), false ; "match cut off by limit")]
+    fn limit(limit: Option<u64>, expected: bool) -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+
+        fs::write(file.path(), b"hay\nneedle\n")?;
+
+        assert_eq!(
+            expected,
+            "needle"
+                .parse::<ContentFilter>()?
+                .matches(file.path(), limit)?
+        );
+
+        Ok(file.close()?)
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!("(".parse::<ContentFilter>().is_err())
+    }
+}
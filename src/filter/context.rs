@@ -0,0 +1,99 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+pub use imp::ContextFilter;
+
+#[cfg(all(feature = "selinux", target_os = "linux"))]
+mod imp {
+    use crate::security::read_xattr;
+    use regex::Regex;
+    use std::path::Path;
+    use std::str::FromStr;
+
+    /// Matches entries whose SELinux security context, e.g.
+    /// 'system_u:object_r:bin_t:s0', matches the given regular
+    /// expression. Entries without a context never match.
+    #[derive(Clone, Debug)]
+    pub struct ContextFilter(Regex);
+
+    impl ContextFilter {
+        pub fn matches(&self, path: &Path) -> anyhow::Result<bool> {
+            Ok(match read_xattr(path, "security.selinux")? {
+                Some(ctx) => self
+                    .0
+                    .is_match(String::from_utf8_lossy(&ctx).trim_end_matches('\0')),
+                None => false,
+            })
+        }
+    }
+
+    impl FromStr for ContextFilter {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Ok(Self(Regex::new(s)?))
+        }
+    }
+}
+
+#[cfg(not(all(feature = "selinux", target_os = "linux")))]
+mod imp {
+    use std::path::Path;
+    use std::str::FromStr;
+
+    /// Stands in for the SELinux context filter when findr is
+    /// built without the 'selinux' feature or for a platform
+    /// other than Linux.
+    #[derive(Clone, Debug)]
+    pub enum ContextFilter {}
+
+    impl ContextFilter {
+        pub fn matches(&self, _: &Path) -> anyhow::Result<bool> {
+            match *self {}
+        }
+    }
+
+    impl FromStr for ContextFilter {
+        type Err = anyhow::Error;
+
+        fn from_str(_: &str) -> Result<Self, Self::Err> {
+            anyhow::bail!(
+                "'--context' requires findr to be built with the 'selinux' feature on Linux"
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContextFilter;
+
+    #[cfg(all(feature = "selinux", target_os = "linux"))]
+    #[test_case::test_case("[", false ; "invalid regex")]
+    #[test_case::test_case("_t:", true ; "valid regex")]
+    fn from_str(s: &str, expected: bool) {
+        assert_eq!(expected, s.parse::<ContextFilter>().is_ok())
+    }
+
+    #[cfg(all(feature = "selinux", target_os = "linux"))]
+    #[test]
+    fn unlabeled() -> anyhow::Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        let labeled = ::xattr::get(file.path(), "security.selinux")?.is_some();
+
+        // SELinux enabled hosts label every file
+        assert_eq!(
+            labeled,
+            ".*".parse::<ContextFilter>()?.matches(file.path())?
+        );
+
+        Ok(file.close()?)
+    }
+
+    #[cfg(not(all(feature = "selinux", target_os = "linux")))]
+    #[test]
+    fn without_feature() {
+        assert!("_t:".parse::<ContextFilter>().is_err())
+    }
+}
@@ -0,0 +1,92 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+/// Compares a count such as the number of hard links or a
+/// numeric id using the same prefixes and ranges as
+/// '--size'.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CountFilter {
+    Less(u64),
+    Greater(u64),
+    Equal(u64),
+    /// Counts from the first up to and including the second,
+    /// given as e.g. '1000..2000'.
+    Between(u64, u64),
+}
+
+impl CountFilter {
+    pub fn matches(&self, n: u64) -> bool {
+        match self {
+            Self::Equal(c) => n == *c,
+            Self::Less(c) => n < *c,
+            Self::Greater(c) => n > *c,
+            Self::Between(from, to) => n >= *from && n <= *to,
+        }
+    }
+}
+
+impl FromStr for CountFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if let Some((from, to)) = s.split_once("..") {
+            let (from, to) = (parse_count(from)?, parse_count(to)?);
+
+            Self::Between(from.min(to), from.max(to))
+        } else if let Some(maybe_int) = s.strip_prefix('-') {
+            Self::Less(parse_count(maybe_int)?)
+        } else if let Some(maybe_int) = s.strip_prefix('+') {
+            Self::Greater(parse_count(maybe_int)?)
+        } else {
+            Self::Equal(parse_count(s)?)
+        })
+    }
+}
+
+// u64's FromStr accepts a leading '+' which would let
+// prefixes stack or appear within ranges.
+fn parse_count(s: &str) -> anyhow::Result<u64> {
+    if s.starts_with('+') {
+        anyhow::bail!("invalid count '{}'", s);
+    }
+
+    Ok(s.parse()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CountFilter;
+    use crate::filter::testing::*;
+    use anyhow::{anyhow, Result};
+    use test_case::test_case;
+
+    #[test_case("1", Ok(CountFilter::Equal(1)) ; "equals")]
+    #[test_case("+1", Ok(CountFilter::Greater(1)) ; "greater than")]
+    #[test_case("-2", Ok(CountFilter::Less(2)) ; "less than")]
+    #[test_case("1000..2000", Ok(CountFilter::Between(1000, 2000)) ; "range")]
+    #[test_case("2000..1000", Ok(CountFilter::Between(1000, 2000)) ; "reversed range")]
+    #[test_case("+1..2", Err(anyhow!("")) ; "range with prefix")]
+    #[test_case("1..", Err(anyhow!("")) ; "open range")]
+    #[test_case("1k", Err(anyhow!("")) ; "suffix")]
+    #[test_case("+-1", Err(anyhow!("")) ; "several prefixes")]
+    #[test_case("++1", Err(anyhow!("")) ; "repeated prefix")]
+    fn from_str(s: &str, expected: Result<CountFilter>) {
+        assert_from_str(s, expected)
+    }
+
+    #[test_case(CountFilter::Equal(1), 1, true ; "equals")]
+    #[test_case(CountFilter::Greater(1), 2, true ; "greater than")]
+    #[test_case(CountFilter::Less(2), 1, true ; "less than")]
+    #[test_case(CountFilter::Equal(1), 2, false ; "not equals")]
+    #[test_case(CountFilter::Greater(1), 1, false ; "not greater than")]
+    #[test_case(CountFilter::Less(2), 2, false ; "not less than")]
+    #[test_case(CountFilter::Between(1, 2), 1, true ; "lower end of range")]
+    #[test_case(CountFilter::Between(1, 2), 2, true ; "upper end of range")]
+    #[test_case(CountFilter::Between(1, 2), 3, false ; "above range")]
+    fn matches(f: CountFilter, n: u64, expected: bool) {
+        assert_eq!(expected, f.matches(n))
+    }
+}
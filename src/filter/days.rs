@@ -0,0 +1,71 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::filter::CountFilter;
+use std::str::FromStr;
+use std::time;
+
+const DAY: i64 = 24 * 60 * 60;
+
+/// Compares timestamps by the number of whole 24-hour
+/// periods since they passed like 'find -mtime', so that
+/// '1' matches timestamps between one and two days ago,
+/// '+1' those at least two days ago and '-1' those less
+/// than a day ago.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DaysFilter(CountFilter);
+
+impl DaysFilter {
+    pub fn matches(&self, instant: u64) -> anyhow::Result<bool> {
+        let now = time::SystemTime::now().duration_since(time::UNIX_EPOCH)?;
+
+        Ok(self.matches_age(now.as_secs() as i64 - instant as i64))
+    }
+    fn matches_age(&self, secs: i64) -> bool {
+        // timestamps in the future are less than any number
+        // of days ago
+        match u64::try_from(secs.div_euclid(DAY)) {
+            Ok(days) => self.0.matches(days),
+            Err(_) => matches!(self.0, CountFilter::Less(_)),
+        }
+    }
+}
+
+impl FromStr for DaysFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.parse()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DaysFilter, DAY};
+    use anyhow::Result;
+    use test_case::test_case;
+
+    #[test_case("1", DAY + 1, true ; "within day")]
+    #[test_case("1", 2 * DAY - 1, true ; "end of day")]
+    #[test_case("1", 2 * DAY, false ; "next day")]
+    #[test_case("1", DAY - 1, false ; "previous day")]
+    #[test_case("+1", 2 * DAY, true ; "more than one day")]
+    #[test_case("+1", 2 * DAY - 1, false ; "rounded down to one day")]
+    #[test_case("-1", DAY - 1, true ; "less than one day")]
+    #[test_case("-1", DAY, false ; "not less than one day")]
+    #[test_case("0", 0, true ; "today")]
+    #[test_case("-0", -1, true ; "future less than zero days")]
+    #[test_case("0", -1, false ; "future not today")]
+    #[test_case("1..3", 3 * DAY + 1, true ; "range")]
+    fn matches_age(s: &str, secs: i64, expected: bool) -> Result<()> {
+        assert_eq!(expected, s.parse::<DaysFilter>()?.matches_age(secs));
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_str() {
+        assert!("1d".parse::<DaysFilter>().is_err())
+    }
+}
@@ -0,0 +1,98 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::filter::CountFilter;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Matches directories by their number of direct entries
+/// using the same prefixes and ranges as '--links'.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntriesFilter(CountFilter);
+
+impl EntriesFilter {
+    /// Returns how many entries need to be counted to decide
+    /// whether a directory matches.
+    pub fn limit(&self) -> u64 {
+        match self.0 {
+            CountFilter::Less(n) => n,
+            CountFilter::Greater(n) | CountFilter::Equal(n) | CountFilter::Between(_, n) => {
+                n.saturating_add(1)
+            }
+        }
+    }
+    pub fn matches(&self, n: u64) -> bool {
+        self.0.matches(n)
+    }
+}
+
+impl FromStr for EntriesFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.parse()?))
+    }
+}
+
+/// Counts the direct entries of a directory, stopping once
+/// 'limit' entries were seen so that huge directories are
+/// not read in full when a smaller count suffices.
+pub fn count_entries(path: &Path, limit: u64) -> io::Result<u64> {
+    let mut count = 0;
+
+    for ent in fs::read_dir(path)? {
+        if count == limit {
+            break;
+        }
+
+        ent?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_entries, EntriesFilter};
+    use crate::filter::CountFilter;
+    use anyhow::Result;
+    use std::fs;
+    use test_case::test_case;
+
+    #[test_case("-3", 3 ; "less than")]
+    #[test_case("+3", 4 ; "greater than")]
+    #[test_case("3", 4 ; "equals")]
+    #[test_case("1..3", 4 ; "range")]
+    fn limit(s: &str, expected: u64) -> Result<()> {
+        assert_eq!(expected, s.parse::<EntriesFilter>()?.limit());
+
+        Ok(())
+    }
+
+    #[test_case(u64::MAX, 3 ; "all entries")]
+    #[test_case(2, 2 ; "limited")]
+    #[test_case(0, 0 ; "nothing")]
+    fn count(limit: u64, expected: u64) -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+
+        for name in ["a", "b", "c"] {
+            fs::write(dir.path().join(name), "")?;
+        }
+
+        assert_eq!(expected, count_entries(dir.path(), limit)?);
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn limited_count_decides() {
+        let f = EntriesFilter(CountFilter::Greater(2));
+
+        assert!(f.matches(f.limit()));
+        assert!(!EntriesFilter(CountFilter::Less(2)).matches(2));
+    }
+}
@@ -6,61 +6,130 @@ use humantime::Duration;
 use std::str::FromStr;
 use std::time;
 
+/// The point in time a timestamp is compared against given
+/// either relative to now as a duration, e.g. '7d', or as
+/// an absolute UTC timestamp, e.g. '2024-01-01' or
+/// '2024-01-01T12:00:00Z'.
 #[derive(Clone, Debug, PartialEq)]
-pub enum DurationFilter {
-    Less(time::Duration),
-    Greater(time::Duration),
+pub enum Boundary {
+    Ago(time::Duration),
+    /// Seconds since the epoch.
+    At(u64),
 }
 
-impl DurationFilter {
+impl Boundary {
+    fn resolve(&self, now: time::Duration) -> time::Duration {
+        match self {
+            Self::Ago(d) => now.saturating_sub(*d),
+            Self::At(secs) => time::Duration::from_secs(*secs),
+        }
+    }
+}
+
+impl FromStr for Boundary {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(d) = s.parse::<Duration>() {
+            return Ok(Self::Ago(d.into()));
+        }
+
+        // dates alone refer to the start of the day
+        let timestamp = if s.len() == "YYYY-MM-DD".len() {
+            humantime::parse_rfc3339_weak(&format!("{s} 00:00:00"))
+        } else {
+            humantime::parse_rfc3339_weak(s)
+        };
+
+        match timestamp {
+            Ok(t) => Ok(Self::At(t.duration_since(time::UNIX_EPOCH)?.as_secs())),
+            Err(_) => anyhow::bail!("invalid duration or timestamp '{}'", s),
+        }
+    }
+}
+
+/// Compares timestamps against a boundary. 'Greater' matches
+/// timestamps further in the past than the boundary and
+/// 'Less' those more recent. 'Between' matches timestamps
+/// within a window given as 'A..B', e.g. '1d..7d', including
+/// both ends regardless of their order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimeFilter {
+    Less(Boundary),
+    Greater(Boundary),
+    Between(Boundary, Boundary),
+}
+
+impl TimeFilter {
     pub fn matches(&self, instant: u64) -> anyhow::Result<bool> {
         let now = time::SystemTime::now().duration_since(time::UNIX_EPOCH)?;
         let instant = time::Duration::from_secs(instant);
 
         Ok(match self {
-            Self::Less(d) => now - *d < instant,
-            Self::Greater(d) => now - *d > instant,
+            Self::Less(b) => b.resolve(now) < instant,
+            Self::Greater(b) => b.resolve(now) > instant,
+            Self::Between(a, b) => {
+                let (a, b) = (a.resolve(now), b.resolve(now));
+
+                a.min(b) <= instant && instant <= a.max(b)
+            }
         })
     }
 }
 
-impl FromStr for DurationFilter {
+impl FromStr for TimeFilter {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(if let Some(maybe_dur) = s.strip_prefix('-') {
-            Self::Less(maybe_dur.parse::<Duration>()?.into())
-        } else if let Some(maybe_dur) = s.strip_prefix('+') {
-            Self::Greater(maybe_dur.parse::<Duration>()?.into())
+        Ok(if let Some((from, to)) = s.split_once("..") {
+            Self::Between(from.parse()?, to.parse()?)
+        } else if let Some(maybe_bound) = s.strip_prefix('-') {
+            Self::Less(maybe_bound.parse()?)
+        } else if let Some(maybe_bound) = s.strip_prefix('+') {
+            Self::Greater(maybe_bound.parse()?)
         } else {
-            Self::Greater(s.parse::<Duration>()?.into())
+            Self::Greater(s.parse()?)
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::DurationFilter;
+    use super::{Boundary, TimeFilter};
     use crate::filter::testing::*;
-    use anyhow;
     use std::time::{Duration, SystemTime, UNIX_EPOCH};
     use test_case::test_case;
 
     const DAY: Duration = Duration::from_secs(24 * 60 * 60);
 
-    #[test_case("1d", Ok(DurationFilter::Greater(DAY)) ; "greater than one day")]
-    #[test_case("+1d", Ok(DurationFilter::Greater(DAY)) ; "greater than one day '+' prefix")]
-    #[test_case("-1d", Ok(DurationFilter::Less(DAY)) ; "less than one day")]
+    #[test_case("1d", Ok(TimeFilter::Greater(Boundary::Ago(DAY))) ; "greater than one day")]
+    #[test_case("+1d", Ok(TimeFilter::Greater(Boundary::Ago(DAY))) ; "greater than one day '+' prefix")]
+    #[test_case("-1d", Ok(TimeFilter::Less(Boundary::Ago(DAY))) ; "less than one day")]
+    #[test_case("+2024-01-01", Ok(TimeFilter::Greater(Boundary::At(1704067200))) ; "before date")]
+    #[test_case("-2024-01-01T12:00:00Z", Ok(TimeFilter::Less(Boundary::At(1704110400))) ; "after timestamp")]
+    #[test_case("2024-01-01 12:00:00", Ok(TimeFilter::Greater(Boundary::At(1704110400))) ; "timestamp without zone")]
+    #[test_case("1d..7d", Ok(TimeFilter::Between(Boundary::Ago(DAY), Boundary::Ago(7 * DAY))) ; "range")]
+    #[test_case("2024-01-01..1d", Ok(TimeFilter::Between(Boundary::At(1704067200), Boundary::Ago(DAY))) ; "range since date")]
+    #[test_case("-1d..7d", Err(anyhow::anyhow!("")) ; "range with prefix")]
+    #[test_case("1d..", Err(anyhow::anyhow!("")) ; "open range")]
+    #[test_case("2024-13-01", Err(anyhow::anyhow!("")) ; "invalid date")]
     #[test_case("!1d", Err(anyhow::anyhow!("")) ; "invalid prefix")]
     #[test_case("3x", Err(anyhow::anyhow!("")) ; "invalid duration format")]
-    fn from_str(s: &str, expected: anyhow::Result<DurationFilter>) {
+    fn from_str(s: &str, expected: anyhow::Result<TimeFilter>) {
         assert_from_str(s, expected)
     }
-    #[test_case(DurationFilter::Greater(DAY), days_ago(2), Ok(true) ; "greater than one day")]
-    #[test_case(DurationFilter::Less(DAY), days_ago(0), Ok(true) ; "less than one day")]
-    #[test_case(DurationFilter::Greater(DAY), days_ago(0), Ok(false) ; "not greater than one day")]
-    #[test_case(DurationFilter::Less(DAY), days_ago(2), Ok(false) ; "not less than one day")]
-    fn matches(f: DurationFilter, instant: u64, expected: anyhow::Result<bool>) {
+    #[test_case(TimeFilter::Greater(Boundary::Ago(DAY)), days_ago(2), Ok(true) ; "greater than one day")]
+    #[test_case(TimeFilter::Less(Boundary::Ago(DAY)), days_ago(0), Ok(true) ; "less than one day")]
+    #[test_case(TimeFilter::Greater(Boundary::Ago(DAY)), days_ago(0), Ok(false) ; "not greater than one day")]
+    #[test_case(TimeFilter::Less(Boundary::Ago(DAY)), days_ago(2), Ok(false) ; "not less than one day")]
+    #[test_case(TimeFilter::Greater(Boundary::At(days_ago(1))), days_ago(2), Ok(true) ; "before timestamp")]
+    #[test_case(TimeFilter::Less(Boundary::At(days_ago(1))), days_ago(0), Ok(true) ; "after timestamp")]
+    #[test_case(TimeFilter::Greater(Boundary::At(days_ago(1))), days_ago(0), Ok(false) ; "not before timestamp")]
+    #[test_case(TimeFilter::Between(Boundary::Ago(DAY), Boundary::Ago(7 * DAY)), days_ago(2), Ok(true) ; "within range")]
+    #[test_case(TimeFilter::Between(Boundary::Ago(7 * DAY), Boundary::Ago(DAY)), days_ago(2), Ok(true) ; "within reversed range")]
+    #[test_case(TimeFilter::Between(Boundary::Ago(DAY), Boundary::Ago(7 * DAY)), days_ago(0), Ok(false) ; "after range")]
+    #[test_case(TimeFilter::Between(Boundary::Ago(DAY), Boundary::Ago(7 * DAY)), days_ago(8), Ok(false) ; "before range")]
+    fn matches(f: TimeFilter, instant: u64, expected: anyhow::Result<bool>) {
         let result = f.matches(instant);
 
         match expected {
@@ -0,0 +1,91 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Error;
+use anyhow::anyhow;
+use clap::ValueEnum;
+use std::io;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum ErrorPolicy {
+    #[default]
+    Report,
+    Skip,
+    Match,
+    Fail,
+}
+
+impl ErrorPolicy {
+    /// Decides the fate of an entry whose filter could not be evaluated.
+    pub fn apply<E>(self, ent: E, err: anyhow::Error) -> Option<anyhow::Result<E>> {
+        match self {
+            Self::Report => Some(Err(err)),
+            Self::Skip => None,
+            Self::Match => Some(Ok(ent)),
+            Self::Fail => Some(Err(anyhow!(Error::FilterEvaluation(err)))),
+        }
+    }
+}
+
+/// Decides how creation time filters treat entries on
+/// filesystems which do not expose creation times.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum BtimePolicy {
+    /// Entries never match.
+    Skip,
+    /// Entries fail to be filtered like any other error.
+    #[default]
+    Error,
+    /// Entries match as if no creation time filter was given.
+    IgnoreFilter,
+}
+
+impl BtimePolicy {
+    /// Decides whether an entry whose creation time could not
+    /// be read matches. Errors other than missing support are
+    /// passed on.
+    pub fn apply(self, err: anyhow::Error) -> anyhow::Result<bool> {
+        let unsupported = err
+            .downcast_ref::<io::Error>()
+            .is_some_and(|e| e.kind() == io::ErrorKind::Unsupported);
+
+        match self {
+            Self::Skip if unsupported => Ok(false),
+            Self::IgnoreFilter if unsupported => Ok(true),
+            _ => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BtimePolicy, ErrorPolicy};
+    use crate::Error;
+    use std::io;
+    use test_case::test_case;
+
+    #[test_case(ErrorPolicy::Report, Some(Err(false)) ; "report")]
+    #[test_case(ErrorPolicy::Skip, None ; "skip")]
+    #[test_case(ErrorPolicy::Match, Some(Ok("entry")) ; "match")]
+    #[test_case(ErrorPolicy::Fail, Some(Err(true)) ; "fail")]
+    fn apply(p: ErrorPolicy, expected: Option<Result<&str, bool>>) {
+        let result = p
+            .apply("entry", anyhow::anyhow!("permission denied"))
+            .map(|r| r.map_err(|e| e.is::<Error>()));
+
+        assert_eq!(expected, result)
+    }
+
+    #[test_case(BtimePolicy::Skip, io::ErrorKind::Unsupported, Some(false) ; "skip unsupported")]
+    #[test_case(BtimePolicy::IgnoreFilter, io::ErrorKind::Unsupported, Some(true) ; "ignore filter when unsupported")]
+    #[test_case(BtimePolicy::Error, io::ErrorKind::Unsupported, None ; "error when unsupported")]
+    #[test_case(BtimePolicy::Skip, io::ErrorKind::PermissionDenied, None ; "skip other error")]
+    #[test_case(BtimePolicy::IgnoreFilter, io::ErrorKind::PermissionDenied, None ; "ignore filter other error")]
+    fn btime(p: BtimePolicy, kind: io::ErrorKind, expected: Option<bool>) {
+        assert_eq!(
+            expected,
+            p.apply(io::Error::new(kind, "creation time").into()).ok()
+        )
+    }
+}
@@ -0,0 +1,71 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::Path;
+use std::str::FromStr;
+
+/// Matches results whose file name ends with the given
+/// extension, e.g. 'rs' or 'tar.gz'. A leading '.' is
+/// optional. Names consisting of only the extension such
+/// as '.rs' have no extension and do not match.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtensionFilter(String);
+
+impl ExtensionFilter {
+    pub fn matches(&self, path: &str) -> bool {
+        Path::new(path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(&self.0))
+            .and_then(|n| n.strip_suffix('.'))
+            .is_some_and(|stem| !stem.is_empty())
+    }
+}
+
+impl FromStr for ExtensionFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ext = s.strip_prefix('.').unwrap_or(s);
+
+        if ext.is_empty() || ext.contains('/') {
+            anyhow::bail!("invalid extension '{}'", s);
+        }
+
+        Ok(Self(ext.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExtensionFilter;
+    use crate::filter::testing::*;
+    use anyhow::{anyhow, Result};
+    use test_case::test_case;
+
+    #[test_case("rs", Ok(ExtensionFilter("rs".into())) ; "extension")]
+    #[test_case(".rs", Ok(ExtensionFilter("rs".into())) ; "leading dot")]
+    #[test_case("tar.gz", Ok(ExtensionFilter("tar.gz".into())) ; "multiple parts")]
+    #[test_case("", Err(anyhow!("")) ; "empty")]
+    #[test_case(".", Err(anyhow!("")) ; "only dot")]
+    #[test_case("a/b", Err(anyhow!("")) ; "separator")]
+    fn from_str(s: &str, expected: Result<ExtensionFilter>) {
+        assert_from_str(s, expected)
+    }
+
+    #[test_case("rs", "./src/main.rs", true ; "matches")]
+    #[test_case("rs", "./src/main.rsx", false ; "longer extension")]
+    #[test_case("rs", "./src/mainrs", false ; "no dot")]
+    #[test_case("rs", "./.rs", false ; "hidden file without extension")]
+    #[test_case("gz", "./a.tar.gz", true ; "last part")]
+    #[test_case("tar.gz", "./a.tar.gz", true ; "multiple parts")]
+    #[test_case("rs", "./src.rs/main", false ; "directory extension")]
+    #[test_case("rs", ".", false ; "root")]
+    fn matches(ext: &str, path: &str, expected: bool) {
+        assert_eq!(
+            expected,
+            ext.parse::<ExtensionFilter>().unwrap().matches(path)
+        )
+    }
+}
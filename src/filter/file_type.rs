@@ -2,19 +2,47 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::entry::Entry;
+use crate::entry::{Entry, FileKind};
+use crate::filter::Access;
 use clap::ValueEnum;
-use is_executable::is_executable;
-use std::os::unix::fs::FileTypeExt;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, ValueEnum)]
+/// Number of bytes sampled to tell text from binary files.
+const BLOCK_SIZE: usize = 8 * 1024;
+
+#[derive(Debug, Clone, PartialEq, ValueEnum)]
 pub enum TypeFilter {
+    #[value(alias = "b")]
+    BlockDevice,
+    /// Symlinks whose target does not exist.
+    #[value(alias = "bl")]
+    BrokenSymLink,
+    #[value(alias = "c")]
+    CharDevice,
     #[value(alias = "d")]
     Dir,
+    /// Empty regular files and directories.
+    Empty,
+    EmptyDir,
+    NonEmptyDir,
     #[value(alias = "x")]
     Executable,
+    /// Directories on a different device than their parent.
+    #[value(alias = "mountpoint")]
+    MountPoint,
     #[value(alias = "f")]
     File,
+    /// Regular files whose first block is valid UTF-8
+    /// without NUL bytes.
+    Text,
+    /// Regular files which are not text.
+    Binary,
+    NonEmptyFile,
     #[value(alias = "p")]
     Pipe,
     #[value(alias = "s")]
@@ -24,16 +52,188 @@ pub enum TypeFilter {
 }
 
 impl TypeFilter {
-    pub fn matches(&self, ent: &impl Entry) -> bool {
+    /// Reports whether the entry is of this type reading at
+    /// most 'read_limit' bytes to tell text from binary files.
+    pub fn matches(&self, ent: &impl Entry, read_limit: Option<u64>) -> anyhow::Result<bool> {
         let ftype = ent.file_type();
 
-        match self {
-            Self::Dir => ftype.is_dir(),
-            Self::Executable => is_executable(ent.path()),
-            Self::File => ftype.is_file(),
-            Self::Pipe => ftype.is_fifo(),
-            Self::Socket => ftype.is_socket(),
-            Self::SymLink => ftype.is_symlink(),
-        }
+        Ok(match self {
+            Self::BlockDevice => ftype == FileKind::BlockDevice,
+            Self::BrokenSymLink => ftype == FileKind::SymLink && is_dangling(ent)?,
+            Self::CharDevice => ftype == FileKind::CharDevice,
+            Self::Dir => ftype == FileKind::Dir,
+            Self::Empty => match ftype {
+                FileKind::File => ent.size()? == 0,
+                FileKind::Dir => is_empty_dir(ent)?,
+                _ => false,
+            },
+            Self::EmptyDir => ftype == FileKind::Dir && is_empty_dir(ent)?,
+            Self::NonEmptyDir => ftype == FileKind::Dir && !is_empty_dir(ent)?,
+            Self::Executable => is_executable(ent)?,
+            Self::MountPoint => ftype == FileKind::Dir && is_mount_point(ent)?,
+            Self::File => ftype == FileKind::File,
+            Self::Text => ftype == FileKind::File && !is_binary(ent.fs_path(), read_limit)?,
+            Self::Binary => ftype == FileKind::File && is_binary(ent.fs_path(), read_limit)?,
+            Self::NonEmptyFile => ftype == FileKind::File && ent.size()? > 0,
+            Self::Pipe => ftype == FileKind::Pipe,
+            Self::Socket => ftype == FileKind::Socket,
+            Self::SymLink => ftype == FileKind::SymLink,
+        })
+    }
+}
+
+/// Reports whether the invoking user may execute the given
+/// entry, or the target of symlinks, as a regular file.
+fn is_executable(ent: &impl Entry) -> anyhow::Result<bool> {
+    let path = ent.fs_path();
+
+    Ok(Access::Execute.permits(path)? && fs::metadata(path)?.is_file())
+}
+
+/// Samples the first block of the given file like 'grep'
+/// so that only a bounded amount is read. A multi-byte
+/// character cut off at the end of the block is not
+/// considered invalid.
+fn is_binary(path: &Path, read_limit: Option<u64>) -> io::Result<bool> {
+    let mut block = Vec::with_capacity(BLOCK_SIZE);
+
+    fs::File::open(path)?
+        .take(read_limit.map_or(BLOCK_SIZE as u64, |l| l.min(BLOCK_SIZE as u64)))
+        .read_to_end(&mut block)?;
+
+    Ok(block.contains(&0) || std::str::from_utf8(&block).is_err_and(|e| e.error_len().is_some()))
+}
+
+fn is_mount_point(ent: &impl Entry) -> anyhow::Result<bool> {
+    let parent = ent.fs_path().join("..");
+
+    Ok(ent.dev()? != fs::symlink_metadata(parent)?.dev())
+}
+
+fn is_empty_dir(ent: &impl Entry) -> anyhow::Result<bool> {
+    Ok(fs::read_dir(ent.fs_path())?.next().is_none())
+}
+
+/// Reports whether resolving the given symlink fails as
+/// its target, or a directory leading to it, is missing or
+/// it is part of a loop of symlinks.
+fn is_dangling(ent: &impl Entry) -> anyhow::Result<bool> {
+    match fs::metadata(ent.fs_path()) {
+        Ok(_) => Ok(false),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(true),
+        Err(e) if matches!(e.raw_os_error(), Some(libc::ELOOP | libc::ENOTDIR)) => Ok(true),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Matches entries which either are or are not of the
+/// given type. Negation is expressed with a leading '!'.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeSelector {
+    Is(TypeFilter),
+    IsNot(TypeFilter),
+}
+
+impl TypeSelector {
+    pub fn is_negated(&self) -> bool {
+        matches!(self, Self::IsNot(_))
+    }
+    pub fn matches(&self, ent: &impl Entry, read_limit: Option<u64>) -> anyhow::Result<bool> {
+        Ok(match self {
+            Self::Is(t) => t.matches(ent, read_limit)?,
+            Self::IsNot(t) => !t.matches(ent, read_limit)?,
+        })
+    }
+}
+
+impl fmt::Display for TypeSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, t) = match self {
+            Self::Is(t) => ("", t),
+            Self::IsNot(t) => ("!", t),
+        };
+        let name = t.to_possible_value().map(|v| v.get_name().to_string());
+
+        write!(f, "{}{}", prefix, name.unwrap_or_default())
+    }
+}
+
+impl FromStr for TypeSelector {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if let Some(maybe_type) = s.strip_prefix('!') {
+            Self::IsNot(parse_type(maybe_type)?)
+        } else {
+            Self::Is(parse_type(s)?)
+        })
+    }
+}
+
+fn parse_type(s: &str) -> anyhow::Result<TypeFilter> {
+    TypeFilter::from_str(s, false).map_err(|e| anyhow::anyhow!(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_binary, TypeFilter, TypeSelector, BLOCK_SIZE};
+    use crate::filter::testing::*;
+    use anyhow::{anyhow, Result};
+    use std::fs;
+    use test_case::test_case;
+
+    #[test_case("d", Ok(TypeSelector::Is(TypeFilter::Dir)) ; "alias")]
+    #[test_case("b", Ok(TypeSelector::Is(TypeFilter::BlockDevice)) ; "block device alias")]
+    #[test_case("bl", Ok(TypeSelector::Is(TypeFilter::BrokenSymLink)) ; "broken symlink alias")]
+    #[test_case("char-device", Ok(TypeSelector::Is(TypeFilter::CharDevice)) ; "char device")]
+    #[test_case("empty", Ok(TypeSelector::Is(TypeFilter::Empty)) ; "empty")]
+    #[test_case("empty-dir", Ok(TypeSelector::Is(TypeFilter::EmptyDir)) ; "empty dir")]
+    #[test_case("mountpoint", Ok(TypeSelector::Is(TypeFilter::MountPoint)) ; "mount point alias")]
+    #[test_case("!f", Ok(TypeSelector::IsNot(TypeFilter::File)) ; "negated alias")]
+    #[test_case("!non-empty-file", Ok(TypeSelector::IsNot(TypeFilter::NonEmptyFile)) ; "negated non-empty file")]
+    #[test_case("!!f", Err(anyhow!("")) ; "double negation")]
+    #[test_case("j", Err(anyhow!("")) ; "unknown type")]
+    fn from_str(s: &str, expected: Result<TypeSelector>) {
+        assert_from_str(s, expected)
+    }
+
+    #[test_case(b"plain\ntext\n", false ; "ascii")]
+    #[test_case("caf\u{e9}".as_bytes(), false ; "utf-8")]
+    #[test_case(b"", false ; "empty")]
+    #[test_case(b"text\x00", true ; "nul byte")]
+    #[test_case(b"caf\xe9 au lait", true ; "invalid utf-8")]
+    fn binary(contents: &[u8], expected: bool) -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+
+        fs::write(file.path(), contents)?;
+
+        assert_eq!(expected, is_binary(file.path(), None)?);
+
+        Ok(file.close()?)
+    }
+
+    #[test]
+    fn character_cut_off_by_block() -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        let mut contents = vec![b'x'; BLOCK_SIZE - 1];
+
+        contents.extend_from_slice("\u{e9}".as_bytes());
+        fs::write(file.path(), contents)?;
+
+        assert!(!is_binary(file.path(), None)?);
+
+        Ok(file.close()?)
+    }
+
+    #[test_case(None, true ; "whole block")]
+    #[test_case(Some(4), false ; "limited")]
+    fn binary_beyond_limit(read_limit: Option<u64>, expected: bool) -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+
+        fs::write(file.path(), b"text\x00")?;
+
+        assert_eq!(expected, is_binary(file.path(), read_limit)?);
+
+        Ok(file.close()?)
     }
 }
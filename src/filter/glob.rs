@@ -0,0 +1,95 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Shell style glob such as '*.rs' or '**/target/**'. Like
+/// 'find -name' globs without a '/' are matched against the
+/// file name while all others are matched against the path
+/// where '*' and '?' never match a '/'.
+#[derive(Clone, Debug)]
+pub struct GlobFilter {
+    glob: String,
+    matcher: GlobMatcher,
+}
+
+impl GlobFilter {
+    fn new(glob: &str, case_insensitive: bool) -> anyhow::Result<Self> {
+        Ok(Self {
+            glob: glob.to_string(),
+            matcher: GlobBuilder::new(glob)
+                .case_insensitive(case_insensitive)
+                .literal_separator(true)
+                .build()?
+                .compile_matcher(),
+        })
+    }
+    pub fn glob(&self) -> &str {
+        &self.glob
+    }
+    /// Returns the same glob ignoring case.
+    pub fn case_insensitive(&self) -> Self {
+        Self::new(&self.glob, true).expect("glob was already parsed")
+    }
+    pub fn is_match(&self, path: &str) -> bool {
+        let path = Path::new(path);
+
+        if self.glob.contains('/') {
+            self.matcher.is_match(path)
+        } else {
+            path.file_name().is_some_and(|n| self.matcher.is_match(n))
+        }
+    }
+}
+
+impl PartialEq for GlobFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.glob == other.glob
+    }
+}
+
+impl FromStr for GlobFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GlobFilter;
+    use test_case::test_case;
+
+    #[test_case("*.rs", "./src/main.rs", true ; "extension")]
+    #[test_case("*.rs", "./src/main.rs.bak", false ; "extension not at end")]
+    #[test_case("main.?s", "./src/main.rs", true ; "single character")]
+    #[test_case("src", "./src/main.rs", false ; "directory name")]
+    #[test_case("src", "./src", true ; "file name")]
+    #[test_case("[ab].txt", "./b.txt", true ; "character class")]
+    #[test_case("**/target/**", "./crate/target/debug/findr", true ; "nested directory")]
+    #[test_case("**/target/**", "./crate/src/target.rs", false ; "nested directory mismatch")]
+    #[test_case("./src/*", "./src/main.rs", true ; "relative path")]
+    #[test_case("./src/*", "./src/filter/glob.rs", false ; "relative path nested")]
+    #[test_case("./src/*/*.rs", "./src/filter/glob.rs", true ; "relative path one level")]
+    fn is_match(glob: &str, path: &str, expected: bool) {
+        assert_eq!(expected, glob.parse::<GlobFilter>().unwrap().is_match(path))
+    }
+
+    #[test_case("*.RS", "./src/main.rs" ; "extension")]
+    #[test_case("**/Target/**", "./crate/target/debug/findr" ; "path")]
+    fn case_insensitive(glob: &str, path: &str) {
+        let glob = glob.parse::<GlobFilter>().unwrap();
+
+        assert!(!glob.is_match(path));
+        assert!(glob.case_insensitive().is_match(path));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!("a[".parse::<GlobFilter>().is_err())
+    }
+}
@@ -0,0 +1,206 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::{Entry, HashMap};
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Streams the contents of the given file through the
+    /// hash so that memory use does not grow with its size.
+    /// At most 'limit' bytes are hashed when given.
+    pub fn digest(self, path: &Path, limit: Option<u64>) -> io::Result<Vec<u8>> {
+        let mut file = fs::File::open(path)?.take(limit.unwrap_or(u64::MAX));
+
+        Ok(match self {
+            Self::Md5 => {
+                let mut hasher = Md5::new();
+
+                io::copy(&mut file, &mut hasher)?;
+                hasher.finalize().to_vec()
+            }
+            Self::Sha256 => {
+                let mut hasher = Sha256::new();
+
+                io::copy(&mut file, &mut hasher)?;
+                hasher.finalize().to_vec()
+            }
+            Self::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+
+                io::copy(&mut file, &mut hasher)?;
+                hasher.finalize().as_bytes().to_vec()
+            }
+        })
+    }
+    fn len(self) -> usize {
+        match self {
+            Self::Md5 => 16,
+            Self::Sha256 | Self::Blake3 => 32,
+        }
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "md5" => Self::Md5,
+            "sha256" => Self::Sha256,
+            "blake3" => Self::Blake3,
+            _ => anyhow::bail!("invalid hash algorithm '{}'", s),
+        })
+    }
+}
+
+/// Matches files whose contents hash to the given digest
+/// written in hexadecimal, e.g. 'sha256=9f86d0...'.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HashFilter {
+    algorithm: HashAlgorithm,
+    digest: Vec<u8>,
+}
+
+impl HashFilter {
+    /// Reports whether the given file matches any of the
+    /// filters. Files are read once per algorithm used up
+    /// to 'limit' bytes.
+    pub fn matches_any(filters: &[Self], path: &Path, limit: Option<u64>) -> io::Result<bool> {
+        let mut digests = HashMap::new();
+
+        for f in filters {
+            let digest = match digests.entry(f.algorithm) {
+                Entry::Occupied(e) => e.into_mut(),
+                Entry::Vacant(e) => e.insert(f.algorithm.digest(path, limit)?),
+            };
+
+            if *digest == f.digest {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+impl FromStr for HashFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (algorithm, hex) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("hash '{}' must be given as 'ALGORITHM=DIGEST'", s))?;
+        let algorithm: HashAlgorithm = algorithm.parse()?;
+        let digest = parse_hex(hex)
+            .filter(|d| d.len() == algorithm.len())
+            .ok_or_else(|| anyhow::anyhow!("invalid digest '{}'", hex))?;
+
+        Ok(Self { algorithm, digest })
+    }
+}
+
+fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    // from_str_radix alone accepts a leading '+'
+    if !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_hex, HashAlgorithm, HashFilter};
+    use crate::filter::testing::*;
+    use anyhow::{anyhow, Result};
+    use std::fs;
+    use test_case::test_case;
+
+    const MD5_TEST: &str = "098f6bcd4621d373cade4e832627b4f6";
+    const SHA256_TEST: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+    const BLAKE3_TEST: &str = "4878ca0425c739fa427f7eda20fe845f6b2e46ba5fe2a14df5b1e32f50603215";
+
+    fn filter(algorithm: HashAlgorithm, hex: &str) -> HashFilter {
+        HashFilter {
+            algorithm,
+            digest: super::parse_hex(hex).unwrap(),
+        }
+    }
+
+    #[test_case("md5=098f6bcd4621d373cade4e832627b4f6", Ok(filter(HashAlgorithm::Md5, MD5_TEST)) ; "md5")]
+    #[test_case("SHA256=9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08", Ok(filter(HashAlgorithm::Sha256, SHA256_TEST)) ; "upper case")]
+    #[test_case("md5=098f6bcd", Err(anyhow!("")) ; "short digest")]
+    #[test_case("md5=098f6bcd4621d373cade4e832627b4fx", Err(anyhow!("")) ; "invalid hex")]
+    #[test_case("md5=098f6bcd4621d373cade4e832627b4f", Err(anyhow!("")) ; "odd length")]
+    #[test_case("md5=+98f6bcd4621d373cade4e832627b4f6", Err(anyhow!("")) ; "sign")]
+    #[test_case("crc32=d87f7e0c", Err(anyhow!("")) ; "unknown algorithm")]
+    #[test_case("098f6bcd4621d373cade4e832627b4f6", Err(anyhow!("")) ; "missing algorithm")]
+    fn from_str(s: &str, expected: Result<HashFilter>) {
+        assert_from_str(s, expected)
+    }
+
+    #[test_case("0aFf", Some(vec![0x0a, 0xff]) ; "mixed case")]
+    #[test_case("+a", None ; "plus sign")]
+    #[test_case("-a", None ; "minus sign")]
+    #[test_case("0 ", None ; "space")]
+    #[test_case("", Some(vec![]) ; "empty")]
+    fn hex(s: &str, expected: Option<Vec<u8>>) {
+        assert_eq!(expected, parse_hex(s))
+    }
+
+    #[test_case(HashAlgorithm::Md5, MD5_TEST ; "md5")]
+    #[test_case(HashAlgorithm::Sha256, SHA256_TEST ; "sha256")]
+    #[test_case(HashAlgorithm::Blake3, BLAKE3_TEST ; "blake3")]
+    fn matches(algorithm: HashAlgorithm, hex: &str) -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+
+        fs::write(file.path(), "test")?;
+
+        assert!(HashFilter::matches_any(
+            &[
+                filter(HashAlgorithm::Md5, &"0".repeat(32)),
+                filter(algorithm, hex)
+            ],
+            file.path(),
+            None
+        )?);
+        assert!(!HashFilter::matches_any(
+            &[filter(algorithm, &"0".repeat(hex.len()))],
+            file.path(),
+            None
+        )?);
+
+        Ok(file.close()?)
+    }
+
+    #[test_case(Some(4), true ; "within limit")]
+    #[test_case(Some(2), false ; "beyond limit")]
+    fn limit(limit: Option<u64>, expected: bool) -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+
+        fs::write(file.path(), "test")?;
+
+        assert_eq!(
+            expected,
+            HashFilter::matches_any(&[filter(HashAlgorithm::Md5, MD5_TEST)], file.path(), limit)?
+        );
+
+        Ok(file.close()?)
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::ffi::OsString;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Matches results lying anywhere beneath a directory with
+/// the given name, e.g. 'tests'. Whole path components are
+/// compared so that 'tests' does not match 'unit-tests'.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InDirFilter(OsString);
+
+impl InDirFilter {
+    pub fn matches(&self, path: &str) -> bool {
+        Path::new(path)
+            .parent()
+            .is_some_and(|p| p.iter().any(|c| c == self.0))
+    }
+}
+
+impl FromStr for InDirFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim_end_matches('/');
+
+        if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+            anyhow::bail!("invalid directory name '{}'", s);
+        }
+
+        Ok(Self(name.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InDirFilter;
+    use crate::filter::testing::*;
+    use anyhow::{anyhow, Result};
+    use test_case::test_case;
+
+    #[test_case("tests", Ok(InDirFilter("tests".into())) ; "name")]
+    #[test_case("tests/", Ok(InDirFilter("tests".into())) ; "trailing separator")]
+    #[test_case("", Err(anyhow!("")) ; "empty")]
+    #[test_case("a/b", Err(anyhow!("")) ; "several components")]
+    #[test_case("..", Err(anyhow!("")) ; "parent")]
+    fn from_str(s: &str, expected: Result<InDirFilter>) {
+        assert_from_str(s, expected)
+    }
+
+    #[test_case("tests", "./tests/a.rs", true ; "parent")]
+    #[test_case("tests", "./crate/tests/unit/a.rs", true ; "ancestor")]
+    #[test_case("tests", "./tests", false ; "directory itself")]
+    #[test_case("tests", "./unit-tests/a.rs", false ; "substring of component")]
+    #[test_case("tests", "./tests.rs", false ; "file name")]
+    #[test_case("tests", ".", false ; "root")]
+    fn matches(name: &str, path: &str, expected: bool) {
+        assert_eq!(expected, name.parse::<InDirFilter>().unwrap().matches(path))
+    }
+}
@@ -0,0 +1,112 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::ValueEnum;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Number of hops followed by default which matches the
+/// limit Linux applies before failing with 'ELOOP'.
+pub const DEFAULT_MAX_LINK_DEPTH: u32 = 40;
+
+/// Outcome of following a chain of symlinks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum LinkState {
+    /// The chain ends in an existing entry.
+    #[default]
+    Resolvable,
+    /// The chain ends in a missing entry.
+    Dangling,
+    /// The chain leads back to one of its links or is
+    /// longer than the maximum link depth.
+    Cyclic,
+}
+
+impl LinkState {
+    /// Follows the symlink at the given path for at most
+    /// 'max_depth' hops. Each link is read individually so
+    /// that chains are classified by their own length rather
+    /// than the kernel's limit.
+    pub fn classify(path: &Path, max_depth: u32) -> io::Result<Self> {
+        let mut visited = HashSet::new();
+        let mut current = PathBuf::from(path);
+
+        for _ in 0..=max_depth {
+            let meta = match fs::symlink_metadata(&current) {
+                Ok(meta) => meta,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::Dangling),
+                Err(e) if e.raw_os_error() == Some(libc::ENOTDIR) => return Ok(Self::Dangling),
+                // a symlinked directory leading to the target loops
+                Err(e) if e.raw_os_error() == Some(libc::ELOOP) => return Ok(Self::Cyclic),
+                Err(e) => return Err(e),
+            };
+
+            if !meta.is_symlink() {
+                return Ok(Self::Resolvable);
+            }
+            if !visited.insert((meta.dev(), meta.ino())) {
+                return Ok(Self::Cyclic);
+            }
+
+            let target = fs::read_link(&current)?;
+
+            current = match current.parent() {
+                Some(parent) => parent.join(target),
+                None => target,
+            };
+        }
+
+        Ok(Self::Cyclic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkState;
+    use anyhow::Result;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use test_case::test_case;
+
+    fn setup() -> Result<tempfile::TempDir> {
+        let dir = tempfile::TempDir::new()?;
+        let path = dir.path();
+
+        fs::write(path.join("file"), "")?;
+        fs::create_dir(path.join("sub"))?;
+        symlink("file", path.join("one"))?;
+        symlink("one", path.join("two"))?;
+        symlink("../two", path.join("sub").join("three"))?;
+        symlink("dne", path.join("dangling"))?;
+        symlink("file/dne", path.join("not_dir"))?;
+        symlink("loop_b", path.join("loop_a"))?;
+        symlink("./loop_a", path.join("loop_b"))?;
+        symlink(path.join("file"), path.join("absolute"))?;
+
+        Ok(dir)
+    }
+
+    #[test_case("one", 40, LinkState::Resolvable ; "single hop")]
+    #[test_case("sub/three", 40, LinkState::Resolvable ; "relative to parent")]
+    #[test_case("absolute", 40, LinkState::Resolvable ; "absolute target")]
+    #[test_case("sub/three", 3, LinkState::Resolvable ; "within depth")]
+    #[test_case("sub/three", 2, LinkState::Cyclic ; "deeper than depth")]
+    #[test_case("dangling", 40, LinkState::Dangling ; "missing target")]
+    #[test_case("not_dir", 40, LinkState::Dangling ; "target below file")]
+    #[test_case("loop_a", 40, LinkState::Cyclic ; "loop")]
+    #[test_case("file", 0, LinkState::Resolvable ; "not a symlink")]
+    fn classify(path: &str, max_depth: u32, expected: LinkState) -> Result<()> {
+        let dir = setup()?;
+
+        assert_eq!(
+            expected,
+            LinkState::classify(&dir.path().join(path), max_depth)?
+        );
+
+        Ok(dir.close()?)
+    }
+}
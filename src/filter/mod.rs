@@ -2,17 +2,49 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+mod access;
+mod attr;
+mod caps;
+mod contents;
+mod context;
+mod count;
+mod days;
+mod dir_entries;
 mod duration;
+mod error_policy;
+mod extension;
 mod file_type;
+mod glob;
+mod hash;
+mod in_dir;
+mod link_chain;
 mod octal;
 mod owner;
+mod since;
 mod size;
+mod xattr;
 
-pub use self::duration::DurationFilter;
-pub use self::file_type::TypeFilter;
+pub use self::access::Access;
+pub use self::attr::{AttrFilter, ProjectIdFilter};
+pub use self::caps::{CapsFilter, FileCaps};
+pub use self::contents::ContentFilter;
+pub use self::context::ContextFilter;
+pub use self::count::CountFilter;
+pub use self::days::DaysFilter;
+pub use self::dir_entries::{count_entries, EntriesFilter};
+pub use self::duration::{Boundary, TimeFilter};
+pub use self::error_policy::{BtimePolicy, ErrorPolicy};
+pub use self::extension::ExtensionFilter;
+pub use self::file_type::{TypeFilter, TypeSelector};
+pub use self::glob::GlobFilter;
+pub use self::hash::{HashAlgorithm, HashFilter};
+pub use self::in_dir::InDirFilter;
+pub use self::link_chain::{LinkState, DEFAULT_MAX_LINK_DEPTH};
 pub use self::octal::OctalFilter;
-pub use self::owner::OwnerFilter;
-pub use self::size::SizeFilter;
+pub use self::owner::{OrphanFilter, OwnerFilter, OwnerSpec};
+pub use self::since::SinceSpec;
+pub use self::size::{parse_size, SizeFilter};
+pub use self::xattr::XattrFilter;
 
 #[cfg(test)]
-mod testing;
+pub mod testing;
@@ -11,10 +11,16 @@ pub enum OctalFilter {
     NotContains(u32),
 }
 
+/// The setuid, setgid and sticky bits.
+pub const SPECIAL_BITS: u32 = 0o7000;
+
 impl OctalFilter {
+    /// 'Equal' only compares the setuid, setgid and sticky
+    /// bits when any of them are given, e.g. '4755'.
     pub fn matches(&self, o: u32) -> bool {
         match self {
             Self::Contains(m) => *m == (o & *m),
+            Self::Equal(m) if *m & SPECIAL_BITS != 0 => *m == (o & (SPECIAL_BITS | 0o777)),
             Self::Equal(m) => *m == (o & 0o777),
             Self::NotContains(m) => 0 == (o & *m),
         }
@@ -25,6 +31,10 @@ impl FromStr for OctalFilter {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains(|c: char| c.is_ascii_alphabetic() || c == '=') {
+            return parse_symbolic(s);
+        }
+
         Ok(if let Some(maybe_octal) = s.strip_prefix('+') {
             Self::Contains(u32::from_str_radix(maybe_octal, 8)?)
         } else if let Some(maybe_octal) = s.strip_prefix('~') {
@@ -35,6 +45,76 @@ impl FromStr for OctalFilter {
     }
 }
 
+/// Parses symbolic modes as understood by chmod, e.g.
+/// 'u=rwx,g=rx,o=' or 'g+w'. Clauses assigning permissions
+/// with '=' match modes with exactly those permissions,
+/// clauses adding permissions with '+' match modes with at
+/// least those permissions and clauses removing permissions
+/// with '-' match modes with none of them. All clauses must
+/// use the same operator.
+fn parse_symbolic(s: &str) -> anyhow::Result<OctalFilter> {
+    let mut op = None;
+    let mut mode = 0;
+
+    for clause in s.split(',') {
+        let (who, perms) = clause
+            .split_once(['=', '+', '-'])
+            .ok_or_else(|| anyhow::anyhow!("missing operator in symbolic mode '{}'", clause))?;
+        let clause_op = clause.as_bytes()[who.len()];
+
+        if op.is_some_and(|o| o != clause_op) {
+            anyhow::bail!("symbolic mode '{}' mixes operators", s);
+        }
+
+        op = Some(clause_op);
+
+        if !who.chars().all(|w| "ugoa".contains(w)) {
+            anyhow::bail!("invalid classes in symbolic mode '{}'", clause);
+        }
+
+        let who = if who.is_empty() { "a" } else { who };
+
+        for (w, p) in who.chars().flat_map(|w| perms.chars().map(move |p| (w, p))) {
+            mode |= symbolic_bit(w, p).ok_or_else(|| {
+                anyhow::anyhow!("invalid permission '{}' in symbolic mode '{}'", p, clause)
+            })?;
+        }
+    }
+
+    Ok(match op {
+        Some(b'+') => OctalFilter::Contains(mode),
+        Some(b'-') => OctalFilter::NotContains(mode),
+        _ => OctalFilter::Equal(mode),
+    })
+}
+
+/// Returns the mode bit of the given permission for the
+/// given class of users or 'None' if the permission is
+/// invalid. Permissions which do not apply to a class such
+/// as the sticky bit for the owner contribute no bits.
+fn symbolic_bit(who: char, perm: char) -> Option<u32> {
+    let shift = match who {
+        'u' => 6,
+        'g' => 3,
+        'o' => 0,
+        'a' => {
+            return ['u', 'g', 'o'].iter().map(|w| symbolic_bit(*w, perm)).sum();
+        }
+        _ => unreachable!("classes are validated"),
+    };
+
+    Some(match (perm, who) {
+        ('r', _) => 0o4 << shift,
+        ('w', _) => 0o2 << shift,
+        ('x', _) => 0o1 << shift,
+        ('s', 'u') => 0o4000,
+        ('s', 'g') => 0o2000,
+        ('t', 'o') => 0o1000,
+        ('s' | 't', _) => 0,
+        _ => return None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::OctalFilter;
@@ -46,6 +126,17 @@ mod tests {
     #[test_case("+0440", Ok(OctalFilter::Contains(0o440)) ; "contains r ug")]
     #[test_case("~0006", Ok(OctalFilter::NotContains(0o006)) ; "not contains rw o")]
     #[test_case("-0000", Err(anyhow!("")) ; "bad prefix")]
+    #[test_case("u=rwx,g=rx,o=", Ok(OctalFilter::Equal(0o750)) ; "symbolic equal")]
+    #[test_case("g+w", Ok(OctalFilter::Contains(0o020)) ; "symbolic contains")]
+    #[test_case("go-w", Ok(OctalFilter::NotContains(0o022)) ; "symbolic not contains")]
+    #[test_case("+x", Ok(OctalFilter::Contains(0o111)) ; "symbolic all classes")]
+    #[test_case("a=r", Ok(OctalFilter::Equal(0o444)) ; "symbolic all")]
+    #[test_case("u+s", Ok(OctalFilter::Contains(0o4000)) ; "symbolic setuid")]
+    #[test_case("+t", Ok(OctalFilter::Contains(0o1000)) ; "symbolic sticky")]
+    #[test_case("u=rw,g+r", Err(anyhow!("")) ; "symbolic mixed operators")]
+    #[test_case("x=r", Err(anyhow!("")) ; "symbolic unknown class")]
+    #[test_case("u=q", Err(anyhow!("")) ; "symbolic unknown permission")]
+    #[test_case("urw", Err(anyhow!("")) ; "symbolic without operator")]
     #[test_case("0999", Err(anyhow!("")) ; "bad octal integer")]
     fn from_str(s: &str, expected: Result<OctalFilter>) {
         assert_from_str(s, expected)
@@ -54,6 +145,10 @@ mod tests {
     #[test_case(OctalFilter::Equal(0o600), 0o600, true ; "equal matches")]
     #[test_case(OctalFilter::Equal(0o700), 0o600, false ; "equal does not match")]
     #[test_case(OctalFilter::Equal(0o600), 0o66600, true ; "equal extended perms matches")]
+    #[test_case(OctalFilter::Equal(0o4755), 0o104755, true ; "equal setuid matches")]
+    #[test_case(OctalFilter::Equal(0o4755), 0o100755, false ; "equal setuid does not match")]
+    #[test_case(OctalFilter::Equal(0o755), 0o104755, true ; "equal ignores setuid")]
+    #[test_case(OctalFilter::Contains(0o1000), 0o41777, true ; "contains sticky matches")]
     #[test_case(OctalFilter::Contains(0o400), 0o600, true ; "contains matches")]
     #[test_case(OctalFilter::Contains(0o700), 0o600, false ; "contains does not match")]
     #[test_case(OctalFilter::NotContains(0o040), 0o600, true ; "not_contains matches")]
@@ -2,6 +2,10 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::nss::IdDatabase;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
 use std::str::FromStr;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -9,58 +13,139 @@ pub enum OwnerFilter {
     User(u32),
     Group(u32),
     UserGroup(u32, u32),
+    Not(Box<OwnerFilter>),
 }
 
 impl OwnerFilter {
+    pub fn is_negated(&self) -> bool {
+        matches!(self, Self::Not(_))
+    }
     pub fn matches(&self, uid: u32, gid: u32) -> bool {
         match self {
             Self::User(u) => *u == uid,
             Self::Group(g) => *g == gid,
             Self::UserGroup(u, g) => *u == uid && *g == gid,
+            Self::Not(f) => !f.matches(uid, gid),
+        }
+    }
+}
+
+impl fmt::Display for OwnerFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::User(u) => write!(f, "{u}"),
+            Self::Group(g) => write!(f, ":{g}"),
+            Self::UserGroup(u, g) => write!(f, "{u}:{g}"),
+            Self::Not(o) => write!(f, "!{o}"),
+        }
+    }
+}
+
+/// Matches entries whose owner or group no longer exists in
+/// the user or group database like 'find -nouser' and
+/// '-nogroup'. Ids are looked up in a snapshot of the
+/// databases. Ids missing from it are looked up directly as
+/// hosts backed by directory services often disable
+/// enumeration, and the outcome is remembered either way so
+/// every id is looked up at most once.
+pub struct OrphanFilter {
+    nouser: bool,
+    nogroup: bool,
+    uids: RefCell<HashMap<u32, bool>>,
+    gids: RefCell<HashMap<u32, bool>>,
+}
+
+impl OrphanFilter {
+    pub fn new(nouser: bool, nogroup: bool, db: &IdDatabase) -> Self {
+        Self {
+            nouser,
+            nogroup,
+            uids: RefCell::new(db.uids().map(|id| (id, true)).collect()),
+            gids: RefCell::new(db.gids().map(|id| (id, true)).collect()),
         }
     }
+    pub fn matches(&self, uid: u32, gid: u32) -> bool {
+        (!self.nouser || !known(&self.uids, uid, |id| users::get_user_by_uid(id).is_some()))
+            && (!self.nogroup
+                || !known(&self.gids, gid, |id| users::get_group_by_gid(id).is_some()))
+    }
+}
+
+fn known(ids: &RefCell<HashMap<u32, bool>>, id: u32, lookup: impl Fn(u32) -> bool) -> bool {
+    *ids.borrow_mut().entry(id).or_insert_with(|| lookup(id))
+}
+
+/// Unresolved owner:group specification as given on
+/// the command line. Negation is expressed with a
+/// leading '!'.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OwnerSpec {
+    user: Option<String>,
+    group: Option<String>,
+    negated: bool,
+}
+
+impl OwnerSpec {
+    /// Reports whether resolving the spec requires looking up names.
+    pub fn has_names(&self) -> bool {
+        [&self.user, &self.group]
+            .into_iter()
+            .flatten()
+            .any(|s| s.parse::<u32>().is_err())
+    }
+    pub fn resolve(&self, db: &IdDatabase) -> anyhow::Result<OwnerFilter> {
+        let filter = match (&self.user, &self.group) {
+            (Some(user), None) => OwnerFilter::User(db.uid(user)?),
+            (None, Some(group)) => OwnerFilter::Group(db.gid(group)?),
+            (Some(user), Some(group)) => OwnerFilter::UserGroup(db.uid(user)?, db.gid(group)?),
+            (None, None) => unreachable!("owner spec without user or group"),
+        };
+
+        Ok(match self.negated {
+            true => OwnerFilter::Not(Box::new(filter)),
+            false => filter,
+        })
+    }
 }
 
-impl FromStr for OwnerFilter {
+impl FromStr for OwnerSpec {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s.split_once(':') {
+        let (negated, spec) = match s.strip_prefix('!') {
+            Some(spec) => (true, spec),
+            None => (false, s),
+        };
+        let (user, group) = match spec.split_once(':') {
             // "user:"
-            Some((user, "")) => Self::User(parse_user(user)?),
+            Some((user, "")) => (Some(user), None),
             // ":group"
-            Some(("", group)) => Self::Group(parse_group(group)?),
+            Some(("", group)) => (None, Some(group)),
             // "user:group"
-            Some((user, group)) => Self::UserGroup(parse_user(user)?, parse_group(group)?),
+            Some((user, group)) => (Some(user), Some(group)),
             // "user"
-            None => Self::User(parse_user(s)?),
-        })
-    }
-}
+            None => (Some(spec), None),
+        };
 
-fn parse_user(s: &str) -> anyhow::Result<u32> {
-    match u32::from_str(s) {
-        Ok(uid) => users::get_user_by_uid(uid),
-        Err(_) => users::get_user_by_name(s),
-    }
-    .map(|u| u.uid())
-    .ok_or_else(|| anyhow::anyhow!("invalid user '{}'", s))
-}
+        if user == Some("") || spec.starts_with('!') {
+            anyhow::bail!("invalid owner '{}'", s);
+        }
 
-fn parse_group(s: &str) -> anyhow::Result<u32> {
-    match u32::from_str(s) {
-        Ok(gid) => users::get_group_by_gid(gid),
-        Err(_) => users::get_group_by_name(s),
+        Ok(Self {
+            user: user.map(String::from),
+            group: group.map(String::from),
+            negated,
+        })
     }
-    .map(|g| g.gid())
-    .ok_or_else(|| anyhow::anyhow!("invalid group '{}'", s))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::OwnerFilter;
-    use crate::filter::testing::*;
+    use super::{known, OrphanFilter, OwnerFilter, OwnerSpec};
+    use crate::nss::IdDatabase;
     use anyhow::{anyhow, Result};
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
     use test_case::test_case;
     use users::{get_current_gid, get_current_groupname, get_current_uid, get_current_username};
 
@@ -84,10 +169,32 @@ mod tests {
         Ok(OwnerFilter::UserGroup(get_current_uid(), get_current_gid()))
         ; "current user:group"
     )]
+    #[test_case("4242:4343", Ok(OwnerFilter::UserGroup(4242, 4343)) ; "numeric ids")]
+    #[test_case("!4242", Ok(OwnerFilter::Not(Box::new(OwnerFilter::User(4242)))) ; "negated user")]
+    #[test_case("!:4343", Ok(OwnerFilter::Not(Box::new(OwnerFilter::Group(4343)))) ; "negated group")]
+    #[test_case("!!4242", Err(anyhow!("")) ; "double negation")]
+    #[test_case("!", Err(anyhow!("")) ; "negated empty")]
     #[test_case("dne_user", Err(anyhow!("")) ; "non-existent user")]
     #[test_case(":dne_group", Err(anyhow!("")) ; "non-existent group")]
-    fn from_str(s: &str, expected: Result<OwnerFilter>) {
-        assert_from_str(s, expected)
+    #[test_case(":", Err(anyhow!("")) ; "empty user and group")]
+    #[test_case("", Err(anyhow!("")) ; "empty")]
+    fn resolve(s: &str, expected: Result<OwnerFilter>) {
+        let result = s
+            .parse::<OwnerSpec>()
+            .and_then(|spec| spec.resolve(&IdDatabase::load()));
+
+        match expected {
+            Ok(f) => assert_eq!(f, result.unwrap()),
+            Err(_) => assert!(result.is_err()),
+        }
+    }
+    #[test_case("4242", false ; "numeric user")]
+    #[test_case(":4242", false ; "numeric group")]
+    #[test_case("root:4242", true ; "named user")]
+    #[test_case("4242:root", true ; "named group")]
+    #[test_case("!root", true ; "negated named user")]
+    fn has_names(s: &str, expected: bool) {
+        assert_eq!(expected, s.parse::<OwnerSpec>().unwrap().has_names())
     }
     #[test_case(
         OwnerFilter::User(get_current_uid()),
@@ -125,9 +232,50 @@ mod tests {
         false
         ; "current user and group does not match current uid +1 and gid + 1"
     )]
+    #[test_case(
+        OwnerFilter::Not(Box::new(OwnerFilter::User(get_current_uid()))),
+        get_current_uid(), get_current_gid(),
+        false
+        ; "negated current user does not match"
+    )]
+    #[test_case(
+        OwnerFilter::Not(Box::new(OwnerFilter::User(get_current_uid()))),
+        get_current_uid()+1, get_current_gid(),
+        true
+        ; "negated current user matches current uid + 1"
+    )]
     fn matches(f: OwnerFilter, uid: u32, gid: u32, expected: bool) {
         assert_eq!(expected, f.matches(uid, gid))
     }
+    #[test_case(true, false, get_current_uid(), 4242424, false ; "existing user")]
+    #[test_case(true, false, 4242424, get_current_gid(), true ; "missing user")]
+    #[test_case(false, true, 4242424, get_current_gid(), false ; "existing group")]
+    #[test_case(false, true, get_current_uid(), 4242424, true ; "missing group")]
+    #[test_case(true, true, 4242424, get_current_gid(), false ; "missing user only")]
+    #[test_case(true, true, 4242424, 4242424, true ; "missing user and group")]
+    fn orphans(nouser: bool, nogroup: bool, uid: u32, gid: u32, expected: bool) {
+        assert_eq!(
+            expected,
+            OrphanFilter::new(nouser, nogroup, &IdDatabase::load()).matches(uid, gid)
+        )
+    }
+    #[test_case(true ; "found")]
+    #[test_case(false ; "missing")]
+    fn lookups_are_cached(found: bool) {
+        let ids = RefCell::new(HashMap::new());
+        let lookups = Cell::new(0);
+
+        for _ in 0..3 {
+            let known = known(&ids, 4242424, |_| {
+                lookups.set(lookups.get() + 1);
+                found
+            });
+
+            assert_eq!(found, known);
+        }
+
+        assert_eq!(1, lookups.get());
+    }
     fn current_user_name() -> String {
         get_current_username()
             .unwrap()
@@ -0,0 +1,139 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::str::FromStr;
+
+/// A point in time given by an event on the host rather
+/// than as a duration.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SinceSpec {
+    Boot,
+    /// The time the given systemd unit last became active.
+    Unit(String),
+}
+
+impl SinceSpec {
+    /// Resolves the event to seconds since the epoch.
+    pub fn resolve(&self) -> Result<u64> {
+        let boot = boot_time()?;
+
+        match self {
+            Self::Boot => Ok(boot),
+            Self::Unit(name) => Ok(boot + unit_active_since_boot(name)?),
+        }
+    }
+}
+
+impl FromStr for SinceSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "boot" => Self::Boot,
+            "" => anyhow::bail!("empty unit name"),
+            _ if cfg!(feature = "systemd") => Self::Unit(s.to_string()),
+            _ => anyhow::bail!(
+                "'--since UNIT' requires findr to be built with the 'systemd' feature"
+            ),
+        })
+    }
+}
+
+fn boot_time() -> Result<u64> {
+    let stat = fs::read_to_string("/proc/stat").context("unable to determine boot time")?;
+
+    parse_btime(&stat).ok_or_else(|| anyhow!("unable to determine boot time"))
+}
+
+fn parse_btime(stat: &str) -> Option<u64> {
+    stat.lines()
+        .find_map(|l| l.strip_prefix("btime "))
+        .and_then(|t| t.trim().parse().ok())
+}
+
+// Seconds after boot the unit entered the active state.
+// The monotonic timestamp is used as the realtime one is
+// only available as a formatted date on older systemd.
+#[cfg(feature = "systemd")]
+fn unit_active_since_boot(name: &str) -> Result<u64> {
+    use std::{io, path::Path, process};
+
+    // the same check as sd_booted(3) so hosts running
+    // another init fail clearly instead of on systemctl
+    if !Path::new("/run/systemd/system").is_dir() {
+        anyhow::bail!("unable to query unit '{}': systemd is not running", name);
+    }
+
+    let out = process::Command::new("systemctl")
+        .args([
+            "show",
+            "--property=ActiveEnterTimestampMonotonic",
+            "--value",
+            "--",
+            name,
+        ])
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => anyhow!("unable to query systemd: 'systemctl' not found"),
+            _ => anyhow!(e).context("unable to query systemd"),
+        })?;
+
+    if !out.status.success() {
+        anyhow::bail!(
+            "unable to query unit '{}': {}",
+            name,
+            String::from_utf8_lossy(&out.stderr).trim()
+        );
+    }
+
+    match String::from_utf8_lossy(&out.stdout).trim().parse::<u64>() {
+        Ok(0) => Err(anyhow!("unit '{}' has not been active since boot", name)),
+        Ok(usec) => Ok(usec / 1_000_000),
+        Err(_) => Err(anyhow!("unknown unit '{}'", name)),
+    }
+}
+
+// Units are rejected when parsed without the 'systemd'
+// feature so this is never reached.
+#[cfg(not(feature = "systemd"))]
+fn unit_active_since_boot(name: &str) -> Result<u64> {
+    anyhow::bail!(
+        "unable to query unit '{}': findr was built without the 'systemd' feature",
+        name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_btime, SinceSpec};
+    use crate::filter::testing::*;
+    use anyhow::anyhow;
+    use test_case::test_case;
+
+    #[test_case("boot", Ok(SinceSpec::Boot) ; "boot")]
+    #[test_case("", Err(anyhow!("")) ; "empty")]
+    fn from_str(s: &str, expected: anyhow::Result<SinceSpec>) {
+        assert_from_str(s, expected)
+    }
+
+    #[cfg(feature = "systemd")]
+    #[test]
+    fn unit() {
+        assert_from_str("nginx.service", Ok(SinceSpec::Unit("nginx.service".into())))
+    }
+
+    #[cfg(not(feature = "systemd"))]
+    #[test]
+    fn unit_without_feature() {
+        assert_from_str::<SinceSpec>("nginx.service", Err(anyhow!("")))
+    }
+
+    #[test_case("cpu  1 2 3\nbtime 1677000000\nprocesses 42\n", Some(1677000000) ; "present")]
+    #[test_case("cpu  1 2 3\n", None ; "missing")]
+    fn btime(stat: &str, expected: Option<u64>) {
+        assert_eq!(expected, parse_btime(stat))
+    }
+}
@@ -10,6 +10,9 @@ pub enum SizeFilter {
     Less(u64),
     Greater(u64),
     Equal(u64),
+    /// Sizes from the first up to and including the second,
+    /// given as e.g. '1MiB..100MiB'.
+    Between(u64, u64),
 }
 
 impl SizeFilter {
@@ -18,6 +21,7 @@ impl SizeFilter {
             Self::Equal(u) => size == *u,
             Self::Less(u) => size < *u,
             Self::Greater(u) => size > *u,
+            Self::Between(from, to) => size >= *from && size <= *to,
         }
     }
 }
@@ -26,7 +30,11 @@ impl FromStr for SizeFilter {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(if let Some(maybe_int) = s.strip_prefix('-') {
+        Ok(if let Some((from, to)) = s.split_once("..") {
+            let (from, to) = (parse_size(from)?, parse_size(to)?);
+
+            Self::Between(from.min(to), from.max(to))
+        } else if let Some(maybe_int) = s.strip_prefix('-') {
             Self::Less(parse_size(maybe_int)?)
         } else if let Some(maybe_int) = s.strip_prefix('+') {
             Self::Greater(parse_size(maybe_int)?)
@@ -36,7 +44,7 @@ impl FromStr for SizeFilter {
     }
 }
 
-fn parse_size(s: &str) -> anyhow::Result<u64> {
+pub fn parse_size(s: &str) -> anyhow::Result<u64> {
     _parse_size(s).map_err(|e| anyhow::anyhow!(e))
 }
 
@@ -52,6 +60,10 @@ mod test {
     #[test_case("-1", Ok(SizeFilter::Less(1)) ; "less than 1 byte")]
     #[test_case("1M", Ok(SizeFilter::Equal(1_000_000)) ; "equals 1 megabytes")]
     #[test_case("1MiB", Ok(SizeFilter::Equal(2u64.pow(20))) ; "equals 1 mebibytes")]
+    #[test_case("1MiB..100MiB", Ok(SizeFilter::Between(2u64.pow(20), 100 * 2u64.pow(20))) ; "range")]
+    #[test_case("10..1", Ok(SizeFilter::Between(1, 10)) ; "reversed range")]
+    #[test_case("+1..10", Err(anyhow!("")) ; "range with prefix")]
+    #[test_case("1..", Err(anyhow!("")) ; "open range")]
     #[test_case("3jb", Err(anyhow!("")) ; "invalid suffix")]
     fn from_str(s: &str, expected: Result<SizeFilter>) {
         assert_from_str(s, expected)
@@ -62,6 +74,10 @@ mod test {
     #[test_case(SizeFilter::Equal(1), 2, false ; "not equals 1 byte")]
     #[test_case(SizeFilter::Less(1), 2, false ; "not less than 1 byte")]
     #[test_case(SizeFilter::Greater(1), 0, false ; "not greater than 1 byte")]
+    #[test_case(SizeFilter::Between(1, 10), 1, true ; "lower end of range")]
+    #[test_case(SizeFilter::Between(1, 10), 10, true ; "upper end of range")]
+    #[test_case(SizeFilter::Between(1, 10), 0, false ; "below range")]
+    #[test_case(SizeFilter::Between(1, 10), 11, false ; "above range")]
     fn matches(f: SizeFilter, size: u64, expected: bool) {
         assert_eq!(expected, f.matches(size))
     }
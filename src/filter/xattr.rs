@@ -0,0 +1,126 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+pub use imp::XattrFilter;
+
+#[cfg(feature = "xattr")]
+mod imp {
+    use crate::security::read_xattr;
+    use std::path::Path;
+    use std::str::FromStr;
+
+    /// Matches entries carrying the given extended attribute,
+    /// optionally with the given value, e.g.
+    /// 'user.backup.exclude' or 'user.backup.exclude=1'.
+    /// Trailing NUL bytes stored by some tools are ignored
+    /// when comparing values.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct XattrFilter {
+        pub(super) name: String,
+        pub(super) value: Option<String>,
+    }
+
+    impl XattrFilter {
+        pub fn matches(&self, path: &Path) -> anyhow::Result<bool> {
+            Ok(match read_xattr(path, &self.name)? {
+                Some(v) => self.value.as_ref().is_none_or(|expected| {
+                    let end = v.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+
+                    v[..end] == *expected.as_bytes()
+                }),
+                None => false,
+            })
+        }
+    }
+
+    impl FromStr for XattrFilter {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let (name, value) = match s.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (s, None),
+            };
+
+            if name.is_empty() || name.contains('\0') {
+                anyhow::bail!("invalid extended attribute name '{}'", name);
+            }
+
+            Ok(Self {
+                name: name.to_string(),
+                value,
+            })
+        }
+    }
+}
+
+#[cfg(not(feature = "xattr"))]
+mod imp {
+    use std::path::Path;
+    use std::str::FromStr;
+
+    /// Stands in for the extended attribute filter when
+    /// findr is built without the 'xattr' feature.
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum XattrFilter {}
+
+    impl XattrFilter {
+        pub fn matches(&self, _: &Path) -> anyhow::Result<bool> {
+            match *self {}
+        }
+    }
+
+    impl FromStr for XattrFilter {
+        type Err = anyhow::Error;
+
+        fn from_str(_: &str) -> Result<Self, Self::Err> {
+            anyhow::bail!("'--xattr' requires findr to be built with the 'xattr' feature")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XattrFilter;
+
+    #[cfg(feature = "xattr")]
+    fn filter(name: &str, value: Option<&str>) -> XattrFilter {
+        XattrFilter {
+            name: name.into(),
+            value: value.map(String::from),
+        }
+    }
+
+    #[cfg(feature = "xattr")]
+    #[test_case::test_case("user.a", Ok(filter("user.a", None)) ; "name")]
+    #[test_case::test_case("user.a=1", Ok(filter("user.a", Some("1"))) ; "name and value")]
+    #[test_case::test_case("user.a=", Ok(filter("user.a", Some(""))) ; "empty value")]
+    #[test_case::test_case("user.a=b=c", Ok(filter("user.a", Some("b=c"))) ; "value containing separator")]
+    #[test_case::test_case("=1", Err(anyhow::anyhow!("")) ; "empty name")]
+    fn from_str(s: &str, expected: anyhow::Result<XattrFilter>) {
+        crate::filter::testing::assert_from_str(s, expected)
+    }
+
+    #[cfg(feature = "xattr")]
+    #[test_case::test_case("user.findr.test", true ; "name")]
+    #[test_case::test_case("user.findr.test=1", true ; "value")]
+    #[test_case::test_case("user.findr.test=2", false ; "other value")]
+    #[test_case::test_case("user.findr.other", false ; "other name")]
+    fn matches(spec: &str, expected: bool) -> anyhow::Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+
+        // stored with a trailing NUL like SELinux contexts
+        ::xattr::set(file.path(), "user.findr.test", b"1\0")?;
+
+        assert_eq!(expected, spec.parse::<XattrFilter>()?.matches(file.path())?);
+
+        Ok(file.close()?)
+    }
+
+    #[cfg(not(feature = "xattr"))]
+    #[test]
+    fn without_feature() {
+        assert!("user.a".parse::<XattrFilter>().is_err())
+    }
+}
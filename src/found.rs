@@ -0,0 +1,123 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::entry::{Entry, FileKind};
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+/// A snapshot of the metadata of a matching entry as
+/// recorded by structured outputs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoundEntry {
+    pub path: String,
+    pub file_type: FileKind,
+    pub size: u64,
+    /// POSIX permissions including special bits.
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub mtime: u64,
+    /// Summary of special bits, capabilities, ACLs and
+    /// SELinux context. Only read when a sink emits it as
+    /// it requires reading several extended attributes.
+    pub security: Option<String>,
+}
+
+impl FoundEntry {
+    pub(crate) fn new<E: Entry>(ent: &E, with_security: bool) -> Result<Self> {
+        Ok(Self {
+            path: ent.path(),
+            file_type: ent.file_type(),
+            size: ent.size()?,
+            mode: ent.mode()? & 0o7777,
+            uid: ent.uid()?,
+            gid: ent.gid()?,
+            mtime: ent.mtime()?,
+            security: with_security.then(|| ent.security()).transpose()?,
+        })
+    }
+}
+
+/// Stored results can be filtered like live entries as long
+/// as the filters only depend on the recorded fields.
+impl Entry for FoundEntry {
+    fn path(&self) -> String {
+        self.path.clone()
+    }
+    fn fs_path(&self) -> &Path {
+        Path::new(&self.path)
+    }
+    fn depth(&self) -> Result<usize> {
+        Err(not_recorded(&self.path, "depth"))
+    }
+    fn uid(&self) -> Result<u32> {
+        Ok(self.uid)
+    }
+    fn gid(&self) -> Result<u32> {
+        Ok(self.gid)
+    }
+    fn atime(&self) -> Result<u64> {
+        Err(not_recorded(&self.path, "access time"))
+    }
+    fn ctime(&self) -> Result<u64> {
+        Err(not_recorded(&self.path, "change time"))
+    }
+    fn created_time(&self) -> Result<u64> {
+        Err(not_recorded(&self.path, "creation time"))
+    }
+    fn mtime(&self) -> Result<u64> {
+        Ok(self.mtime)
+    }
+    fn mode(&self) -> Result<u32> {
+        Ok(self.mode)
+    }
+    fn ino(&self) -> Result<u64> {
+        Err(not_recorded(&self.path, "inode number"))
+    }
+    fn dev(&self) -> Result<u64> {
+        Err(not_recorded(&self.path, "device"))
+    }
+    fn nlink(&self) -> Result<u64> {
+        Err(not_recorded(&self.path, "link count"))
+    }
+    fn blocks(&self) -> Result<u64> {
+        Err(not_recorded(&self.path, "allocated blocks"))
+    }
+    fn size(&self) -> Result<u64> {
+        Ok(self.size)
+    }
+    fn target_size(&self) -> Result<u64> {
+        match self.file_type {
+            FileKind::SymLink => Err(not_recorded(&self.path, "target size")),
+            _ => Ok(self.size),
+        }
+    }
+    fn target_mtime(&self) -> Result<u64> {
+        match self.file_type {
+            FileKind::SymLink => Err(not_recorded(&self.path, "target modification time")),
+            _ => Ok(self.mtime),
+        }
+    }
+    fn link_target(&self) -> Result<Option<String>> {
+        match self.file_type {
+            FileKind::SymLink => Err(not_recorded(&self.path, "link target")),
+            _ => Ok(None),
+        }
+    }
+    fn subtree_mtime(&self) -> Option<u64> {
+        None
+    }
+    fn file_type(&self) -> FileKind {
+        self.file_type
+    }
+    fn security(&self) -> Result<String> {
+        self.security
+            .clone()
+            .ok_or_else(|| not_recorded(&self.path, "security summary"))
+    }
+}
+
+fn not_recorded(path: &str, field: &str) -> anyhow::Error {
+    anyhow!("{} of '{}' is not recorded in stored results", field, path)
+}
@@ -0,0 +1,134 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::ValueEnum;
+use std::env;
+
+/// Languages user facing messages are available in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Lang {
+    #[default]
+    En,
+    Es,
+}
+
+impl Lang {
+    /// Selects a language from the locale environment
+    /// variables in POSIX precedence order. Unset, empty
+    /// and unsupported locales fall back to English.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|v| env::var(v).ok())
+            .find(|v| !v.is_empty())
+            .map_or_else(Self::default, |v| Self::from_locale(&v))
+    }
+    fn from_locale(locale: &str) -> Self {
+        match locale.split(['_', '.', '@']).next() {
+            Some("es") => Self::Es,
+            _ => Self::En,
+        }
+    }
+    /// Trailer written when results are omitted due to
+    /// '--max-output'.
+    pub fn truncated(self, remaining: usize) -> String {
+        match self {
+            Self::En => format!(
+                "… truncated ({remaining} more {})",
+                plural(remaining, "match", "matches")
+            ),
+            Self::Es => format!(
+                "… truncado ({remaining} {} más)",
+                plural(remaining, "coincidencia", "coincidencias")
+            ),
+        }
+    }
+    /// Summary written after an audit completes.
+    pub fn audited(self, checked: usize, deviating: usize) -> String {
+        match self {
+            Self::En => format!(
+                "audited {checked} {}, {deviating} deviating",
+                plural(checked, "entry", "entries")
+            ),
+            Self::Es => format!(
+                "{checked} {}, {deviating} con desviaciones",
+                plural(checked, "entrada auditada", "entradas auditadas")
+            ),
+        }
+    }
+    /// Warning written when filesystems are mounted or
+    /// unmounted during a search with '--one-file-system'.
+    pub fn mount_table_changed(self, mounted: &[String], unmounted: &[String]) -> String {
+        let (message, on, off) = match self {
+            Self::En => ("mount table changed during search", "mounted", "unmounted"),
+            Self::Es => (
+                "la tabla de montajes cambió durante la búsqueda",
+                "montado",
+                "desmontado",
+            ),
+        };
+        let changes = mounted
+            .iter()
+            .map(|m| format!("{on} '{m}'"))
+            .chain(unmounted.iter().map(|m| format!("{off} '{m}'")))
+            .collect::<Vec<String>>();
+
+        format!("{message}: {}", changes.join(", "))
+    }
+    /// Prefix for errors raised while evaluating filters.
+    pub fn filter_evaluation_failed(self) -> &'static str {
+        match self {
+            Self::En => "filter evaluation failed",
+            Self::Es => "falló la evaluación del filtro",
+        }
+    }
+}
+
+fn plural(n: usize, one: &'static str, many: &'static str) -> &'static str {
+    if n == 1 {
+        one
+    } else {
+        many
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lang;
+    use test_case::test_case;
+
+    #[test_case("es_ES.UTF-8", Lang::Es ; "spanish")]
+    #[test_case("es", Lang::Es ; "bare spanish")]
+    #[test_case("en_US.UTF-8", Lang::En ; "english")]
+    #[test_case("de_DE@euro", Lang::En ; "unsupported")]
+    #[test_case("C", Lang::En ; "posix")]
+    fn from_locale(locale: &str, expected: Lang) {
+        assert_eq!(expected, Lang::from_locale(locale))
+    }
+
+    #[test_case(Lang::En, 1, "… truncated (1 more match)" ; "english singular")]
+    #[test_case(Lang::En, 3, "… truncated (3 more matches)" ; "english plural")]
+    #[test_case(Lang::Es, 1, "… truncado (1 coincidencia más)" ; "spanish singular")]
+    #[test_case(Lang::Es, 0, "… truncado (0 coincidencias más)" ; "spanish plural")]
+    fn truncated(lang: Lang, remaining: usize, expected: &str) {
+        assert_eq!(expected, lang.truncated(remaining))
+    }
+
+    #[test_case(Lang::En, 1, 0, "audited 1 entry, 0 deviating" ; "english singular")]
+    #[test_case(Lang::En, 2, 1, "audited 2 entries, 1 deviating" ; "english plural")]
+    #[test_case(Lang::Es, 1, 1, "1 entrada auditada, 1 con desviaciones" ; "spanish singular")]
+    #[test_case(Lang::Es, 2, 0, "2 entradas auditadas, 0 con desviaciones" ; "spanish plural")]
+    fn audited(lang: Lang, checked: usize, deviating: usize, expected: &str) {
+        assert_eq!(expected, lang.audited(checked, deviating))
+    }
+
+    #[test_case(Lang::En, "mount table changed during search: mounted '/mnt/usb', unmounted '/mnt/backup'" ; "english")]
+    #[test_case(Lang::Es, "la tabla de montajes cambió durante la búsqueda: montado '/mnt/usb', desmontado '/mnt/backup'" ; "spanish")]
+    fn mount_table_changed(lang: Lang, expected: &str) {
+        assert_eq!(
+            expected,
+            lang.mount_table_changed(&["/mnt/usb".into()], &["/mnt/backup".into()])
+        )
+    }
+}
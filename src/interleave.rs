@@ -0,0 +1,52 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::VecDeque;
+
+/// Yields one item from each of the given iterators in
+/// turn, dropping iterators as they are exhausted, so a
+/// single large iterator cannot starve the others.
+pub struct RoundRobin<I> {
+    iters: VecDeque<I>,
+}
+
+impl<I: Iterator> RoundRobin<I> {
+    pub fn new(iters: impl IntoIterator<Item = I>) -> Self {
+        Self {
+            iters: iters.into_iter().collect(),
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for RoundRobin<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(mut iter) = self.iters.pop_front() {
+            if let Some(item) = iter.next() {
+                self.iters.push_back(iter);
+
+                return Some(item);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoundRobin;
+    use test_case::test_case;
+
+    #[test_case(vec![vec![1, 2, 3], vec![4, 5], vec![6]], vec![1, 4, 6, 2, 5, 3] ; "uneven lengths")]
+    #[test_case(vec![vec![], vec![1, 2]], vec![1, 2] ; "empty iterator")]
+    #[test_case(vec![], vec![] ; "no iterators")]
+    fn round_robin(input: Vec<Vec<u32>>, expected: Vec<u32>) {
+        let result =
+            RoundRobin::new(input.into_iter().map(|v| v.into_iter())).collect::<Vec<u32>>();
+
+        assert_eq!(expected, result)
+    }
+}
@@ -0,0 +1,163 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::options::Options;
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobFile {
+    #[serde(default, rename = "job")]
+    jobs: Vec<JobSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobSpec {
+    name: String,
+    #[serde(default)]
+    roots: Vec<PathBuf>,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+/// A named search declared in a jobs file.
+#[derive(Debug)]
+pub struct Job {
+    pub name: String,
+    pub options: Options,
+}
+
+/// Reads the jobs declared in the given TOML file. The
+/// options of every job are validated up front so a
+/// mistake in one job prevents any job from running.
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<Job>> {
+    let path = path.as_ref();
+    let data = fs::read_to_string(path)
+        .map_err(|e| anyhow!("invalid jobs file '{}': {}", path.display(), e))?;
+
+    parse(&data).map_err(|e| anyhow!("invalid jobs file '{}': {}", path.display(), e))
+}
+
+fn parse(data: &str) -> Result<Vec<Job>> {
+    let file: JobFile = toml::from_str(data)?;
+    let mut names = HashSet::new();
+
+    file.jobs
+        .into_iter()
+        .map(|j| {
+            if !names.insert(j.name.clone()) {
+                bail!("duplicate job '{}'", j.name);
+            }
+
+            let options = job_options(&j).map_err(|e| anyhow!("job '{}': {}", j.name, e))?;
+
+            Ok(Job {
+                name: j.name,
+                options,
+            })
+        })
+        .collect()
+}
+
+fn job_options(job: &JobSpec) -> Result<Options> {
+    let args = std::iter::once(clap::crate_name!().into())
+        .chain(job.args.iter().map(Into::into))
+        .chain(job.roots.iter().map(|r| r.as_os_str().to_owned()));
+    let options = Options::try_parse_constrained_from(args)?;
+
+    if options.jobs.is_some() {
+        bail!("jobs cannot run other jobs");
+    }
+
+    Ok(options)
+}
+
+/// Returns the jobs with the given names in the order
+/// given or all jobs if no names are given.
+pub fn select(jobs: Vec<Job>, names: &[String]) -> Result<Vec<Job>> {
+    if names.is_empty() {
+        return Ok(jobs);
+    }
+
+    let mut jobs = jobs.into_iter().map(Some).collect::<Vec<Option<Job>>>();
+
+    names
+        .iter()
+        .map(|n| {
+            jobs.iter_mut()
+                .find(|j| j.as_ref().is_some_and(|j| &j.name == n))
+                .and_then(Option::take)
+                .ok_or_else(|| anyhow!("unknown job '{}'", n))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, select};
+    use anyhow::Result;
+    use std::path::PathBuf;
+    use test_case::test_case;
+
+    const JOBS: &str = r#"
+[[job]]
+name = "tmp"
+roots = ["/tmp", "/var/tmp"]
+args = ["--type=f", "--mtime", "7d"]
+
+[[job]]
+name = "home"
+roots = ["/home"]
+"#;
+
+    #[test]
+    fn parse_jobs() -> Result<()> {
+        let jobs = parse(JOBS)?;
+
+        assert_eq!(
+            vec!["tmp", "home"],
+            jobs.iter().map(|j| &j.name).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![PathBuf::from("/tmp"), PathBuf::from("/var/tmp")],
+            jobs[0].options.dirs
+        );
+        assert_eq!(1, jobs[0].options.mtime_filters.len());
+        assert!(jobs[1].options.type_filters.is_empty());
+
+        Ok(())
+    }
+
+    #[test_case("[[job]]\nname = \"a\"\nargs = [\"--type=j\"]" ; "invalid option value")]
+    #[test_case("[[job]]\nname = \"a\"\n\n[[job]]\nname = \"a\"" ; "duplicate name")]
+    #[test_case("[[job]]\nname = \"a\"\nargs = [\"run-jobs\", \"jobs.toml\"]" ; "nested jobs")]
+    #[test_case("[[job]]\nroots = [\"/tmp\"]" ; "missing name")]
+    #[test_case("[[job]]\nname = \"a\"\nactions = []" ; "unknown field")]
+    fn parse_invalid(data: &str) {
+        assert!(parse(data).is_err())
+    }
+
+    #[test_case(&[], Ok(vec!["tmp", "home"]) ; "all jobs")]
+    #[test_case(&["home", "tmp"], Ok(vec!["home", "tmp"]) ; "named jobs in order given")]
+    #[test_case(&["dne"], Err(()) ; "unknown job")]
+    #[test_case(&["tmp", "tmp"], Err(()) ; "repeated job")]
+    fn select_jobs(names: &[&str], expected: Result<Vec<&str>, ()>) -> Result<()> {
+        let names = names.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+        let selected = select(parse(JOBS)?, &names)
+            .map(|jobs| jobs.into_iter().map(|j| j.name).collect::<Vec<String>>())
+            .map_err(|_| ());
+
+        assert_eq!(
+            expected.map(|v| v.into_iter().map(String::from).collect::<Vec<String>>()),
+            selected
+        );
+
+        Ok(())
+    }
+}
@@ -3,19 +3,93 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{anyhow, Result};
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::error;
+use std::fs;
 use std::io::{self, Write};
+use std::iter;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
 use std::path;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time;
 use walkdir::WalkDir;
 
+mod audit;
+mod cachedir;
+mod columnar;
+pub mod constraints;
+mod duplicates;
 mod entry;
+mod escape;
+mod estimate;
+mod events;
+pub mod exit;
 mod filter;
+mod found;
+pub mod i18n;
+mod interleave;
+pub mod jobs;
+mod lock;
+mod mounts;
+mod nss;
+mod open_files;
 pub mod options;
+mod order;
+mod output;
+mod pattern;
+mod policy;
+mod quickfix;
+mod reference;
+mod retry;
+mod score;
+mod security;
+pub mod sink;
+mod split;
+mod sqlite;
+mod store;
+mod subtree;
+mod template;
+mod transform;
+pub mod version;
+
+pub use entry::FileKind;
+pub use found::FoundEntry;
+
+/// How often the mount table is re-read when searches
+/// are restricted to the filesystems of their roots.
+const MOUNT_POLL_INTERVAL: time::Duration = time::Duration::from_secs(30);
 
 pub struct Command<'a> {
     options: &'a options::Options,
+    owners: Vec<filter::OwnerFilter>,
+    orphans: Option<filter::OrphanFilter>,
+    group_of: Option<Vec<u32>>,
+    reference: Option<reference::ReferenceList>,
+    expectations: Vec<audit::Expectation>,
+    policy: Option<policy::Policy>,
+    since: Option<u64>,
+    special_bits: Option<filter::OctalFilter>,
+    fstypes: Option<mounts::FsTypes>,
+    open_files: Option<open_files::OpenFiles>,
+    newer: Option<u64>,
+    older: Option<u64>,
+    samefile: Option<(u64, u64)>,
+    patterns: Vec<pattern::Pattern>,
+    glob: Option<filter::GlobFilter>,
+    excludes: Vec<regex::Regex>,
+    samples: Vec<estimate::RootSample>,
+}
+
+/// Totals gathered over the course of a search.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub matches: usize,
+    pub errors: usize,
+    /// matches which were not written due to '--max-output'.
+    pub truncated: usize,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -24,75 +98,699 @@ pub enum Error {
     Terminated(usize),
     #[error("invalid root dir: {0}")]
     InvalidRootDir(#[from] walkdir::Error),
+    #[error("{0}")]
+    InvalidArgument(anyhow::Error),
+    #[error("filter evaluation failed: {0}")]
+    FilterEvaluation(anyhow::Error),
+    #[error("'{}' is locked by another search", .0.display())]
+    Locked(path::PathBuf),
+    #[error("{0}")]
+    Output(anyhow::Error),
 }
 
 impl<'a> Command<'a> {
-    pub fn new(options: &'a options::Options) -> Self {
-        Self { options }
+    pub fn new(options: &'a options::Options) -> Result<Self> {
+        constraints::check_types(options).map_err(Error::InvalidArgument)?;
+        constraints::check_outputs(options).map_err(Error::InvalidArgument)?;
+
+        let dbs = IdDatabases::new(options);
+        let db = dbs.get(options.owner.iter().any(|o| o.has_names()));
+        let mut owners = options
+            .owner
+            .iter()
+            .map(|o| o.resolve(db))
+            .collect::<Result<Vec<filter::OwnerFilter>>>()
+            .map_err(Error::InvalidArgument)?;
+
+        if options.group_members {
+            let members = owners
+                .iter()
+                .filter_map(|o| match o {
+                    filter::OwnerFilter::Group(g) => Some(*g),
+                    _ => None,
+                })
+                .flat_map(nss::group_members)
+                .map(filter::OwnerFilter::User)
+                .collect::<Vec<filter::OwnerFilter>>();
+
+            owners.extend(members);
+        }
+
+        let group_of = options
+            .group_of
+            .as_deref()
+            .map(nss::user_groups)
+            .transpose()
+            .map_err(Error::InvalidArgument)?;
+
+        let orphans = (options.nouser || options.nogroup)
+            .then(|| filter::OrphanFilter::new(options.nouser, options.nogroup, dbs.get(true)));
+
+        let reference = match &options.reference {
+            Some(path) => {
+                let sep = if options.null { b'\0' } else { b'\n' };
+
+                Some(reference::ReferenceList::load(path, sep).map_err(Error::InvalidArgument)?)
+            }
+            None => None,
+        };
+
+        let db = dbs.get(options.expectations.iter().any(|e| e.has_names()));
+        let expectations = options
+            .expectations
+            .iter()
+            .map(|e| e.resolve(db))
+            .collect::<Result<Vec<audit::Expectation>>>()
+            .map_err(Error::InvalidArgument)?;
+
+        let policy = match &options.policy {
+            Some(path) => Some(
+                policy::PolicySpec::load(path)
+                    .and_then(|p| p.resolve(dbs.get(p.has_names())))
+                    .map_err(Error::InvalidArgument)?,
+            ),
+            None => None,
+        };
+
+        let since = options
+            .since
+            .as_ref()
+            .map(|s| s.resolve())
+            .transpose()
+            .map_err(Error::InvalidArgument)?;
+
+        let special_bits = [
+            (options.suid, 0o4000),
+            (options.sgid, 0o2000),
+            (options.sticky, 0o1000),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .fold(0, |mask, (_, bit)| mask | bit);
+        let special_bits =
+            (special_bits != 0).then_some(filter::OctalFilter::Contains(special_bits));
+        let fstypes = match &options.fstype {
+            Some(_) => Some(mounts::FsTypes::load().map_err(Error::InvalidArgument)?),
+            None => None,
+        };
+        let open_files = match options.open {
+            true => Some(open_files::OpenFiles::load().map_err(Error::InvalidArgument)?),
+            false => None,
+        };
+
+        let newer = options
+            .newer
+            .as_deref()
+            .map(reference_mtime)
+            .transpose()
+            .map_err(Error::InvalidArgument)?;
+        let older = options
+            .older
+            .as_deref()
+            .map(reference_mtime)
+            .transpose()
+            .map_err(Error::InvalidArgument)?;
+        let samefile = options
+            .samefile
+            .as_deref()
+            .map(reference_inode)
+            .transpose()
+            .map_err(Error::InvalidArgument)?;
+
+        let mut patterns = options.patterns.clone();
+
+        for path in &options.pattern_files {
+            patterns.extend(pattern::load(path).map_err(Error::InvalidArgument)?);
+        }
+
+        let patterns = patterns
+            .iter()
+            .map(|p| {
+                let ignore_case = options.ignore_case
+                    || options.smart_case && !pattern::has_uppercase(p, options.fixed_strings);
+                let p = match options.normalize {
+                    Some(form) => form.apply(p),
+                    None => p.into(),
+                };
+
+                pattern::Pattern::new(&p, options.fixed_strings, ignore_case, options.regex_engine)
+            })
+            .collect::<Result<Vec<_>>>()
+            .map_err(Error::InvalidArgument)?;
+        let mut excludes = options.excludes.clone();
+
+        for path in &options.exclude_from {
+            for p in pattern::load(path).map_err(Error::InvalidArgument)? {
+                excludes.push(regex::Regex::new(&p).map_err(|e| {
+                    Error::InvalidArgument(anyhow!("invalid exclude '{}': {}", p, e))
+                })?);
+            }
+        }
+
+        let glob = match &options.glob {
+            Some(g)
+                if options.ignore_case
+                    || options.smart_case && !pattern::has_uppercase(g.glob(), true) =>
+            {
+                Some(g.case_insensitive())
+            }
+            g => g.clone(),
+        };
+
+        let samples = match options.estimate {
+            Some(fraction) => options
+                .dirs
+                .iter()
+                .map(|d| estimate::RootSample::select(d, fraction))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            options,
+            owners,
+            orphans,
+            group_of,
+            reference,
+            expectations,
+            policy,
+            since,
+            special_bits,
+            fstypes,
+            open_files,
+            newer,
+            older,
+            samefile,
+            patterns,
+            glob,
+            excludes,
+            samples,
+        })
     }
-    pub fn run(&self, term_sig: Arc<AtomicUsize>) -> Result<()> {
-        let mut out = io::stdout().lock();
-        let mut err = io::stderr().lock();
+    pub fn run(&self, term_sig: Arc<AtomicUsize>) -> Result<Summary> {
+        let _lock = match &self.options.lock {
+            Some(path) => Some(self.lock(path, &term_sig)?),
+            None => None,
+        };
+
+        if let Some(path) = &self.options.from_db {
+            let entries = sqlite::StoredEntries::open(path).map_err(Error::InvalidArgument)?;
+
+            return self.search(entries, None, &term_sig);
+        }
+
+        let retry = self.retry_policy();
+        let mut subtree = self
+            .options
+            .subtree_modified_within
+            .map(|_| subtree::SubtreeTracker::default());
+        let mounts = self
+            .options
+            .one_file_system
+            .then(|| mounts::MountWatcher::new(MOUNT_POLL_INTERVAL));
 
-        self.options
+        let walkers = self
+            .options
             .dirs
             .iter()
-            .flat_map(|p| self.new_walker(p))
-            .map(|r| match term_sig.load(Ordering::Relaxed) {
-                0 => r.map(entry::EntryImpl::from).map_err(|e| match e.depth() {
+            .enumerate()
+            .map(|(i, p)| self.new_walker(p, self.samples.get(i)));
+        let entries: Box<dyn Iterator<Item = walkdir::Result<walkdir::DirEntry>>> =
+            if self.options.interleave_roots {
+                Box::new(interleave::RoundRobin::new(walkers))
+            } else {
+                Box::new(walkers.flatten())
+            };
+
+        self.search(
+            entries.map(|r| {
+                r.map(|e| {
+                    let subtree_mtime = subtree.as_mut().and_then(|t| t.observe(&e));
+
+                    entry::EntryImpl::new(e, retry).with_subtree_mtime(subtree_mtime)
+                })
+                .map_err(|e| match e.depth() {
                     0 => anyhow!(Error::InvalidRootDir(e)),
                     _ => anyhow!(e),
-                }),
+                })
+            }),
+            mounts,
+            &term_sig,
+        )
+    }
+    fn search<E: entry::Entry>(
+        &self,
+        entries: impl Iterator<Item = Result<E>>,
+        mut mounts: Option<mounts::MountWatcher>,
+        term_sig: &AtomicUsize,
+    ) -> Result<Summary> {
+        let mut sinks = sink::SinkSet::open(self.options).map_err(Error::InvalidArgument)?;
+        let mut err = io::stderr().lock();
+        let mut visited = 0;
+
+        let mut seen = self.options.missing.then(HashSet::new);
+        let mut inodes = self.options.unique_inodes.then(HashSet::new);
+        let mut audit = self
+            .options
+            .audit
+            .as_ref()
+            .map(|_| audit::AuditReport::default());
+
+        let mut scored = self.options.score_cleanup.then(Vec::new);
+        let mut estimator = self
+            .options
+            .estimate
+            .map(|_| estimate::Estimator::new(&self.samples));
+        let mut duplicates = self
+            .options
+            .duplicates
+            .map(|_| duplicates::DuplicateFinder::default());
+
+        sinks.begin()?;
+
+        // progress is reported while entries are visited
+        // while results are only written once they matched
+        let sinks = RefCell::new(sinks);
+
+        let mut summary = entries
+            .map(|r| match term_sig.load(Ordering::Relaxed) {
+                0 => {
+                    visited += 1;
+                    sinks.borrow_mut().write_progress(visited)?;
+
+                    if let Some(change) = mounts.as_mut().and_then(|m| m.poll()) {
+                        sinks.borrow_mut().write_warning(
+                            &self
+                                .options
+                                .lang()
+                                .mount_table_changed(&change.mounted, &change.unmounted),
+                        )?;
+                    }
+
+                    r
+                }
                 u => Err(anyhow!(Error::Terminated(u))),
             })
-            .filter_map(curry_filter(|e| self.matches_pattern(e)))
-            .filter_map(curry_filter(|e| self.matches_owner(e)))
-            .filter_map(curry_filter(|e| self.matches_mode(e)))
-            .filter_map(curry_filter(|e| self.matches_type_filters(e)))
-            .filter_map(curry_filter(|e| self.matches_size_filters(e)))
-            .filter_map(curry_filter(|e| self.matches_atime_filters(e)))
-            .filter_map(curry_filter(|e| self.matches_ctime_filters(e)))
-            .filter_map(curry_filter(|e| self.matches_creation_time_filters(e)))
-            .filter_map(curry_filter(|e| self.matches_mtime_filters(e)))
-            .try_for_each(|r| -> Result<()> {
+            .filter_map(self.curry_filter(|e| self.matches_pattern(e)))
+            .filter_map(self.curry_filter(|e| self.matches_glob(e)))
+            .filter_map(self.curry_filter(|e| self.matches_extension(e)))
+            .filter_map(self.curry_filter(|e| self.matches_lname(e)))
+            .filter_map(self.curry_filter(|e| self.matches_link_state(e)))
+            .filter_map(self.curry_filter(|e| self.matches_in_dir(e)))
+            .filter_map(self.curry_filter(|e| self.matches_owners(e)))
+            .filter_map(self.curry_filter(|e| self.matches_id_filters(e)))
+            .filter_map(self.curry_filter(|e| self.matches_orphans(e)))
+            .filter_map(self.curry_filter(|e| self.matches_group_of(e)))
+            .filter_map(self.curry_filter(|e| self.matches_mode(e)))
+            .filter_map(self.curry_filter(|e| self.matches_special_bits(e)))
+            .filter_map(self.curry_filter(|e| self.matches_inum(e)))
+            .filter_map(self.curry_filter(|e| self.matches_samefile(e)))
+            .filter_map(self.curry_filter(|e| self.matches_fstype(e)))
+            .filter_map(self.curry_filter(|e| self.matches_open(e)))
+            .filter_map(self.curry_filter(|e| self.matches_xattrs(e)))
+            .filter_map(self.curry_filter(|e| self.matches_context(e)))
+            .filter_map(self.curry_filter(|e| self.matches_acl(e)))
+            .filter_map(self.curry_filter(|e| self.matches_caps(e)))
+            .filter_map(self.curry_filter(|e| self.matches_attr(e)))
+            .filter_map(self.curry_filter(|e| self.matches_project_id(e)))
+            .filter_map(self.curry_filter(|e| self.matches_access(e)))
+            .filter_map(self.curry_filter(|e| self.matches_links_filters(e)))
+            .filter_map(self.curry_filter(|e| self.matches_type_filters(e)))
+            .filter_map(self.curry_filter(|e| self.matches_empty(e)))
+            .filter_map(self.curry_filter(|e| self.matches_entries(e)))
+            .filter_map(self.curry_filter(|e| self.matches_sparse(e)))
+            .filter_map(self.curry_filter(|e| self.matches_size_filters(e)))
+            .filter_map(self.curry_filter(|e| self.matches_atime_filters(e)))
+            .filter_map(self.curry_filter(|e| self.matches_atime_days(e)))
+            .filter_map(self.curry_filter(|e| self.matches_ctime_filters(e)))
+            .filter_map(self.curry_filter(|e| self.matches_ctime_days(e)))
+            .filter_map(self.curry_filter(|e| self.matches_creation_time_filters(e)))
+            .filter_map(self.curry_filter(|e| self.matches_mtime_filters(e)))
+            .filter_map(self.curry_filter(|e| self.matches_mtime_days(e)))
+            .filter_map(self.curry_filter(|e| self.matches_since(e)))
+            .filter_map(self.curry_filter(|e| self.matches_reference_mtime(e)))
+            .filter_map(self.curry_filter(|e| self.matches_newer_than_parent(e)))
+            .filter_map(self.curry_filter(|e| self.matches_target_size_filters(e)))
+            .filter_map(self.curry_filter(|e| self.matches_target_mtime_filters(e)))
+            .filter_map(self.curry_filter(|e| self.matches_subtree_modified(e)))
+            .filter_map(self.curry_filter(|e| self.matches_contents(e)))
+            .filter_map(self.curry_filter(|e| self.matches_hashes(e)))
+            .filter_map(self.curry_filter(|e| self.matches_orphan(e)))
+            .filter_map(|r| match (r, audit.as_mut()) {
+                (Ok(ent), Some(report)) => match report.check(&self.expectations, &ent) {
+                    Ok(deviates) => deviates.then_some(Ok(ent)),
+                    Err(e) => self.options.on_filter_error.apply(ent, e),
+                },
+                (r, _) => Some(r),
+            })
+            .filter_map(|r| match (r, inodes.as_mut()) {
+                (Ok(ent), Some(inodes)) => match ent.dev().and_then(|d| Ok((d, ent.ino()?))) {
+                    Ok(inode) => inodes.insert(inode).then_some(Ok(ent)),
+                    Err(e) => self.options.on_filter_error.apply(ent, e),
+                },
+                (r, _) => Some(r),
+            })
+            .try_fold(Summary::default(), |mut summary, r| -> Result<Summary> {
+                let mut sinks = sinks.borrow_mut();
+
                 match r {
-                    Ok(ent) => print_dirent(&mut out, ent),
-                    Err(e) if e.is::<Error>() => Err(e),
-                    Err(e) => self.print_error(&mut err, e),
+                    Ok(ent) => match (&self.policy, seen.as_mut()) {
+                        (Some(policy), _) => match policy.check(&ent, self.options.read_limit()) {
+                            Ok(violations) => {
+                                if !violations.is_empty() {
+                                    summary.matches += 1;
+                                }
+                                for v in violations {
+                                    self.emit_line(
+                                        &mut sinks,
+                                        &mut summary,
+                                        &v.to_json(&ent.path()),
+                                    )?;
+                                }
+                            }
+                            Err(e) => self.report_error(&mut sinks, &mut err, &mut summary, e)?,
+                        },
+                        (None, Some(seen)) => {
+                            seen.insert(reference::normalize(ent.fs_path()));
+                        }
+                        (None, None) => {
+                            match (estimator.as_mut(), scored.as_mut(), duplicates.as_mut()) {
+                                (Some(estimator), _, _) => match self.size(&ent) {
+                                    Ok(size) => {
+                                        estimator.record(ent.fs_path(), size);
+                                        summary.matches += 1;
+                                    }
+                                    Err(e) => {
+                                        self.report_error(&mut sinks, &mut err, &mut summary, e)?
+                                    }
+                                },
+                                (None, Some(scored), _) => match self.found(&sinks, &ent) {
+                                    Ok(found) => scored.push(found),
+                                    Err(e) => {
+                                        self.report_error(&mut sinks, &mut err, &mut summary, e)?
+                                    }
+                                },
+                                (None, None, Some(duplicates)) => {
+                                    if ent.file_type() == FileKind::File {
+                                        if let Err(e) = duplicates.record(&ent) {
+                                            self.report_error(
+                                                &mut sinks,
+                                                &mut err,
+                                                &mut summary,
+                                                e,
+                                            )?
+                                        }
+                                    }
+                                }
+                                (None, None, None) => match self.metadata(&sinks, &ent) {
+                                    Ok(found) => self.emit(
+                                        &mut sinks,
+                                        &mut summary,
+                                        &ent.path(),
+                                        found.as_ref(),
+                                        None,
+                                    )?,
+                                    Err(e) => {
+                                        self.report_error(&mut sinks, &mut err, &mut summary, e)?
+                                    }
+                                },
+                            }
+                        }
+                    },
+                    Err(e) if e.is::<Error>() => return Err(e),
+                    Err(e) => self.report_error(&mut sinks, &mut err, &mut summary, e)?,
                 }
-            })
+
+                Ok(summary)
+            })?;
+
+        let mut sinks = sinks.into_inner();
+
+        if let Some(scored) = scored {
+            let now = time::SystemTime::now()
+                .duration_since(time::UNIX_EPOCH)?
+                .as_secs();
+            let ranked = score::rank(
+                scored,
+                now,
+                self.options.min_score,
+                self.options.top.map(|n| n.get()),
+            );
+
+            for (score, found) in ranked {
+                self.emit(
+                    &mut sinks,
+                    &mut summary,
+                    &found.path,
+                    Some(&found),
+                    Some(score),
+                )?;
+            }
+        }
+
+        if let (Some(reference), Some(seen)) = (&self.reference, &seen) {
+            for p in reference.missing(seen) {
+                self.emit(&mut sinks, &mut summary, &p.to_string_lossy(), None, None)?;
+            }
+        }
+        if let (Some(finder), Some(format)) = (duplicates, self.options.duplicates) {
+            let sets = finder.sets(|e| self.report_error(&mut sinks, &mut err, &mut summary, e))?;
+
+            for (i, set) in sets.iter().enumerate() {
+                if i > 0 && format == duplicates::DuplicatesFormat::Plain {
+                    self.emit_line(&mut sinks, &mut summary, "")?;
+                }
+                for line in set.lines(format, |p| self.display_path(&p.to_string_lossy())) {
+                    self.emit_line(&mut sinks, &mut summary, &line)?;
+                }
+
+                summary.matches += set.paths.len();
+            }
+        }
+        if let Some(estimator) = &estimator {
+            let mut report = Vec::new();
+
+            estimator.estimate().write(&mut report)?;
+
+            for line in String::from_utf8_lossy(&report).lines() {
+                self.emit_line(&mut sinks, &mut summary, line)?;
+            }
+        }
+
+        sinks.write_summary(&Summary {
+            errors: summary.errors + sinks.failures(),
+            ..summary
+        })?;
+        sinks.finish()?;
+        sinks.write_stats()?;
+        summary.errors += sinks.failures();
+
+        if let Some(report) = &audit {
+            report.write_summary(&mut err, self.options.lang())?;
+        }
+
+        Ok(summary)
+    }
+    fn lock(&self, path: &path::Path, term_sig: &AtomicUsize) -> Result<lock::FileLock> {
+        let lock = lock::FileLock::acquire(path, self.options.on_locked, || {
+            term_sig.load(Ordering::Relaxed) != 0
+        })
+        .map_err(|e| Error::InvalidArgument(anyhow!("invalid lock '{}': {}", path.display(), e)))?;
+
+        match (lock, term_sig.load(Ordering::Relaxed)) {
+            (Some(lock), _) => Ok(lock),
+            (None, 0) => Err(anyhow!(Error::Locked(path.to_path_buf()))),
+            (None, u) => Err(anyhow!(Error::Terminated(u))),
+        }
     }
-    fn new_walker(&self, path: impl AsRef<path::Path>) -> walkdir::WalkDir {
+    fn retry_policy(&self) -> retry::RetryPolicy {
+        retry::RetryPolicy::new(self.options.retries, self.options.retry_delay.into())
+    }
+    fn new_walker<'s>(
+        &'s self,
+        path: impl AsRef<path::Path>,
+        sample: Option<&'s estimate::RootSample>,
+    ) -> impl Iterator<Item = walkdir::Result<walkdir::DirEntry>> + 's {
+        self.walk_from(path.as_ref(), 0, false, sample)
+    }
+    /// Walks the given directory found at 'depth' below its
+    /// root. Directories which fail to be read with a
+    /// transient error are re-opened and walked in place of
+    /// the error, skipping the directory itself as it was
+    /// already visited. Entries of such walks report their
+    /// depth relative to the re-opened directory.
+    fn walk_from<'s>(
+        &'s self,
+        path: &path::Path,
+        depth: usize,
+        resumed: bool,
+        sample: Option<&'s estimate::RootSample>,
+    ) -> Box<dyn Iterator<Item = walkdir::Result<walkdir::DirEntry>> + 's> {
         let mut walker = WalkDir::new(path);
+        let mut cache_probe = self
+            .options
+            .exclude_caches
+            .then(cachedir::CacheDirProbe::default);
+        let min_depth = self.options.min_depth.unwrap_or(0).saturating_sub(depth);
+        // only the subdirectories of roots are sampled
+        let sample = sample.filter(|_| depth == 0);
 
-        if let Some(depth) = self.options.min_depth {
-            walker = walker.min_depth(depth);
+        walker = walker.min_depth(if resumed { min_depth.max(1) } else { min_depth });
+        if let Some(max) = self.options.max_depth {
+            walker = walker.max_depth(max.saturating_sub(depth));
+        }
+        if self.options.subtree_modified_within.is_some() {
+            walker = walker.contents_first(true);
+        }
+        if self.options.one_file_system {
+            walker = walker.same_file_system(true);
         }
-        if let Some(depth) = self.options.max_depth {
-            walker = walker.max_depth(depth);
+        if let Some(order) = self.options.visit_order {
+            walker = walker.sort_by(order.comparator());
         }
 
-        walker
+        let retry = self.retry_policy();
+
+        Box::new(
+            walker
+                .into_iter()
+                .filter_entry(move |e| {
+                    !cache_probe.as_mut().is_some_and(|p| p.excludes(e))
+                        && !sample.is_some_and(|s| s.excludes(e))
+                        && !self.is_excluded(e)
+                        && !self.is_hidden(e)
+                })
+                .flat_map(move |r| -> Box<dyn Iterator<Item = _>> {
+                    let reopened = match &r {
+                        Err(e) => e.path().zip(e.io_error()).and_then(|(p, err)| {
+                            retry
+                                .retry(err, || fs::read_dir(p))
+                                .map(|_| p.to_path_buf())
+                        }),
+                        Ok(_) => None,
+                    };
+
+                    match (reopened, r) {
+                        (Some(p), Err(e)) => self.walk_from(&p, depth + e.depth(), true, sample),
+                        (_, r) => Box::new(iter::once(r)),
+                    }
+                }),
+        )
     }
-    fn matches_owner<E: entry::Entry>(&self, ent: E) -> Result<Option<E>> {
-        Ok(match &self.options.owner {
-            Some(f) => f.matches(ent.uid()?, ent.gid()?).then_some(ent),
-            None => Some(ent),
+    fn is_excluded(&self, ent: &walkdir::DirEntry) -> bool {
+        ent.depth() > 0 && {
+            let path = ent.path().to_string_lossy();
+
+            self.excludes.iter().any(|r| r.is_match(&path))
+        }
+    }
+    fn is_hidden(&self, ent: &walkdir::DirEntry) -> bool {
+        self.options.no_hidden && ent.depth() > 0 && ent.file_name().as_bytes().starts_with(b".")
+    }
+    fn matches_owners<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.owners.is_empty() || {
+            let (uid, gid) = (ent.uid()?, ent.gid()?);
+            let (negated, owners): (Vec<_>, Vec<_>) =
+                self.owners.iter().partition(|o| o.is_negated());
+
+            (owners.is_empty() || owners.iter().any(|o| o.matches(uid, gid)))
+                && negated.iter().all(|o| o.matches(uid, gid))
+        })
+    }
+    fn matches_id_filters<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok((self.options.uid_filters.is_empty() || {
+            let uid = ent.uid()?.into();
+
+            self.options.uid_filters.iter().all(|f| f.matches(uid))
+        }) && (self.options.gid_filters.is_empty() || {
+            let gid = ent.gid()?.into();
+
+            self.options.gid_filters.iter().all(|f| f.matches(gid))
+        }))
+    }
+    fn matches_group_of<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.group_of {
+            Some(gids) => gids.contains(&ent.gid()?),
+            None => true,
+        })
+    }
+    fn matches_orphans<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.orphans {
+            Some(f) => f.matches(ent.uid()?, ent.gid()?),
+            None => true,
         })
     }
-    fn matches_pattern<E: entry::Entry>(&self, ent: E) -> Result<Option<E>> {
-        Ok(match &self.options.pattern {
-            Some(p) => p.is_match(&ent.path()).then_some(ent),
-            None => Some(ent),
+    fn matches_pattern<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.patterns.is_empty() || {
+            let path = ent.path();
+            let path = if self.options.relative_match {
+                pattern::relative(&path, &self.options.dirs)
+            } else {
+                &path
+            };
+            let subject = self.options.match_on.subject(path);
+            let subject = match self.options.normalize {
+                Some(form) => form.apply(subject),
+                None => subject.into(),
+            };
+
+            self.patterns
+                .iter()
+                .map(|p| p.is_match(&subject))
+                .try_any()?
         })
     }
-    fn matches_type_filters<E: entry::Entry>(&self, ent: E) -> Result<Option<E>> {
-        Ok((self.options.type_filters.is_empty()
-            || self.options.type_filters.iter().any(|t| t.matches(&ent)))
-        .then_some(ent))
+    fn matches_glob<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.glob {
+            Some(g) => g.is_match(&ent.path()),
+            None => true,
+        })
     }
-    fn matches_atime_filters<E: entry::Entry>(&self, ent: E) -> Result<Option<E>> {
-        Ok((self.options.atime_filters.is_empty() || {
+    fn matches_extension<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.extensions.is_empty() || {
+            let path = ent.path();
+
+            self.options.extensions.iter().any(|x| x.matches(&path))
+        })
+    }
+    fn matches_lname<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.options.lname {
+            Some(r) => ent.link_target()?.is_some_and(|t| r.is_match(&t)),
+            None => true,
+        })
+    }
+    fn matches_link_state<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match self.options.resolvable {
+            Some(state) => {
+                ent.file_type() == FileKind::SymLink
+                    && filter::LinkState::classify(ent.fs_path(), self.options.max_link_depth)?
+                        == state
+            }
+            None => true,
+        })
+    }
+    fn matches_in_dir<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.in_dirs.is_empty() || {
+            let path = ent.path();
+
+            self.options.in_dirs.iter().any(|d| d.matches(&path))
+        })
+    }
+    fn matches_type_filters<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        let (negated, types): (Vec<_>, Vec<_>) = self
+            .options
+            .type_filters
+            .iter()
+            .partition(|t| t.is_negated());
+        let limit = self.options.read_limit();
+
+        Ok(
+            (types.is_empty() || types.iter().map(|t| t.matches(ent, limit)).try_any()?)
+                && negated.iter().map(|t| t.matches(ent, limit)).try_all()?,
+        )
+    }
+    fn matches_atime_filters<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.atime_filters.is_empty() || {
             let atime = ent.atime()?;
 
             self.options
@@ -101,10 +799,20 @@ impl<'a> Command<'a> {
                 .map(|f| f.matches(atime))
                 .try_all()?
         })
-        .then_some(ent))
     }
-    fn matches_ctime_filters<E: entry::Entry>(&self, ent: E) -> Result<Option<E>> {
-        Ok((self.options.ctime_filters.is_empty() || {
+    fn matches_atime_days<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.atime_days.is_empty() || {
+            let atime = ent.atime()?;
+
+            self.options
+                .atime_days
+                .iter()
+                .map(|f| f.matches(atime))
+                .try_all()?
+        })
+    }
+    fn matches_ctime_filters<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.ctime_filters.is_empty() || {
             let ctime = ent.ctime()?;
 
             self.options
@@ -113,28 +821,137 @@ impl<'a> Command<'a> {
                 .map(|f| f.matches(ctime))
                 .try_all()?
         })
-        .then_some(ent))
     }
-    fn matches_creation_time_filters<E: entry::Entry>(&self, ent: E) -> Result<Option<E>> {
-        Ok((self.options.creation_time_filters.is_empty() || {
-            let creation_time = ent.created_time()?;
+    fn matches_ctime_days<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.ctime_days.is_empty() || {
+            let ctime = ent.ctime()?;
 
             self.options
-                .creation_time_filters
+                .ctime_days
+                .iter()
+                .map(|f| f.matches(ctime))
+                .try_all()?
+        })
+    }
+    fn matches_creation_time_filters<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.creation_time_filters.is_empty() || {
+            match ent.created_time() {
+                Ok(creation_time) => self
+                    .options
+                    .creation_time_filters
+                    .iter()
+                    .map(|f| f.matches(creation_time))
+                    .try_all()?,
+                Err(e) => self.options.btime_missing.apply(e)?,
+            }
+        })
+    }
+    fn matches_inum<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match self.options.inum {
+            Some(n) => ent.ino()? == n,
+            None => true,
+        })
+    }
+    fn matches_samefile<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match self.samefile {
+            Some(inode) => (ent.dev()?, ent.ino()?) == inode,
+            None => true,
+        })
+    }
+    fn matches_fstype<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match (&self.options.fstype, &self.fstypes) {
+            (Some(fstype), Some(types)) => types.get(ent.dev()?) == Some(fstype.as_str()),
+            _ => true,
+        })
+    }
+    fn matches_open<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.open_files {
+            Some(open) => open.contains(ent.dev()?, ent.ino()?),
+            None => true,
+        })
+    }
+    fn matches_xattrs<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.xattrs.is_empty() || {
+            self.options
+                .xattrs
                 .iter()
-                .map(|f| f.matches(creation_time))
+                .map(|x| x.matches(ent.fs_path()))
                 .try_all()?
         })
-        .then_some(ent))
     }
-    fn matches_mode<E: entry::Entry>(&self, ent: E) -> Result<Option<E>> {
+    fn matches_context<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.options.context {
+            Some(f) => f.matches(ent.fs_path())?,
+            None => true,
+        })
+    }
+    fn matches_acl<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match (self.options.has_acl, self.options.no_acl) {
+            (false, false) => true,
+            (has_acl, _) => security::has_acl(ent.fs_path())? == has_acl,
+        })
+    }
+    fn matches_caps<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(!self.options.has_caps && self.options.caps.is_empty()
+            || match filter::FileCaps::read(ent.fs_path())? {
+                Some(caps) => self.options.caps.iter().all(|f| f.matches(&caps)),
+                None => false,
+            })
+    }
+    fn matches_attr<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.options.attr {
+            Some(f) => {
+                matches!(
+                    ent.file_type(),
+                    entry::FileKind::File | entry::FileKind::Dir
+                ) && f.matches(ent.fs_path())?
+            }
+            None => true,
+        })
+    }
+    fn matches_project_id<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.options.project_id {
+            Some(f) => {
+                matches!(
+                    ent.file_type(),
+                    entry::FileKind::File | entry::FileKind::Dir
+                ) && f.matches(ent.fs_path())?
+            }
+            None => true,
+        })
+    }
+    fn matches_access<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        [
+            (self.options.readable, filter::Access::Read),
+            (self.options.writable, filter::Access::Write),
+            (self.options.executable, filter::Access::Execute),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, a)| Ok(a.permits(ent.fs_path())?))
+        .try_all()
+    }
+    fn matches_links_filters<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.links_filters.is_empty() || {
+            let links = ent.nlink()?;
+
+            self.options.links_filters.iter().all(|f| f.matches(links))
+        })
+    }
+    fn matches_mode<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
         Ok(match &self.options.mode {
-            Some(f) => f.matches(ent.mode()?).then_some(ent),
-            None => Some(ent),
+            Some(f) => f.matches(ent.mode()?),
+            None => true,
+        })
+    }
+    fn matches_special_bits<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.special_bits {
+            Some(f) => f.matches(ent.mode()?),
+            None => true,
         })
     }
-    fn matches_mtime_filters<E: entry::Entry>(&self, ent: E) -> Result<Option<E>> {
-        Ok((self.options.mtime_filters.is_empty() || {
+    fn matches_mtime_filters<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.mtime_filters.is_empty() || {
             let mtime = ent.mtime()?;
 
             self.options
@@ -143,15 +960,228 @@ impl<'a> Command<'a> {
                 .map(|f| f.matches(mtime))
                 .try_all()?
         })
-        .then_some(ent))
     }
-    fn matches_size_filters<E: entry::Entry>(&self, ent: E) -> Result<Option<E>> {
-        Ok((self.options.size_filters.is_empty() || {
-            let size = ent.size()?;
+    fn matches_mtime_days<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.mtime_days.is_empty() || {
+            let mtime = ent.mtime()?;
+
+            self.options
+                .mtime_days
+                .iter()
+                .map(|f| f.matches(mtime))
+                .try_all()?
+        })
+    }
+    fn matches_since<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match self.since {
+            Some(since) => ent.mtime()? >= since,
+            None => true,
+        })
+    }
+    fn matches_reference_mtime<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        if self.newer.is_none() && self.older.is_none() {
+            return Ok(true);
+        }
+
+        let mtime = ent.mtime()?;
+
+        Ok(self.newer.is_none_or(|t| mtime > t) && self.older.is_none_or(|t| mtime < t))
+    }
+    fn matches_newer_than_parent<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        if !self.options.newer_than_parent {
+            return Ok(true);
+        }
+        // roots are never compared against their parent
+        if ent.depth()? == 0 {
+            return Ok(false);
+        }
+
+        Ok(match ent.fs_path().parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => {
+                ent.mtime()? > fs::metadata(parent)?.mtime().try_into()?
+            }
+            _ => false,
+        })
+    }
+    fn matches_empty<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(!self.options.empty || filter::TypeFilter::Empty.matches(ent, None)?)
+    }
+    fn matches_entries<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.options.entries {
+            Some(f) => {
+                ent.file_type() == entry::FileKind::Dir
+                    && f.matches(filter::count_entries(ent.fs_path(), f.limit())?)
+            }
+            None => true,
+        })
+    }
+    fn matches_sparse<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(!self.options.sparse
+            || ent.file_type() == entry::FileKind::File && ent.blocks()? * 512 < ent.size()?)
+    }
+    fn matches_size_filters<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.size_filters.is_empty() || {
+            let size = self.size(ent)?;
 
             self.options.size_filters.iter().all(|f| f.matches(size))
         })
-        .then_some(ent))
+    }
+    fn matches_target_size_filters<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.target_size_filters.is_empty() || {
+            let size = ent.target_size()?;
+
+            self.options
+                .target_size_filters
+                .iter()
+                .all(|f| f.matches(size))
+        })
+    }
+    fn matches_target_mtime_filters<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.target_mtime_filters.is_empty() || {
+            let mtime = ent.target_mtime()?;
+
+            self.options
+                .target_mtime_filters
+                .iter()
+                .map(|f| f.matches(mtime))
+                .try_all()?
+        })
+    }
+    fn matches_subtree_modified<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match self.options.subtree_modified_within {
+            Some(d) => match ent.subtree_mtime() {
+                Some(mtime) => {
+                    filter::TimeFilter::Less(filter::Boundary::Ago(d.into())).matches(mtime)?
+                }
+                None => false,
+            },
+            None => true,
+        })
+    }
+    fn curry_filter<E: entry::Entry>(
+        &self,
+        f: impl Fn(&E) -> Result<bool>,
+    ) -> impl Fn(Result<E>) -> Option<Result<E>> {
+        let policy = self.options.on_filter_error;
+
+        move |r| match r {
+            Ok(ent) => match f(&ent) {
+                Ok(b) => b.then_some(Ok(ent)),
+                Err(e) => policy.apply(ent, e),
+            },
+            Err(e) => Some(Err(e)),
+        }
+    }
+    fn matches_contents<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.options.contains {
+            Some(c) => {
+                ent.file_type() == FileKind::File
+                    && c.matches(ent.fs_path(), self.options.read_limit())?
+            }
+            None => true,
+        })
+    }
+    fn matches_hashes<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(self.options.hashes.is_empty()
+            || ent.file_type() == FileKind::File
+                && filter::HashFilter::matches_any(
+                    &self.options.hashes,
+                    ent.fs_path(),
+                    self.options.read_limit(),
+                )?)
+    }
+    fn matches_orphan<E: entry::Entry>(&self, ent: &E) -> Result<bool> {
+        Ok(match &self.reference {
+            Some(r) if !self.options.missing => !r.contains(ent.fs_path()),
+            _ => true,
+        })
+    }
+    fn metadata<E: entry::Entry>(
+        &self,
+        sinks: &sink::SinkSet,
+        ent: &E,
+    ) -> Result<Option<found::FoundEntry>> {
+        Ok(match sinks.needs_metadata() {
+            true => Some(self.found(sinks, ent)?),
+            false => None,
+        })
+    }
+    fn found<E: entry::Entry>(&self, sinks: &sink::SinkSet, ent: &E) -> Result<found::FoundEntry> {
+        Ok(found::FoundEntry {
+            size: self.size(ent)?,
+            ..found::FoundEntry::new(ent, sinks.needs_security())?
+        })
+    }
+    /// Returns the apparent size of an entry or the space
+    /// allocated to it when '--disk-usage' is enabled.
+    fn size<E: entry::Entry>(&self, ent: &E) -> Result<u64> {
+        Ok(match self.options.disk_usage {
+            true => ent.blocks()? * 512,
+            false => ent.size()?,
+        })
+    }
+    fn emit(
+        &self,
+        sinks: &mut sink::SinkSet,
+        summary: &mut Summary,
+        path: &str,
+        metadata: Option<&found::FoundEntry>,
+        score: Option<u32>,
+    ) -> Result<()> {
+        let path = self.display_path(path);
+
+        summary.matches += 1;
+
+        if !sinks.write_entry(&sink::SinkEntry {
+            path: &path,
+            metadata,
+            score,
+        })? {
+            summary.truncated += 1;
+        }
+
+        Ok(())
+    }
+    /// Writes a line which is not a result counting it as
+    /// truncated if it was omitted.
+    fn emit_line(
+        &self,
+        sinks: &mut sink::SinkSet,
+        summary: &mut Summary,
+        line: &str,
+    ) -> Result<()> {
+        if !sinks.write_line(line)? {
+            summary.truncated += 1;
+        }
+
+        Ok(())
+    }
+    fn display_path(&self, path: &str) -> String {
+        let path = self
+            .options
+            .path_transforms
+            .iter()
+            .fold(path.to_string(), |p, t| t.apply(&p));
+
+        match &self.options.escape {
+            Some(e) => e.apply(&path),
+            None => path,
+        }
+    }
+    /// Passes an error which does not abort the search to
+    /// the error output and every sink.
+    fn report_error(
+        &self,
+        sinks: &mut sink::SinkSet,
+        err: &mut impl Write,
+        summary: &mut Summary,
+        e: anyhow::Error,
+    ) -> Result<()> {
+        sinks.write_error(&e.to_string())?;
+        self.print_error(err, e)?;
+        summary.errors += 1;
+
+        Ok(())
     }
     fn print_error(&self, err: &mut impl Write, e: impl AsRef<dyn error::Error>) -> Result<()> {
         if self.options.show_errors {
@@ -162,21 +1192,54 @@ impl<'a> Command<'a> {
     }
 }
 
-fn curry_filter<E: entry::Entry>(
-    f: impl Fn(E) -> Result<Option<E>>,
-) -> impl Fn(Result<E>) -> Option<Result<E>> {
-    move |r| match r {
-        Ok(ent) => f(ent).transpose(),
-        Err(e) => Some(Err(e)),
+// Reading the user and group databases can be slow on
+// hosts backed by directory services so they are only
+// loaded when names actually need resolving and at most
+// once however many options need them.
+struct IdDatabases {
+    no_nss: bool,
+    loaded: std::cell::OnceCell<nss::IdDatabase>,
+    numeric: nss::IdDatabase,
+}
+
+impl IdDatabases {
+    fn new(options: &options::Options) -> Self {
+        Self {
+            no_nss: options.no_nss,
+            loaded: std::cell::OnceCell::new(),
+            numeric: nss::IdDatabase::numeric(),
+        }
+    }
+    fn get(&self, needs_names: bool) -> &nss::IdDatabase {
+        if needs_names && !self.no_nss {
+            self.loaded.get_or_init(nss::IdDatabase::load)
+        } else {
+            &self.numeric
+        }
     }
 }
 
-fn print_dirent(out: &mut impl Write, ent: impl entry::Entry) -> Result<()> {
-    Ok(writeln!(out, "{}", ent.path())?)
+/// Reads the modification time of a reference file given
+/// with '--newer' or '--older'.
+fn reference_mtime(path: &path::Path) -> Result<u64> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| anyhow!("invalid reference file '{}': {}", path.display(), e))?;
+
+    Ok(metadata.mtime().try_into()?)
+}
+
+/// Reads the device and inode number of a reference file
+/// given with '--samefile'.
+fn reference_inode(path: &path::Path) -> Result<(u64, u64)> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| anyhow!("invalid reference file '{}': {}", path.display(), e))?;
+
+    Ok((metadata.dev(), metadata.ino()))
 }
 
 trait TryBoolExt {
     fn try_all(&mut self) -> Result<bool>;
+    fn try_any(&mut self) -> Result<bool>;
 }
 
 impl<I: Iterator<Item = Result<bool>>> TryBoolExt for I {
@@ -189,4 +1252,13 @@ impl<I: Iterator<Item = Result<bool>>> TryBoolExt for I {
 
         Ok(true)
     }
+    fn try_any(&mut self) -> Result<bool> {
+        for b in self {
+            if b? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
 }
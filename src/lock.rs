@@ -0,0 +1,135 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fs;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Interval between attempts to take a lock held elsewhere.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Decides what happens when the lock file is already
+/// held by another search.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LockPolicy {
+    #[default]
+    Fail,
+    /// Waits until the lock is released or the
+    /// optional timeout expires.
+    Wait(Option<Duration>),
+}
+
+impl FromStr for LockPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.split_once('=') {
+            None if s == "fail" => Self::Fail,
+            None if s == "wait" => Self::Wait(None),
+            Some(("wait", timeout)) => Self::Wait(Some(humantime::parse_duration(timeout)?)),
+            _ => anyhow::bail!("unknown lock policy '{}'", s),
+        })
+    }
+}
+
+/// An exclusive advisory lock taken with flock(2) which is
+/// released when dropped or when the process exits.
+#[derive(Debug)]
+pub struct FileLock {
+    _file: fs::File,
+}
+
+impl FileLock {
+    /// Takes the lock on the given file, creating it if needed.
+    /// Returns 'None' if the lock is still held elsewhere once
+    /// the policy gives up or 'cancelled' returns true.
+    pub fn acquire(
+        path: &Path,
+        policy: LockPolicy,
+        cancelled: impl Fn() -> bool,
+    ) -> io::Result<Option<Self>> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        let start = Instant::now();
+
+        loop {
+            if try_lock(&file)? {
+                return Ok(Some(Self { _file: file }));
+            }
+
+            let expired = match policy {
+                LockPolicy::Fail => true,
+                LockPolicy::Wait(timeout) => timeout.is_some_and(|t| start.elapsed() >= t),
+            };
+
+            if expired || cancelled() {
+                return Ok(None);
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+fn try_lock(file: &fs::File) -> io::Result<bool> {
+    // SAFETY: the descriptor is owned by 'file' and stays open.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+
+    match io::Error::last_os_error() {
+        e if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+        e if e.kind() == io::ErrorKind::Interrupted => Ok(false),
+        e => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileLock, LockPolicy};
+    use crate::filter::testing::*;
+    use anyhow::{anyhow, Result};
+    use std::time::{Duration, Instant};
+    use test_case::test_case;
+
+    #[test_case("fail", Ok(LockPolicy::Fail) ; "fail")]
+    #[test_case("wait", Ok(LockPolicy::Wait(None)) ; "wait")]
+    #[test_case("wait=30s", Ok(LockPolicy::Wait(Some(Duration::from_secs(30)))) ; "wait with timeout")]
+    #[test_case("wait=soon", Err(anyhow!("")) ; "invalid timeout")]
+    #[test_case("retry", Err(anyhow!("")) ; "unknown policy")]
+    fn from_str(s: &str, expected: Result<LockPolicy>) {
+        assert_from_str(s, expected)
+    }
+
+    #[test]
+    fn exclusive() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let path = dir.path().join("findr.lock");
+
+        let held = FileLock::acquire(&path, LockPolicy::Fail, || false)?;
+
+        assert!(held.is_some());
+        assert!(FileLock::acquire(&path, LockPolicy::Fail, || false)?.is_none());
+
+        let start = Instant::now();
+        let timeout = Duration::from_millis(200);
+
+        assert!(FileLock::acquire(&path, LockPolicy::Wait(Some(timeout)), || false)?.is_none());
+        assert!(start.elapsed() >= timeout);
+        assert!(FileLock::acquire(&path, LockPolicy::Wait(None), || true)?.is_none());
+
+        drop(held);
+
+        assert!(FileLock::acquire(&path, LockPolicy::Fail, || false)?.is_some());
+
+        Ok(dir.close()?)
+    }
+}
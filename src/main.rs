@@ -2,41 +2,137 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use clap::{crate_name, Parser};
-use findr::{self, options};
+use clap::crate_name;
+use findr::constraints;
+use findr::exit::{self, ExitCode};
+use findr::{self, jobs, options, version};
 use signal_hook::consts::signal::*;
 use signal_hook::flag as signal_flag;
 use std::io;
 use std::process;
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
-const SIG_EXIT_MARKER: usize = 128;
-
 fn main() -> io::Result<()> {
     let term_sig = Arc::new(AtomicUsize::new(0));
 
     signal_flag::register_usize(SIGTERM, Arc::clone(&term_sig), SIGTERM as usize)?;
     signal_flag::register_usize(SIGINT, Arc::clone(&term_sig), SIGINT as usize)?;
 
-    let code: i32 = match findr::Command::new(&options::Options::parse()).run(term_sig) {
-        Ok(()) => 0,
+    let options = options::Options::parse_constrained();
+
+    if options.version {
+        version::write_version(&mut io::stdout().lock(), options.verbose)?;
+
+        process::exit(0)
+    }
+    if let Some(format) = &options.exit_codes {
+        exit::write_exit_codes(&mut io::stdout().lock(), format)?;
+
+        process::exit(0)
+    }
+    if options.explain_conflicts {
+        constraints::write_explanation(
+            &mut io::stdout().lock(),
+            &options::Options::constrained_command(),
+        )?;
+
+        process::exit(0)
+    }
+
+    if let Some(path) = &options.jobs {
+        let code = match jobs::load(path).and_then(|j| jobs::select(j, &options.job_names)) {
+            Ok(jobs) => run_jobs(&jobs, &term_sig),
+            Err(e) => {
+                eprintln!("{}: {}", crate_name!(), e);
+
+                strict_code(&options, ExitCode::Usage)
+            }
+        };
+
+        process::exit(code)
+    }
+
+    let result = findr::Command::new(&options).and_then(|c| c.run(term_sig));
+
+    process::exit(exit_code(&options, result))
+}
+
+fn run_jobs(jobs: &[jobs::Job], term_sig: &Arc<AtomicUsize>) -> i32 {
+    let mut code = 0;
+
+    for job in jobs {
+        let result = findr::Command::new(&job.options).and_then(|c| c.run(Arc::clone(term_sig)));
+        let job_code = exit_code(&job.options, result);
+
+        eprintln!(
+            "{}: job '{}' finished with exit code {}",
+            crate_name!(),
+            job.name,
+            job_code
+        );
+
+        code = code.max(job_code);
+
+        if term_sig.load(Ordering::Relaxed) != 0 {
+            break;
+        }
+    }
+
+    code
+}
+
+fn exit_code(options: &options::Options, result: anyhow::Result<findr::Summary>) -> i32 {
+    match result {
+        Ok(summary) if options.strict_exit => ExitCode::from(&summary).code(),
+        Ok(_) => 0,
         Err(e) => match e.downcast::<findr::Error>() {
             Ok(e) => match e {
-                findr::Error::Terminated(u) => (u + SIG_EXIT_MARKER).try_into().unwrap(),
+                findr::Error::Terminated(u) => exit::signal_code(u),
                 findr::Error::InvalidRootDir(e) => {
                     eprintln!("{}: {}", crate_name!(), e);
 
-                    1
+                    strict_code(options, ExitCode::Usage)
+                }
+                findr::Error::InvalidArgument(e) => {
+                    eprintln!("{}: {}", crate_name!(), e);
+
+                    strict_code(options, ExitCode::Usage)
+                }
+                findr::Error::FilterEvaluation(e) => {
+                    eprintln!(
+                        "{}: {}: {}",
+                        crate_name!(),
+                        options.lang().filter_evaluation_failed(),
+                        e
+                    );
+
+                    strict_code(options, ExitCode::PartialErrors)
+                }
+                findr::Error::Locked(_) => {
+                    eprintln!("{}: {}", crate_name!(), e);
+
+                    strict_code(options, ExitCode::Locked)
+                }
+                findr::Error::Output(e) => {
+                    eprintln!("{}: {}", crate_name!(), e);
+
+                    strict_code(options, ExitCode::PartialErrors)
                 }
             },
             Err(e) => {
                 eprintln!("{e}");
 
-                1
+                strict_code(options, ExitCode::PartialErrors)
             }
         },
-    };
+    }
+}
 
-    process::exit(code)
+fn strict_code(options: &options::Options, code: ExitCode) -> i32 {
+    if options.strict_exit {
+        code.code()
+    } else {
+        1
+    }
 }
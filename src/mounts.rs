@@ -0,0 +1,199 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::time::{Duration, Instant};
+
+const MOUNTINFO: &str = "/proc/self/mountinfo";
+
+/// Periodically re-reads the mount table during long
+/// searches to report filesystems which were mounted or
+/// unmounted after the search started.
+pub struct MountWatcher {
+    interval: Duration,
+    last_check: Instant,
+    mounts: BTreeSet<String>,
+}
+
+impl MountWatcher {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_check: Instant::now(),
+            mounts: read_mounts().unwrap_or_default(),
+        }
+    }
+    /// Re-reads the mount table if the interval has elapsed
+    /// since the last check and reports any changes.
+    pub fn poll(&mut self) -> Option<MountChange> {
+        if self.last_check.elapsed() < self.interval {
+            return None;
+        }
+
+        self.last_check = Instant::now();
+
+        self.observe(read_mounts()?)
+    }
+    fn observe(&mut self, mounts: BTreeSet<String>) -> Option<MountChange> {
+        let change = MountChange {
+            mounted: mounts.difference(&self.mounts).cloned().collect(),
+            unmounted: self.mounts.difference(&mounts).cloned().collect(),
+        };
+
+        self.mounts = mounts;
+
+        (!change.mounted.is_empty() || !change.unmounted.is_empty()).then_some(change)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MountChange {
+    pub mounted: Vec<String>,
+    pub unmounted: Vec<String>,
+}
+
+/// Maps device numbers to the type of the filesystem
+/// mounted from them, e.g. 'ext4' or 'tmpfs'.
+#[derive(Debug, Default)]
+pub struct FsTypes(HashMap<u64, String>);
+
+impl FsTypes {
+    pub fn load() -> anyhow::Result<Self> {
+        let info = fs::read_to_string(MOUNTINFO)
+            .map_err(|e| anyhow::anyhow!("unable to read '{}': {}", MOUNTINFO, e))?;
+
+        Ok(Self::parse(&info))
+    }
+    // The device is the third field of each line and the
+    // filesystem type the first field after the '-'
+    // separating the optional fields.
+    fn parse(info: &str) -> Self {
+        Self(
+            info.lines()
+                .filter_map(|l| {
+                    let (major, minor) = l.split(' ').nth(2)?.split_once(':')?;
+                    let fstype = l.split(" - ").nth(1)?.split(' ').next()?;
+
+                    Some((
+                        libc::makedev(major.parse().ok()?, minor.parse().ok()?),
+                        fstype.to_string(),
+                    ))
+                })
+                .collect(),
+        )
+    }
+    /// Returns the type of the filesystem on the given device
+    /// if it is mounted.
+    pub fn get(&self, dev: u64) -> Option<&str> {
+        self.0.get(&dev).map(String::as_str)
+    }
+}
+
+fn read_mounts() -> Option<BTreeSet<String>> {
+    fs::read_to_string(MOUNTINFO)
+        .ok()
+        .map(|info| parse_mountinfo(&info))
+}
+
+// The mount point is the fifth field of each line.
+fn parse_mountinfo(info: &str) -> BTreeSet<String> {
+    info.lines()
+        .filter_map(|l| l.split(' ').nth(4))
+        .map(unescape)
+        .collect()
+}
+
+// The kernel escapes spaces, tabs, newlines and backslashes
+// in paths as three digit octal sequences, e.g. '\040'.
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let octal = bytes
+            .get(i + 1..i + 4)
+            .filter(|d| bytes[i] == b'\\' && d.iter().all(|b| (b'0'..=b'7').contains(b)))
+            .and_then(|d| u8::from_str_radix(std::str::from_utf8(d).ok()?, 8).ok());
+
+        match octal {
+            Some(b) => {
+                out.push(b);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_mountinfo, unescape, FsTypes, MountChange, MountWatcher};
+    use std::collections::BTreeSet;
+    use std::time::Duration;
+    use test_case::test_case;
+
+    const INFO: &str = "\
+23 28 0:22 / /proc rw,relatime - proc proc rw
+28 1 8:1 / / rw,relatime - ext4 /dev/sda1 rw
+40 28 0:40 / /mnt/backup rw,relatime - nfs host:/backup rw
+";
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            BTreeSet::from(["/".to_string(), "/mnt/backup".into(), "/proc".into()]),
+            parse_mountinfo(INFO)
+        )
+    }
+
+    #[test_case("/mnt/backup", "/mnt/backup" ; "plain")]
+    #[test_case(r"/mnt/usb\040drive", "/mnt/usb drive" ; "space")]
+    #[test_case(r"/mnt/a\011b\012c\134d", "/mnt/a\tb\nc\\d" ; "tab newline and backslash")]
+    #[test_case(r"/mnt/caf\303\251", "/mnt/caf\u{e9}" ; "multibyte")]
+    #[test_case(r"/mnt/a\9b\04", r"/mnt/a\9b\04" ; "not octal")]
+    fn unescape_field(field: &str, expected: &str) {
+        assert_eq!(expected, unescape(field))
+    }
+
+    #[test]
+    fn fstypes() {
+        let types = FsTypes::parse(concat!(
+            "28 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw\n",
+            "40 28 0:40 / /mnt/backup rw,relatime - nfs host:/backup rw\n",
+            "malformed\n",
+        ));
+
+        assert_eq!(Some("ext4"), types.get(libc::makedev(8, 1)));
+        assert_eq!(Some("nfs"), types.get(libc::makedev(0, 40)));
+        assert_eq!(None, types.get(libc::makedev(8, 2)));
+    }
+
+    #[test]
+    fn observe() {
+        let mut w = MountWatcher::new(Duration::ZERO);
+
+        w.mounts = parse_mountinfo(INFO);
+
+        assert_eq!(None, w.observe(parse_mountinfo(INFO)));
+
+        let change = w.observe(parse_mountinfo(
+            "28 1 8:1 / / rw - ext4 /dev/sda1 rw\n41 28 0:41 / /mnt/usb rw - vfat /dev/sdb1 rw\n",
+        ));
+
+        assert_eq!(
+            Some(MountChange {
+                mounted: vec!["/mnt/usb".into()],
+                unmounted: vec!["/mnt/backup".into(), "/proc".into()],
+            }),
+            change
+        );
+    }
+}
@@ -0,0 +1,206 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::ffi::CStr;
+use users::os::unix::GroupExt;
+
+/// Snapshot of the user and group databases which is read
+/// once up front rather than queried for every lookup.
+/// Numeric ids are always taken as is.
+#[derive(Debug, Default)]
+pub struct IdDatabase {
+    enabled: bool,
+    users: HashMap<String, u32>,
+    groups: HashMap<String, u32>,
+}
+
+impl IdDatabase {
+    /// Enumerates all users and groups known to NSS.
+    pub fn load() -> Self {
+        Self {
+            enabled: true,
+            users: load_users(),
+            groups: load_groups(),
+        }
+    }
+    /// Returns a database which resolves numeric ids only.
+    pub fn numeric() -> Self {
+        Self::default()
+    }
+    pub fn uid(&self, s: &str) -> anyhow::Result<u32> {
+        self.resolve(s, &self.users, |name| {
+            users::get_user_by_name(name).map(|u| u.uid())
+        })
+        .ok_or_else(|| anyhow::anyhow!("invalid user '{}'", s))
+    }
+    pub fn gid(&self, s: &str) -> anyhow::Result<u32> {
+        self.resolve(s, &self.groups, |name| {
+            users::get_group_by_name(name).map(|g| g.gid())
+        })
+        .ok_or_else(|| anyhow::anyhow!("invalid group '{}'", s))
+    }
+    /// Returns the uids of every enumerated user.
+    pub fn uids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.users.values().copied()
+    }
+    /// Returns the gids of every enumerated group.
+    pub fn gids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.groups.values().copied()
+    }
+    fn resolve(
+        &self,
+        s: &str,
+        ids: &HashMap<String, u32>,
+        lookup: impl Fn(&str) -> Option<u32>,
+    ) -> Option<u32> {
+        if let Ok(id) = s.parse::<u32>() {
+            return Some(id);
+        }
+        if !self.enabled {
+            return None;
+        }
+
+        // Hosts backed by directory services often disable
+        // enumeration so fall back to a direct lookup.
+        ids.get(s).copied().or_else(|| lookup(s))
+    }
+}
+
+/// Returns the ids of every group the given user, by name
+/// or numeric id, belongs to including its primary group.
+pub fn user_groups(s: &str) -> anyhow::Result<Vec<u32>> {
+    let user = match s.parse::<u32>() {
+        Ok(uid) => users::get_user_by_uid(uid),
+        Err(_) => users::get_user_by_name(s),
+    }
+    .ok_or_else(|| anyhow::anyhow!("invalid user '{}'", s))?;
+    let groups = users::get_user_groups(user.name(), user.primary_group_id())
+        .ok_or_else(|| anyhow::anyhow!("failed to list the groups of user '{}'", s))?;
+
+    Ok(groups.iter().map(|g| g.gid()).collect())
+}
+
+/// Returns the ids of every user belonging to the given
+/// group either as their primary group or as a listed
+/// member. Only users which can be enumerated are found
+/// through their primary group.
+pub fn group_members(gid: u32) -> Vec<u32> {
+    let listed = users::get_group_by_gid(gid)
+        .map(|g| g.members().to_vec())
+        .unwrap_or_default();
+    let mut uids = listed
+        .iter()
+        .filter_map(users::get_user_by_name)
+        .map(|u| u.uid())
+        .collect::<Vec<u32>>();
+
+    // SAFETY: see load_users.
+    for user in unsafe { users::all_users() } {
+        if user.primary_group_id() == gid && !uids.contains(&user.uid()) {
+            uids.push(user.uid());
+        }
+    }
+
+    uids
+}
+
+fn load_users() -> HashMap<String, u32> {
+    let mut users = HashMap::new();
+
+    // SAFETY: getpwent is not thread-safe, but the database is
+    // only loaded once from the main thread before searching.
+    unsafe {
+        libc::setpwent();
+
+        loop {
+            let pw = libc::getpwent();
+
+            if pw.is_null() {
+                break;
+            }
+
+            users.insert(
+                CStr::from_ptr((*pw).pw_name).to_string_lossy().into_owned(),
+                (*pw).pw_uid,
+            );
+        }
+
+        libc::endpwent();
+    }
+
+    users
+}
+
+fn load_groups() -> HashMap<String, u32> {
+    let mut groups = HashMap::new();
+
+    // SAFETY: see load_users.
+    unsafe {
+        libc::setgrent();
+
+        loop {
+            let gr = libc::getgrent();
+
+            if gr.is_null() {
+                break;
+            }
+
+            groups.insert(
+                CStr::from_ptr((*gr).gr_name).to_string_lossy().into_owned(),
+                (*gr).gr_gid,
+            );
+        }
+
+        libc::endgrent();
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{group_members, user_groups, IdDatabase};
+    use users::{get_current_gid, get_current_groupname, get_current_uid, get_current_username};
+
+    #[test]
+    fn load_resolves_current_user_and_group() {
+        let db = IdDatabase::load();
+        let user = get_current_username().unwrap();
+        let group = get_current_groupname().unwrap();
+
+        assert_eq!(get_current_uid(), db.uid(&user.to_string_lossy()).unwrap());
+        assert_eq!(get_current_gid(), db.gid(&group.to_string_lossy()).unwrap());
+    }
+    #[test]
+    fn load_enumerates_ids() {
+        let db = IdDatabase::load();
+
+        assert!(db.uids().any(|u| u == get_current_uid()));
+        assert!(db.gids().any(|g| g == get_current_gid()));
+        assert_eq!(0, IdDatabase::numeric().uids().count());
+    }
+    #[test]
+    fn groups_of_current_user() {
+        let user = get_current_username().unwrap();
+        let groups = user_groups(&user.to_string_lossy()).unwrap();
+
+        assert!(groups.contains(&get_current_gid()));
+        assert_eq!(groups, user_groups(&get_current_uid().to_string()).unwrap());
+        assert!(user_groups("dne_user").is_err());
+    }
+    #[test]
+    fn members_of_primary_group() {
+        assert!(group_members(get_current_gid()).contains(&get_current_uid()));
+    }
+    #[test]
+    fn numeric_rejects_names() {
+        let db = IdDatabase::numeric();
+
+        assert_eq!(4242, db.uid("4242").unwrap());
+        assert_eq!(4242, db.gid("4242").unwrap());
+        assert!(db.uid("root").is_err());
+        assert!(db.gid("root").is_err());
+    }
+}
@@ -0,0 +1,78 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashSet;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+
+const PROC: &str = "/proc";
+
+/// Device and inode numbers of every file held open by any
+/// process as listed under '/proc/PID/fd'. The table is
+/// read once so files opened or closed during a search are
+/// not noticed.
+pub struct OpenFiles(HashSet<(u64, u64)>);
+
+impl OpenFiles {
+    /// Processes which exit while being scanned or whose
+    /// descriptors may not be inspected are skipped, so
+    /// files opened by other users are only found with
+    /// sufficient privileges.
+    pub fn load() -> anyhow::Result<Self> {
+        let procs =
+            fs::read_dir(PROC).map_err(|e| anyhow::anyhow!("unable to read '{}': {}", PROC, e))?;
+
+        Ok(Self(
+            procs
+                .filter_map(Result::ok)
+                .filter(|p| p.file_name().to_str().is_some_and(is_pid))
+                .filter_map(|p| fs::read_dir(p.path().join("fd")).ok())
+                .flatten()
+                .filter_map(Result::ok)
+                .filter_map(|fd| fs::metadata(fd.path()).ok())
+                .map(|m| (m.dev(), m.ino()))
+                .collect(),
+        ))
+    }
+    pub fn contains(&self, dev: u64, ino: u64) -> bool {
+        self.0.contains(&(dev, ino))
+    }
+}
+
+fn is_pid(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_pid, OpenFiles};
+    use anyhow::Result;
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+    use test_case::test_case;
+
+    #[test_case("1", true ; "init")]
+    #[test_case("4242", true ; "pid")]
+    #[test_case("self", false ; "self")]
+    #[test_case("", false ; "empty")]
+    fn pid(name: &str, expected: bool) {
+        assert_eq!(expected, is_pid(name))
+    }
+
+    #[test]
+    fn held_open() -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        let closed = tempfile::TempPath::from_path(file.path().with_extension("closed"));
+
+        fs::write(&closed, "")?;
+
+        let open = OpenFiles::load()?;
+        let (held, released) = (fs::metadata(file.path())?, fs::metadata(&closed)?);
+
+        assert!(open.contains(held.dev(), held.ino()));
+        assert!(!open.contains(released.dev(), released.ino()));
+
+        Ok(file.close()?)
+    }
+}
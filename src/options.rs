@@ -2,52 +2,473 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::audit::{AuditKind, ExpectationSpec};
+use crate::constraints;
+use crate::duplicates::DuplicatesFormat;
+use crate::escape::Escape;
+use crate::estimate::SampleFraction;
+use crate::exit::ExitCodesFormat;
 use crate::filter::*;
-use clap::Parser;
-use regex::{self, Regex};
+use crate::i18n::Lang;
+use crate::lock::LockPolicy;
+use crate::order::VisitOrder;
+use crate::output::OutputFormat;
+use crate::pattern::{MatchTarget, Normalization, RegexEngine};
+use crate::sink::SinkErrorPolicy;
+use crate::split::SplitPattern;
+use crate::template::CommandTemplate;
+use crate::transform::PathTransform;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use humantime::Duration;
+use regex::Regex;
+use std::ffi::OsString;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 #[clap(name = clap::crate_name!())]
 #[clap(author = clap::crate_authors!())]
 #[clap(about = clap::crate_description!())]
+#[clap(args_conflicts_with_subcommands = true)]
 pub struct Options {
     /// filters results based on last access time.
     /// By default the value provided filters for
     /// results with an access time further in the past.
     /// Prefixing the value with '-' filters for results
     /// more recent than the value given instead.
+    /// Values are either durations, e.g. '7d', or UTC
+    /// timestamps, e.g. '2024-01-01' or '2024-01-01T12:00:00Z'.
+    /// Ranges such as '1d..7d' filter for results within
+    /// the window between both values.
     #[arg(long = "atime")]
-    pub atime_filters: Vec<DurationFilter>,
+    pub atime_filters: Vec<TimeFilter>,
+    /// filters results based on the number of whole
+    /// 24-hour periods since their last access time like
+    /// 'find -atime' rather than by exact durations, e.g.
+    /// '1' for results accessed between one and two days
+    /// ago, '+1' for at least two days ago and '-1' for
+    /// less than one day ago.
+    #[arg(long = "atime-days")]
+    pub atime_days: Vec<DaysFilter>,
+    /// when enabled reports results which deviate from the
+    /// policy given with '--expect' instead and writes a
+    /// summary of the deviations found to stderr.
+    #[arg(long = "audit", value_enum)]
+    pub audit: Option<AuditKind>,
     /// filters results based on last changed time.
     /// By default the value provided filters for
     /// results with a change time further in the past.
     /// Prefixing the value with '-' filters for results
     /// more recent than the value given instead.
+    /// Values are either durations, e.g. '7d', or UTC
+    /// timestamps, e.g. '2024-01-01' or '2024-01-01T12:00:00Z'.
+    /// Ranges such as '1d..7d' filter for results within
+    /// the window between both values.
     #[arg(long = "ctime")]
-    pub ctime_filters: Vec<DurationFilter>,
+    pub ctime_filters: Vec<TimeFilter>,
+    /// filters results based on the number of whole
+    /// 24-hour periods since their last changed time like
+    /// 'find -ctime' rather than by exact durations, e.g.
+    /// '1' for results changed between one and two days
+    /// ago, '+1' for at least two days ago and '-1' for
+    /// less than one day ago.
+    #[arg(long = "ctime-days")]
+    pub ctime_days: Vec<DaysFilter>,
     /// filters results based on creation time.
     /// By default the value provided filters for
     /// results with a creation time further in the past.
     /// Prefixing the value with '-' filters for results
     /// more recent than the value given instead.
+    /// Values are either durations, e.g. '7d', or UTC
+    /// timestamps, e.g. '2024-01-01' or '2024-01-01T12:00:00Z'.
+    /// Ranges such as '1d..7d' filter for results within
+    /// the window between both values.
     #[arg(long = "creation-time")]
-    pub creation_time_filters: Vec<DurationFilter>,
+    pub creation_time_filters: Vec<TimeFilter>,
+    /// when enabled skips the contents of directories
+    /// tagged with a valid CACHEDIR.TAG file as described
+    /// by the Cache Directory Tagging Standard. The tag
+    /// file itself is still reported. Defaults to 'false'.
+    #[arg(long = "exclude-caches")]
+    pub exclude_caches: bool,
+    /// skips entries whose path matches the given regular
+    /// expression along with the contents of matching
+    /// directories, e.g. '/target$' or '/\.git$'. May be given
+    /// several times to skip entries matching any of them.
+    /// Root directories are never skipped.
+    #[arg(long = "exclude")]
+    pub excludes: Vec<Regex>,
+    /// reads '--exclude' expressions from the given file,
+    /// one per line. Blank lines and lines starting with '#'
+    /// are ignored. May be given several times.
+    #[arg(long = "exclude-from")]
+    pub exclude_from: Vec<PathBuf>,
+    /// when enabled hidden entries, those whose name starts
+    /// with '.', are searched. Overrides an earlier
+    /// '--no-hidden'. Defaults to 'true'.
+    #[arg(long = "hidden", overrides_with = "no_hidden")]
+    pub hidden: bool,
+    /// when enabled hidden entries are skipped without
+    /// descending into hidden directories such as '.git'.
+    /// Roots are always searched. Overrides an earlier
+    /// '--hidden'. Defaults to 'false'.
+    #[arg(long = "no-hidden", overrides_with = "hidden")]
+    pub no_hidden: bool,
+    /// when enabled filters for empty regular files and
+    /// directories without entries. Equivalent to
+    /// '--type=empty' but combines with any other '--type'.
+    /// Defaults to 'false'.
+    #[arg(long = "empty")]
+    pub empty: bool,
+    /// when enabled filters for sparse regular files whose
+    /// allocated blocks take up less space than their
+    /// apparent size, e.g. VM images. Defaults to 'false'.
+    #[arg(long = "sparse")]
+    pub sparse: bool,
+    /// when enabled '--size' filters and reported sizes use
+    /// the space allocated on disk like 'du' rather than the
+    /// apparent size of results. Defaults to 'false'.
+    #[arg(long = "disk-usage")]
+    pub disk_usage: bool,
+    /// escapes result paths for the consuming system after
+    /// any '--map' transformations. 'url' percent-encodes
+    /// paths while 'json' and 'c' write them as quoted
+    /// JSON and C string literals respectively.
+    #[arg(long = "escape", value_enum)]
+    pub escape: Option<Escape>,
+    /// specifies an expectation checked by '--audit'.
+    /// May be given multiple times. Supported expectations
+    /// are 'owner=OWNER' accepting the same values as
+    /// '--owner' and 'mode=OCTAL' matching POSIX
+    /// permissions exactly.
+    #[arg(long = "expect")]
+    pub expectations: Vec<ExpectationSpec>,
+    /// prints which options cannot be combined or
+    /// require one another along with the reason for
+    /// each rule and exits.
+    #[arg(long = "explain-conflicts")]
+    pub explain_conflicts: bool,
+    /// prints the exit codes used when '--strict-exit'
+    /// is enabled in the given format and exits.
+    #[arg(long = "exit-codes", value_enum)]
+    pub exit_codes: Option<ExitCodesFormat>,
+    /// prints a shell command for every result instead
+    /// of its path. '{}' is replaced with the path, '{.}'
+    /// with the path without extension, '{/}' with the
+    /// basename, '{/.}' with the basename without extension
+    /// and '{//}' with the parent directory. All values are
+    /// quoted. The path is appended when no placeholder
+    /// is given.
+    #[arg(long = "emit-cmd")]
+    pub emit_cmd: Option<CommandTemplate>,
+    /// searches the results stored in a SQLite database by
+    /// a previous search with '--output sqlite:PATH' instead
+    /// of the filesystem. Filters depending on metadata which
+    /// is not stored such as '--atime' fail to evaluate and
+    /// are handled according to '--on-filter-error'. Types
+    /// which are only known for live entries such as
+    /// 'empty-dir' or 'text' are rejected.
+    /// Requires the 'sqlite' feature.
+    #[arg(long = "from-db")]
+    pub from_db: Option<PathBuf>,
     /// filters results with names matching the given
-    /// regular expression.
+    /// regular expression. May be given several times to
+    /// match results matching any of the expressions.
     #[arg(short = 'p', long = "pattern")]
-    pub pattern: Option<Regex>,
+    pub patterns: Vec<String>,
+    /// reads additional '--pattern' expressions from the
+    /// given file, one per line. Blank lines and lines
+    /// starting with '#' are ignored. May be given several
+    /// times.
+    #[arg(long = "pattern-file")]
+    pub pattern_files: Vec<PathBuf>,
+    /// when enabled '--pattern' is matched as a literal
+    /// substring rather than a regular expression so that
+    /// characters such as '.', '+' or '(' need no escaping.
+    /// Defaults to 'false'.
+    #[arg(short = 'F', long = "fixed-strings")]
+    pub fixed_strings: bool,
+    /// filters results matching the given shell style glob,
+    /// e.g. '*.rs' or '**/target/**'. Globs without a '/'
+    /// are matched against the file name of each result and
+    /// all others against its path in which case only '**'
+    /// matches across directories.
+    #[arg(short = 'g', long = "glob")]
+    pub glob: Option<GlobFilter>,
+    /// specifies the regular expression engine used for
+    /// '--pattern'. 'fancy' additionally supports look-around
+    /// and backreferences, e.g. 'foo(?!/bar)', at the cost of
+    /// matching in exponential time in the worst case.
+    /// Requires the 'fancy-regex' feature. Defaults to
+    /// 'default'.
+    #[arg(long = "regex-engine", value_enum, default_value_t)]
+    pub regex_engine: RegexEngine,
+    /// filters results whose file name has the given
+    /// extension, e.g. 'rs' or 'tar.gz'. May be given several
+    /// times to match results with any of the extensions.
+    #[arg(short = 'e', long = "ext")]
+    pub extensions: Vec<ExtensionFilter>,
+    /// filters results lying anywhere beneath a directory
+    /// with the given name, e.g. 'tests'. Whole path
+    /// components are compared. May be given several times
+    /// to match results beneath any of the directories.
+    #[arg(long = "in-dir")]
+    pub in_dirs: Vec<InDirFilter>,
+    /// filters results with the given inode number, e.g.
+    /// as reported by fsck or quota tools. Hard links share
+    /// their inode number so every link to it is reported.
+    #[arg(long = "inum")]
+    pub inum: Option<u64>,
+    /// filters results sharing the device and inode number
+    /// of the given reference file, i.e. hard links to it,
+    /// like 'find -samefile'. Symlinks given as reference
+    /// are resolved first.
+    #[arg(long = "samefile")]
+    pub samefile: Option<PathBuf>,
+    /// when enabled only the first result found for each
+    /// device and inode number is output so that hard
+    /// links to the same file appear once.
+    /// Defaults to 'false'.
+    #[arg(long = "unique-inodes")]
+    pub unique_inodes: bool,
+    /// when enabled filters for results currently held open
+    /// by any process as listed under '/proc', e.g. logs
+    /// which are still being written. Files opened by other
+    /// users are only found with sufficient privileges.
+    /// Defaults to 'false'.
+    #[arg(long = "open")]
+    pub open: bool,
+    /// filters results residing on a filesystem of the
+    /// given type as listed in '/proc/self/mountinfo', e.g.
+    /// 'ext4', 'tmpfs' or 'nfs'.
+    #[arg(long = "fstype")]
+    pub fstype: Option<String>,
+    /// filters results carrying the given extended attribute,
+    /// e.g. 'user.backup.exclude', or with the given value if
+    /// given as 'NAME=VALUE'. Attributes of symlinks rather
+    /// than their targets are read. May be given several
+    /// times to match results carrying all of them. Requires
+    /// findr to be built with the 'xattr' feature.
+    #[arg(long = "xattr")]
+    pub xattrs: Vec<XattrFilter>,
+    /// filters regular files and directories carrying all
+    /// of the given Linux inode flags by their 'lsattr'
+    /// letters, e.g. 'i' for immutable or 'a' for
+    /// append-only. Only supported on Linux.
+    #[arg(long = "attr")]
+    pub attr: Option<AttrFilter>,
+    /// filters regular files and directories assigned to
+    /// the given XFS or ext4 project quota id. Only
+    /// supported on Linux.
+    #[arg(long = "project-id")]
+    pub project_id: Option<ProjectIdFilter>,
+    /// filters results whose SELinux security context
+    /// matches the given regular expression, e.g. ':bin_t:'.
+    /// Results without a context never match. Requires findr
+    /// to be built with the 'selinux' feature on Linux.
+    #[arg(long = "context")]
+    pub context: Option<ContextFilter>,
+    /// when enabled filters for results carrying a POSIX
+    /// access ACL beyond their basic mode bits.
+    /// Defaults to 'false'.
+    #[arg(long = "has-acl")]
+    pub has_acl: bool,
+    /// when enabled filters for results without a POSIX
+    /// access ACL. Defaults to 'false'.
+    #[arg(long = "no-acl")]
+    pub no_acl: bool,
+    /// when enabled filters for files carrying Linux file
+    /// capabilities. Defaults to 'false'.
+    #[arg(long = "has-caps")]
+    pub has_caps: bool,
+    /// filters files carrying the given capability, e.g.
+    /// 'cap_net_raw', optionally in all of the given sets
+    /// like 'cap_net_raw+ep' where 'e', 'p' and 'i' refer to
+    /// the effective, permitted and inheritable sets. May
+    /// be given several times to match files carrying all
+    /// of them.
+    #[arg(long = "caps")]
+    pub caps: Vec<CapsFilter>,
+    /// when enabled filters for results the invoking user
+    /// may read as decided by the kernel rather than mode
+    /// bits so that ACLs and capabilities are honored like
+    /// 'find -readable'. Defaults to 'false'.
+    #[arg(long = "readable")]
+    pub readable: bool,
+    /// when enabled filters for results the invoking user
+    /// may write like '--readable'. Defaults to 'false'.
+    #[arg(long = "writable")]
+    pub writable: bool,
+    /// when enabled filters for results the invoking user
+    /// may execute, or search in the case of directories,
+    /// like '--readable'. Defaults to 'false'.
+    #[arg(long = "executable")]
+    pub executable: bool,
+    /// filters results based on their number of hard links.
+    /// Accepts the same '+' and '-' prefixes as '--size', e.g.
+    /// '+1' for results with more than one link.
+    #[arg(long = "links")]
+    pub links_filters: Vec<CountFilter>,
+    /// filters directories based on their number of direct
+    /// entries. Accepts the same values as '--links', e.g.
+    /// '+10000' for directories with more than 10000
+    /// entries. Directories are only read as far as needed.
+    #[arg(long = "entries")]
+    pub entries: Option<EntriesFilter>,
+    /// filters symlinks whose target, as stored in the link
+    /// rather than resolved, matches the given regular
+    /// expression like 'find -lname'. Entries which are not
+    /// symlinks never match.
+    #[arg(long = "lname")]
+    pub lname: Option<Regex>,
+    /// filters symlinks by where following their chain of
+    /// links leads, 'resolvable' for an existing entry,
+    /// 'dangling' for a missing one and 'cyclic' for a loop
+    /// or a chain longer than '--max-link-depth'. Entries
+    /// which are not symlinks never match. Defaults to
+    /// 'resolvable' when given without a value.
+    #[arg(
+        long = "resolvable",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "resolvable"
+    )]
+    pub resolvable: Option<LinkState>,
+    /// limits how many links are followed by '--resolvable'
+    /// before a chain is considered cyclic, e.g. '1' to only
+    /// accept symlinks pointing directly at their target.
+    /// Defaults to '40'.
+    #[arg(long = "max-link-depth", default_value_t = DEFAULT_MAX_LINK_DEPTH)]
+    pub max_link_depth: u32,
+    /// filters files whose contents match the given regular
+    /// expression, e.g. 'TODO' or '^#!/bin/(ba)?sh'. Files
+    /// are read line by line up to the first match so that
+    /// memory use stays bounded, meaning matches never span
+    /// lines. Only the first '--max-read' bytes are searched
+    /// unless '--full-content' is given. Entries which are
+    /// not files never match.
+    #[arg(long = "contains")]
+    pub contains: Option<ContentFilter>,
+    /// filters files whose contents hash to the given
+    /// digest written in hexadecimal as 'ALGORITHM=DIGEST',
+    /// e.g. 'sha256=9f86d0...'. Valid algorithms are 'md5',
+    /// 'sha256' and 'blake3'. When given multiple times
+    /// files matching any of the digests are reported.
+    /// Only files within '--max-read' are hashed in full
+    /// so larger ones never match without '--full-content'.
+    #[arg(long = "hash")]
+    pub hashes: Vec<HashFilter>,
+    /// specifies the maximum number of bytes read from each
+    /// file by '--contains', '--hash' and '--type=text' or
+    /// 'binary', e.g. '1MiB'. Only the head of larger files
+    /// is inspected so trees containing huge files are
+    /// searched in bounded time. Defaults to '64KiB'.
+    #[arg(long = "max-read", default_value = "64KiB", value_parser = parse_size)]
+    pub max_read: u64,
+    /// when enabled files are read in full by content
+    /// filters regardless of their size. Defaults to 'false'.
+    #[arg(long = "full-content")]
+    pub full_content: bool,
+    /// specifies what '--pattern' is matched against. 'path'
+    /// matches the whole path of each result while 'basename'
+    /// only matches its file name like 'find -name', so that
+    /// e.g. '^build$' matches directories named 'build'.
+    /// Defaults to 'path'.
+    #[arg(long = "match-on", value_enum, default_value_t)]
+    pub match_on: MatchTarget,
+    /// normalizes '--pattern' and the paths it is matched
+    /// against to the given Unicode normalization form, 'nfc'
+    /// or 'nfd', so that e.g. names created on macOS, which
+    /// are decomposed, match patterns typed composed.
+    #[arg(long = "normalize", value_enum)]
+    pub normalize: Option<Normalization>,
+    /// when enabled '--pattern' is matched against the path
+    /// of each result relative to the root directory it was
+    /// found in, e.g. 'src/main.rs' rather than
+    /// '/home/user/project/src/main.rs'. Roots themselves are
+    /// matched as '.'. Defaults to 'false'.
+    #[arg(long = "relative-match")]
+    pub relative_match: bool,
+    /// when enabled '--pattern' and '--glob' match regardless
+    /// of case. Defaults to 'false'.
+    #[arg(short = 'i', long = "ignore-case")]
+    pub ignore_case: bool,
+    /// when enabled each '--pattern' and '--glob' without
+    /// uppercase letters matches regardless of case while
+    /// those with uppercase letters match exactly, e.g.
+    /// 'readme' matches 'README.md' but 'README' does not
+    /// match 'readme.md'. Defaults to 'false'.
+    #[arg(short = 'S', long = "smart-case")]
+    pub smart_case: bool,
     /// specifies the root directories to descend into
     /// when searching.
     #[arg(default_value = ".")]
     pub dirs: Vec<PathBuf>,
     /// filters results matching the given entry types.
-    #[arg(short = 't', long = "type", value_enum)]
-    pub type_filters: Vec<TypeFilter>,
+    /// Multiple types may be separated by ','. Prefixing a
+    /// type with '!' filters results which are not of the
+    /// given type instead. Results must match at least one
+    /// of the types given and none of the negated types.
+    /// Valid types are 'block-device' (b), 'broken-sym-link'
+    /// (bl), 'char-device' (c), 'dir' (d), 'empty',
+    /// 'empty-dir', 'non-empty-dir', 'executable' (x),
+    /// 'file' (f), 'mount-point', 'non-empty-file',
+    /// 'text', 'binary', 'pipe' (p), 'socket' (s) and
+    /// 'sym-link' (l). 'text' and 'binary' classify files
+    /// by whether their first block is valid UTF-8 without
+    /// NUL bytes like 'grep'.
+    #[arg(short = 't', long = "type", value_delimiter = ',')]
+    pub type_filters: Vec<TypeSelector>,
+    /// transforms each result path before it is output.
+    /// May be given multiple times to apply transformations
+    /// in order. Supported transformations are sed regex
+    /// substitutions, e.g. 's#^/srv/www/##', where '\1' to
+    /// '\9' and '&' refer to groups and the whole match and
+    /// the delimiter is escaped with '\', with an optional
+    /// 'g' flag, 'prepend:PREFIX', 'append:SUFFIX' and
+    /// 'urlencode'.
+    #[arg(long = "map")]
+    pub path_transforms: Vec<PathTransform>,
+    /// the jobs selected with '--job' from the file given
+    /// to the 'run-jobs' subcommand. By default all jobs
+    /// are run.
+    #[arg(skip)]
+    pub job_names: Vec<String>,
+    /// the jobs file given to the 'run-jobs' subcommand.
+    #[arg(skip)]
+    pub jobs: Option<PathBuf>,
+    /// specifies the language of user facing messages
+    /// such as summaries and errors. Defaults to the
+    /// language of the locale given by 'LC_ALL',
+    /// 'LC_MESSAGES' or 'LANG' falling back to 'en'.
+    #[arg(long = "lang", value_enum)]
+    pub lang: Option<Lang>,
+    /// specifies a file to take an exclusive lock on for
+    /// the duration of the search so overlapping searches,
+    /// e.g. from cron, do not run concurrently. The file
+    /// is created if it does not exist.
+    #[arg(long = "lock")]
+    pub lock: Option<PathBuf>,
+    /// specifies the maximum number of bytes of results
+    /// written, e.g. '10MB'. Once reached no more results
+    /// are written and a trailer reporting the number of
+    /// omitted matches is appended instead. With
+    /// '--output=events' only match events are counted and
+    /// the omitted matches are reported by the summary event.
+    #[arg(long = "max-output", value_parser = parse_size)]
+    pub max_output: Option<u64>,
     /// specifies the maximum level of nested directories
     /// to descend into.
     #[arg(long = "max-depth")]
     pub max_depth: Option<usize>,
+    /// when enabled rejects any option which would read
+    /// the contents of files encountered during the search,
+    /// guaranteeing that only names and metadata are read.
+    /// '--explain-conflicts' lists the options rejected.
+    /// Defaults to 'false'.
+    #[arg(long = "metadata-only")]
+    pub metadata_only: bool,
     /// specifies the minimum level of nested directories
     /// to descend into.
     #[arg(long = "min-depth")]
@@ -57,22 +478,287 @@ pub struct Options {
     /// '+' will match all results which have at least the
     /// given permissions. Conversly prefixing with '~'
     /// will match results which do not have the given
-    /// permissions.
+    /// permissions. Special bits such as setuid are only
+    /// compared exactly when given, e.g. '4755'. Symbolic
+    /// modes are accepted as well where 'u=rwx,g=rx,o='
+    /// matches exactly those permissions, 'g+w' matches at
+    /// least them and 'o-w' matches none of them.
     #[arg(long = "mode")]
     pub mode: Option<OctalFilter>,
+    /// when enabled filters for results with the setuid bit
+    /// set. Defaults to 'false'.
+    #[arg(long = "suid")]
+    pub suid: bool,
+    /// when enabled filters for results with the setgid bit
+    /// set. Defaults to 'false'.
+    #[arg(long = "sgid")]
+    pub sgid: bool,
+    /// when enabled filters for results with the sticky bit
+    /// set. Defaults to 'false'.
+    #[arg(long = "sticky")]
+    pub sticky: bool,
+    /// when enabled results from multiple root directories
+    /// are interleaved by taking one entry from each root
+    /// in turn rather than searching the roots one after
+    /// another. Defaults to 'false'.
+    #[arg(long = "interleave-roots")]
+    pub interleave_roots: bool,
     /// filters results based on modification time.
     /// By default the value provided filters for
     /// results with a modification time further in the past.
     /// Prefixing the value with '-' filters for results
     /// more recent than the value given instead.
+    /// Values are either durations, e.g. '7d', or UTC
+    /// timestamps, e.g. '2024-01-01' or '2024-01-01T12:00:00Z'.
+    /// Ranges such as '1d..7d' filter for results within
+    /// the window between both values.
     #[arg(long = "mtime")]
-    pub mtime_filters: Vec<DurationFilter>,
+    pub mtime_filters: Vec<TimeFilter>,
+    /// filters results based on the number of whole
+    /// 24-hour periods since their modification time like
+    /// 'find -mtime' rather than by exact durations, e.g.
+    /// '1' for results modified between one and two days
+    /// ago, '+1' for at least two days ago and '-1' for
+    /// less than one day ago.
+    #[arg(long = "mtime-days")]
+    pub mtime_days: Vec<DaysFilter>,
+    /// specifies what happens when the file given with
+    /// '--lock' is locked by another search. 'fail' exits
+    /// immediately, 'wait' waits until the lock is released
+    /// and 'wait=DURATION' waits at most the given duration,
+    /// e.g. 'wait=5m'. Defaults to 'fail'.
+    #[arg(long = "on-locked", default_value = "fail")]
+    pub on_locked: LockPolicy,
+    /// specifies how entries are handled when a filter
+    /// cannot be evaluated, e.g. because their metadata
+    /// is unreadable. 'report' passes the error to the
+    /// error output, 'skip' silently drops the entry,
+    /// 'match' treats the filter as matching and 'fail'
+    /// aborts the search. Defaults to 'report'.
+    #[arg(long = "on-filter-error", value_enum, default_value_t)]
+    pub on_filter_error: ErrorPolicy,
+    /// specifies how creation time filters treat entries on
+    /// filesystems which do not expose creation times.
+    /// 'skip' treats them as not matching, 'error' fails
+    /// to filter them like any other error and
+    /// 'ignore-filter' treats them as matching.
+    /// Defaults to 'error'.
+    #[arg(long = "btime-missing", value_enum, default_value_t)]
+    pub btime_missing: BtimePolicy,
+    /// when enabled together with '--reference' outputs
+    /// the paths listed in the reference list which did
+    /// not match any result instead of the results.
+    /// Defaults to 'false'.
+    #[arg(long = "missing")]
+    pub missing: bool,
+    /// when enabled user and group names are never
+    /// resolved through NSS and only numeric ids are
+    /// accepted. Defaults to 'false'.
+    #[arg(long = "no-nss")]
+    pub no_nss: bool,
+    /// specifies how results are written. 'plain' writes
+    /// one path per line while 'shell-array=NAME' writes
+    /// a bash array assignment, e.g. NAME=( 'a' 'b' ),
+    /// with every path quoted so it may be sourced by
+    /// shell scripts. NAME defaults to 'FILES'.
+    /// 'long' writes the type, mode, owner, group, size,
+    /// modification time and a security summary of special
+    /// bits, capabilities, ACLs and SELinux context, e.g.
+    /// 'setuid,caps', before each path while 'csv' writes
+    /// the same fields as comma separated values following
+    /// a header and 'json' as one object per line.
+    /// 'events' writes every result, error, progress update
+    /// and the final summary as versioned JSON lines meant
+    /// to be read by other programs.
+    /// 'quickfix' writes 'path:1:1: summary' lines which
+    /// editors can load into their quickfix or problems list
+    /// while 'quickfix-json' writes the same entries as a JSON
+    /// array of items accepted by Neovim's 'setqflist()'.
+    /// 'sqlite:PATH' stores the same fields as 'long' for
+    /// each result in the 'results' table of the given
+    /// SQLite database, after the results of any previous
+    /// search, and requires the 'sqlite' feature.
+    /// 'parquet:PATH' writes
+    /// the same fields as typed columns of a Parquet file
+    /// and requires the 'analytics' feature. Programs
+    /// embedding findr may register additional sinks which
+    /// are selected with 'NAME' or 'NAME:ARG'. May be given
+    /// several times to write results to several outputs
+    /// at once, of which at most one may be a text format.
+    #[arg(long = "output", default_value = "plain")]
+    pub output: Vec<OutputFormat>,
+    /// specifies how an output which fails during a search,
+    /// e.g. because its disk is full, is handled. 'abort'
+    /// stops the search while 'disable' reports the failure
+    /// and keeps writing to the remaining outputs. Counts of
+    /// the results written to each output are reported on
+    /// stderr when several outputs are used or any failed.
+    /// Defaults to 'abort'.
+    #[arg(long = "on-sink-error", value_enum, default_value_t)]
+    pub on_sink_error: SinkErrorPolicy,
+    /// when enabled ranks results by a heuristic cleanup
+    /// score from 0 to 100 derived from their age, size and
+    /// type, whether their path hints at caches or temporary
+    /// files and whether another result shares their name and
+    /// size. Results are written once the search completes,
+    /// highest score first, with plain output preceding each
+    /// path with its score and a tab. Defaults to 'false'.
+    #[arg(long = "score-cleanup")]
+    pub score_cleanup: bool,
+    /// specifies the minimum cleanup score of results ranked
+    /// with '--score-cleanup'. Defaults to '0'.
+    #[arg(long = "min-score", default_value_t = 0, value_parser = clap::value_parser!(u32).range(0..=100))]
+    pub min_score: u32,
+    /// specifies the maximum number of results ranked with
+    /// '--score-cleanup' to output, keeping those with the
+    /// highest scores.
+    #[arg(long = "top")]
+    pub top: Option<NonZeroUsize>,
+    /// walks only the given fraction of the subdirectories
+    /// of each root directory, chosen at random, and writes
+    /// the total number and size of matches extrapolated from
+    /// them along with 95% confidence intervals instead of the
+    /// matches themselves. Given as a ratio, e.g. '0.1', or a
+    /// percentage, e.g. '10%'.
+    #[arg(long = "estimate")]
+    pub estimate: Option<SampleFraction>,
+    /// writes sets of files with identical contents instead
+    /// of the matching files themselves once the search
+    /// completes. Only files sharing their size with another
+    /// are read and hard links to the same file are only
+    /// reported once under their first path by name. Paths
+    /// are written with any '--map' and '--escape' applied.
+    /// 'plain' writes one path per line with sets
+    /// separated by a blank line while 'json' writes one
+    /// object per set. Defaults to 'plain' when given
+    /// without a value.
+    #[arg(
+        long = "duplicates",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "plain"
+    )]
+    pub duplicates: Option<DuplicatesFormat>,
+    /// when enabled does not descend into directories on
+    /// other filesystems than the root directory they were
+    /// found under. Filesystems mounted during the search
+    /// are excluded as well and changes to the mount table
+    /// are reported as warnings. Defaults to 'false'.
+    #[arg(long = "one-file-system")]
+    pub one_file_system: bool,
     /// filters results based on owner:group.
     /// May be specified as "owner", "owner:group"
     /// or ":group" with unspecified owner or group
     /// matching any owner or group respectively.
+    /// Prefixing a value with '!' filters results not
+    /// owned accordingly instead. May be given several
+    /// times; results must match at least one of the
+    /// owners given and none of the negated owners.
     #[arg(long = "owner")]
-    pub owner: Option<OwnerFilter>,
+    pub owner: Vec<OwnerSpec>,
+    /// when enabled '--owner' values given as ':group' also
+    /// match results owned by any member of the group.
+    /// Defaults to 'false'.
+    #[arg(long = "group-members")]
+    pub group_members: bool,
+    /// filters results group-owned by any group the given
+    /// user, by name or numeric id, belongs to, e.g. to
+    /// audit what an account may access through group
+    /// permissions.
+    #[arg(long = "group-of")]
+    pub group_of: Option<String>,
+    /// filters results owned by numeric user ids without
+    /// resolving names. Accepts the same '+' and '-'
+    /// prefixes and 'FROM..TO' ranges as '--size', e.g.
+    /// '+999' for regular users on most distributions.
+    #[arg(long = "uid")]
+    pub uid_filters: Vec<CountFilter>,
+    /// filters results owned by numeric group ids like
+    /// '--uid'.
+    #[arg(long = "gid")]
+    pub gid_filters: Vec<CountFilter>,
+    /// when enabled filters for results whose owner does
+    /// not exist in the user database, e.g. after the user
+    /// was deleted. Defaults to 'false'.
+    #[arg(long = "nouser")]
+    pub nouser: bool,
+    /// when enabled filters for results whose group does
+    /// not exist in the group database. Defaults to 'false'.
+    #[arg(long = "nogroup")]
+    pub nogroup: bool,
+    /// specifies a YAML policy file declaring expectations
+    /// for paths matching regular expressions. Results
+    /// violating the policy are reported as one JSON object
+    /// per violation instead of their paths. Each rule
+    /// requires a 'path' and may declare a 'name', 'owner',
+    /// 'mode', 'max_size' and 'forbidden_types'. Usually
+    /// given through the 'policy check' subcommand.
+    #[arg(long = "policy")]
+    pub policy: Option<PathBuf>,
+    /// specifies a file listing paths separated by newlines,
+    /// or NUL bytes with '-0', e.g. an artifact manifest.
+    /// Only results which are absent from the list are
+    /// reported. Paths are compared byte for byte as they
+    /// were found rather than as they would be output,
+    /// ignoring leading './' components.
+    #[arg(long = "reference")]
+    pub reference: Option<PathBuf>,
+    /// when enabled the list given with '--reference' is
+    /// read as paths separated by NUL bytes, e.g. as written
+    /// by 'find -print0', so that paths may contain newlines.
+    /// Defaults to 'false'.
+    #[arg(short = '0', long = "null")]
+    pub null: bool,
+    /// specifies how many times reading the metadata of
+    /// an entry or opening a directory is retried after
+    /// failing with a transient error such as EINTR, EAGAIN
+    /// or ESTALE. Directories are re-opened and walked as
+    /// if they had been read the first time.
+    /// Defaults to '0'.
+    #[arg(long = "retries", default_value_t = 0)]
+    pub retries: u32,
+    /// specifies the delay between retries. A random jitter
+    /// of up to half the delay is added to each retry.
+    #[arg(long = "retry-delay", default_value = "100ms")]
+    pub retry_delay: Duration,
+    /// writes results to numbered files instead of stdout
+    /// starting a new file every '--split-every' results.
+    /// The file name is given as a printf style pattern
+    /// with one '%d' or '%0Nd' counter starting at 1, e.g.
+    /// 'results-%03d.txt'. Every file is formatted according
+    /// to '--output' on its own. Cannot be used with
+    /// '--output=events', 'quickfix' or 'quickfix-json'.
+    #[arg(long = "split-output")]
+    pub split_output: Option<SplitPattern>,
+    /// specifies the number of results written to each
+    /// file given with '--split-output'. By default all
+    /// results are written to a single file.
+    #[arg(long = "split-every")]
+    pub split_every: Option<NonZeroUsize>,
+    /// filters results modified more recently than the
+    /// given reference file like 'find -newer'. Modification
+    /// times are compared in whole seconds.
+    #[arg(long = "newer")]
+    pub newer: Option<PathBuf>,
+    /// filters results modified before the given reference
+    /// file. Modification times are compared in whole seconds.
+    #[arg(long = "older")]
+    pub older: Option<PathBuf>,
+    /// when enabled filters for results modified more
+    /// recently than the directory containing them, e.g.
+    /// files changed after a release directory was sealed.
+    /// Roots never match. Defaults to 'false'.
+    #[arg(long = "newer-than-parent")]
+    pub newer_than_parent: bool,
+    /// filters results modified since the given event.
+    /// 'boot' refers to the last boot of the host while any
+    /// other value is taken as the name of a systemd unit,
+    /// e.g. 'nginx.service', referring to the time the unit
+    /// was last started. Units are queried with 'systemctl'
+    /// and require the 'systemd' feature.
+    #[arg(long = "since")]
+    pub since: Option<SinceSpec>,
     /// when enabled outputs any errors encountered
     /// during search. Defaults to 'false'.
     #[arg(long = "show-errors")]
@@ -81,7 +767,191 @@ pub struct Options {
     /// given value. Prefixing with '+' returns
     /// results with size greater than the given value
     /// and prefixing with '-' returns those with
-    /// size smaller than the given value.
+    /// size smaller than the given value. Ranges such as
+    /// '1MiB..100MiB' return results with sizes between
+    /// both values including either end.
     #[arg(short = 's', long = "size")]
     pub size_filters: Vec<SizeFilter>,
+    /// when enabled exits with a code describing the
+    /// outcome of the search: 0 if matches were found,
+    /// 1 if there were no matches, 2 on usage errors,
+    /// 3 if errors were encountered during the search,
+    /// 4 if results were omitted due to '--max-output',
+    /// 5 if the file given with '--lock' is locked by
+    /// another search and 128+N if terminated by signal N.
+    /// Defaults to 'false'.
+    #[arg(long = "strict-exit")]
+    pub strict_exit: bool,
+    /// filters directories containing any entry modified
+    /// more recently than the value given, e.g. '7d'.
+    /// Enabling this filter reports the contents of each
+    /// directory before the directory itself.
+    #[arg(long = "subtree-modified-within")]
+    pub subtree_modified_within: Option<Duration>,
+    /// filters results based on the modification time
+    /// of the target of symlinks while still reporting
+    /// the link itself. Accepts the same values as
+    /// '--mtime'. Entries which are not symlinks are
+    /// compared using their own modification time.
+    #[arg(long = "target-mtime")]
+    pub target_mtime_filters: Vec<TimeFilter>,
+    /// filters results based on the size of the target
+    /// of symlinks while still reporting the link itself.
+    /// Accepts the same values as '--size'. Entries which
+    /// are not symlinks are compared using their own size.
+    #[arg(long = "target-size")]
+    pub target_size_filters: Vec<SizeFilter>,
+    /// when enabled together with '--version' also prints
+    /// build information and which platform capabilities
+    /// such as statx and io_uring are available.
+    /// Defaults to 'false'.
+    #[arg(long = "verbose")]
+    pub verbose: bool,
+    /// prints version information and exits.
+    #[arg(short = 'V', long = "version")]
+    pub version: bool,
+    /// specifies the order in which entries within each
+    /// directory are visited. 'name' visits entries in
+    /// lexicographic order while 'size' and 'mtime' visit
+    /// the largest and most recently modified entries
+    /// first respectively. By default entries are visited
+    /// in the order they are read from the directory.
+    #[arg(long = "visit-order", value_enum)]
+    pub visit_order: Option<VisitOrder>,
+    /// Subcommands are expanded into the options they stand
+    /// for while parsing so this is never set afterwards.
+    #[command(subcommand)]
+    pub command: Option<Subcommand>,
+}
+
+/// Shorthands for common kinds of searches. A subcommand
+/// must be given first and is followed by any other options
+/// and the roots to search, e.g. 'findr orphans -0
+/// --reference manifest.txt --type=f store/'.
+#[derive(Debug, clap::Subcommand)]
+pub enum Subcommand {
+    /// reports files present on disk but absent from the
+    /// reference list given with '--reference' or, with
+    /// '--missing', listed paths which are absent from disk.
+    Orphans {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<OsString>,
+    },
+    /// runs named searches declared in a TOML file as
+    /// '[[job]]' tables with a 'name', the 'roots' to
+    /// search and the command line 'args' to search with.
+    /// Jobs are run one after another with the status of
+    /// each written to stderr. Exits with the highest exit
+    /// code of any job.
+    RunJobs {
+        /// the TOML jobs file to run.
+        jobs: PathBuf,
+        /// selects a job to run. May be given multiple times
+        /// to run jobs in the order given. By default all jobs
+        /// are run.
+        #[arg(long = "job", value_name = "NAME")]
+        names: Vec<String>,
+        /// when enabled exits with the usage error code of
+        /// the top-level '--strict-exit' if the jobs file is
+        /// invalid. Jobs set their own '--strict-exit'.
+        #[arg(long = "strict-exit")]
+        strict_exit: bool,
+    },
+    /// evaluates YAML policy files against the filesystem.
+    Policy {
+        #[command(subcommand)]
+        action: PolicyAction,
+    },
+}
+
+/// Actions of the 'policy' subcommand.
+#[derive(Debug, clap::Subcommand)]
+pub enum PolicyAction {
+    /// reports results violating the given policy file like
+    /// '--policy', e.g. 'findr policy check policy.yaml
+    /// --type=f /etc'.
+    Check {
+        /// the YAML policy file to check.
+        policy: PathBuf,
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<OsString>,
+    },
+}
+
+impl Subcommand {
+    /// Parses the options the subcommand stands for.
+    fn expand(self) -> Result<Options, clap::Error> {
+        match self {
+            Self::Orphans { args } => {
+                let options = Self::parse_args(args)?;
+
+                if options.reference.is_none() {
+                    return Err(Options::constrained_command().error(
+                        clap::error::ErrorKind::MissingRequiredArgument,
+                        "'orphans' requires '--reference <REFERENCE>'",
+                    ));
+                }
+
+                Ok(options)
+            }
+            Self::RunJobs {
+                jobs,
+                names,
+                strict_exit,
+            } => {
+                let mut options = Self::parse_args(strict_exit.then(|| "--strict-exit".into()))?;
+
+                options.jobs = Some(jobs);
+                options.job_names = names;
+
+                Ok(options)
+            }
+            Self::Policy {
+                action: PolicyAction::Check { policy, args },
+            } => Self::parse_args(
+                ["--policy".into(), policy.into_os_string()]
+                    .into_iter()
+                    .chain(args),
+            ),
+        }
+    }
+    fn parse_args(args: impl IntoIterator<Item = OsString>) -> Result<Options, clap::Error> {
+        Options::try_parse_constrained_from(std::iter::once(clap::crate_name!().into()).chain(args))
+    }
+}
+
+impl Options {
+    /// Returns the language selected with '--lang'
+    /// or from the environment.
+    pub fn lang(&self) -> Lang {
+        self.lang.unwrap_or_else(Lang::from_env)
+    }
+    /// Returns the number of bytes content filters read
+    /// from each file or 'None' if files are read in full.
+    pub fn read_limit(&self) -> Option<u64> {
+        (!self.full_content).then_some(self.max_read)
+    }
+    /// Returns the argument parser with all option
+    /// constraints registered.
+    pub fn constrained_command() -> clap::Command {
+        constraints::apply(Self::command())
+    }
+    /// Parses options from the process arguments exiting
+    /// with a usage error if any constraint is violated.
+    pub fn parse_constrained() -> Self {
+        Self::try_parse_constrained_from(std::env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+    /// Parses options from the given arguments checking
+    /// every constraint and expanding subcommands.
+    pub fn try_parse_constrained_from(
+        args: impl IntoIterator<Item = impl Into<OsString> + Clone>,
+    ) -> Result<Self, clap::Error> {
+        let matches = Self::constrained_command().try_get_matches_from(args)?;
+        let mut options = Self::from_arg_matches(&matches)?;
+
+        match options.command.take() {
+            Some(command) => command.expand(),
+            None => Ok(options),
+        }
+    }
 }
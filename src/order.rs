@@ -0,0 +1,106 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::ValueEnum;
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+use walkdir::DirEntry;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum VisitOrder {
+    Name,
+    Size,
+    Mtime,
+}
+
+impl VisitOrder {
+    /// Returns a comparator for the walker's sort hook. Size
+    /// and mtime orders visit the largest and newest entries
+    /// first and fall back to ordering by name.
+    pub fn comparator(self) -> impl FnMut(&DirEntry, &DirEntry) -> Ordering + Send + Sync {
+        let mut keys = KeyCache::default();
+
+        move |a, b| match self {
+            Self::Name => a.file_name().cmp(b.file_name()),
+            Self::Size | Self::Mtime => keys
+                .get(self, a)
+                .cmp(&keys.get(self, b))
+                .then_with(|| a.file_name().cmp(b.file_name())),
+        }
+    }
+}
+
+// Memoizes sort keys so that each entry is only stat'ed once
+// per sort. Entries are only ever compared to siblings so the
+// cache is reset whenever a new directory is sorted.
+#[derive(Default)]
+struct KeyCache {
+    dir: Option<PathBuf>,
+    keys: HashMap<OsString, Reverse<u64>>,
+}
+
+impl KeyCache {
+    fn get(&mut self, order: VisitOrder, ent: &DirEntry) -> Reverse<u64> {
+        let parent = ent.path().parent().map(PathBuf::from);
+
+        if self.dir != parent {
+            self.dir = parent;
+            self.keys.clear();
+        }
+
+        *self
+            .keys
+            .entry(ent.file_name().to_os_string())
+            .or_insert_with(|| {
+                Reverse(
+                    ent.metadata()
+                        .map(|m| match order {
+                            VisitOrder::Size => m.size(),
+                            _ => m.mtime().try_into().unwrap_or_default(),
+                        })
+                        .unwrap_or_default(),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VisitOrder;
+    use anyhow::Result;
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+    use test_case::test_case;
+    use walkdir::WalkDir;
+
+    #[test_case(VisitOrder::Name, &["a", "b", "c"] ; "name")]
+    #[test_case(VisitOrder::Size, &["c", "a", "b"] ; "largest first")]
+    #[test_case(VisitOrder::Mtime, &["b", "a", "c"] ; "newest first")]
+    fn comparator(order: VisitOrder, expected: &[&str]) -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let now = SystemTime::now();
+
+        for (name, size, age) in [("a", 2, 20), ("b", 1, 10), ("c", 3, 30)] {
+            fs::write(temp.path().join(name), "x".repeat(size))?;
+            fs::File::options()
+                .write(true)
+                .open(temp.path().join(name))?
+                .set_modified(now - Duration::from_secs(age))?;
+        }
+
+        let names = WalkDir::new(temp.path())
+            .min_depth(1)
+            .sort_by(order.comparator())
+            .into_iter()
+            .map(|r| r.map(|e| e.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<Vec<String>, _>>()?;
+
+        assert_eq!(expected, names);
+
+        Ok(temp.close()?)
+    }
+}
@@ -0,0 +1,370 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::escape::json_escape;
+use crate::found::FoundEntry;
+use crate::i18n::Lang;
+use crate::sink;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Names of the fields written by the 'csv' format.
+const CSV_HEADER: &str = "path,type,size,mode,uid,gid,mtime,security";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    #[default]
+    Plain,
+    ShellArray(String),
+    /// Results are written one per line along with their
+    /// type, mode, owner, size, modification time and
+    /// security summary.
+    Long,
+    /// Results are written with the same fields as 'Long'
+    /// as comma separated values following a header.
+    Csv,
+    /// Results are written with the same fields as 'Long'
+    /// as one JSON object per line.
+    Json,
+    /// Results, errors, progress and the summary are written
+    /// to stdout as newline delimited JSON events.
+    Events,
+    /// Results are written as editor quickfix entries,
+    /// either as lines or as a JSON array.
+    Quickfix,
+    QuickfixJson,
+    /// Results are stored in a SQLite database rather
+    /// than written to stdout.
+    Sqlite(PathBuf),
+    /// Results are stored in a Parquet file rather
+    /// than written to stdout.
+    Parquet(PathBuf),
+    /// Results are handed to a sink registered by name
+    /// along with its optional argument.
+    Custom(String, Option<String>),
+}
+
+impl OutputFormat {
+    /// Reports whether results are written as text to stdout
+    /// rather than handed to a structured or registered sink.
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self,
+            Self::Plain
+                | Self::ShellArray(_)
+                | Self::Long
+                | Self::Csv
+                | Self::Json
+                | Self::Events
+                | Self::Quickfix
+                | Self::QuickfixJson
+        )
+    }
+    /// Reports whether results are written along with their
+    /// metadata including the security summary.
+    pub fn has_metadata(&self) -> bool {
+        matches!(self, Self::Long | Self::Csv | Self::Json)
+    }
+    pub fn write_header(&self, out: &mut impl Write) -> io::Result<()> {
+        match self {
+            Self::Plain
+            | Self::Long
+            | Self::Json
+            | Self::Events
+            | Self::Quickfix
+            | Self::QuickfixJson
+            | Self::Sqlite(_)
+            | Self::Parquet(_)
+            | Self::Custom(..) => Ok(()),
+            Self::ShellArray(name) => writeln!(out, "{name}=("),
+            Self::Csv => writeln!(out, "{CSV_HEADER}"),
+        }
+    }
+    /// Writes a result along with its metadata for formats
+    /// which include it. Paths reported by '--missing' have
+    /// no metadata and are written with empty fields.
+    pub fn write_entry(
+        &self,
+        out: &mut impl Write,
+        path: &str,
+        metadata: Option<&FoundEntry>,
+    ) -> io::Result<()> {
+        match self {
+            Self::Plain => writeln!(out, "{path}"),
+            Self::ShellArray(_) => writeln!(out, "  {}", shell_quote(path)),
+            Self::Long => match metadata {
+                Some(m) => writeln!(
+                    out,
+                    "{} {:04o} {} {} {} {} {} {}",
+                    m.file_type.name(),
+                    m.mode,
+                    m.uid,
+                    m.gid,
+                    m.size,
+                    format_mtime(m.mtime),
+                    m.security.as_deref().unwrap_or("-"),
+                    path
+                ),
+                None => writeln!(out, "- - - - - - - {path}"),
+            },
+            Self::Csv => match metadata {
+                Some(m) => writeln!(
+                    out,
+                    "{},{},{},{:04o},{},{},{},{}",
+                    csv_quote(path),
+                    m.file_type.name(),
+                    m.size,
+                    m.mode,
+                    m.uid,
+                    m.gid,
+                    format_mtime(m.mtime),
+                    csv_quote(m.security.as_deref().unwrap_or("-"))
+                ),
+                None => writeln!(out, "{},,,,,,,", csv_quote(path)),
+            },
+            Self::Json => match metadata {
+                Some(m) => writeln!(
+                    out,
+                    r#"{{"path":{},"type":"{}","size":{},"mode":"{:04o}","uid":{},"gid":{},"mtime":"{}","security":{}}}"#,
+                    json_escape(path),
+                    m.file_type.name(),
+                    m.size,
+                    m.mode,
+                    m.uid,
+                    m.gid,
+                    format_mtime(m.mtime),
+                    json_escape(m.security.as_deref().unwrap_or("-"))
+                ),
+                None => writeln!(out, r#"{{"path":{}}}"#, json_escape(path)),
+            },
+            Self::Events
+            | Self::Quickfix
+            | Self::QuickfixJson
+            | Self::Sqlite(_)
+            | Self::Parquet(_)
+            | Self::Custom(..) => Ok(()),
+        }
+    }
+    /// Marks output which was cut short by '--max-output'.
+    /// Shell formats receive a comment so the output stays
+    /// valid when sourced.
+    pub fn write_truncated(
+        &self,
+        out: &mut impl Write,
+        as_comment: bool,
+        remaining: usize,
+        lang: Lang,
+    ) -> io::Result<()> {
+        let marker = lang.truncated(remaining);
+
+        match self {
+            Self::Plain | Self::Long if !as_comment => writeln!(out, "{marker}"),
+            Self::Plain | Self::Long => writeln!(out, "# {marker}"),
+            Self::ShellArray(_) => writeln!(out, "  # {marker}"),
+            // a marker would not parse as a record
+            Self::Csv
+            | Self::Json
+            | Self::Events
+            | Self::Quickfix
+            | Self::QuickfixJson
+            | Self::Sqlite(_)
+            | Self::Parquet(_)
+            | Self::Custom(..) => Ok(()),
+        }
+    }
+    pub fn write_footer(&self, out: &mut impl Write) -> io::Result<()> {
+        match self {
+            Self::Plain
+            | Self::Long
+            | Self::Csv
+            | Self::Json
+            | Self::Events
+            | Self::Quickfix
+            | Self::QuickfixJson
+            | Self::Sqlite(_)
+            | Self::Parquet(_)
+            | Self::Custom(..) => Ok(()),
+            Self::ShellArray(_) => writeln!(out, ")"),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Plain => write!(f, "plain"),
+            Self::Long => write!(f, "long"),
+            Self::Csv => write!(f, "csv"),
+            Self::Json => write!(f, "json"),
+            Self::Events => write!(f, "events"),
+            Self::Quickfix => write!(f, "quickfix"),
+            Self::QuickfixJson => write!(f, "quickfix-json"),
+            Self::ShellArray(name) => write!(f, "shell-array={name}"),
+            Self::Sqlite(path) => write!(f, "sqlite:{}", path.display()),
+            Self::Parquet(path) => write!(f, "parquet:{}", path.display()),
+            Self::Custom(name, None) => write!(f, "{name}"),
+            Self::Custom(name, Some(arg)) => write!(f, "{name}:{arg}"),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((kind, path)) = s.split_once(':') {
+            return Ok(match kind {
+                "sqlite" if !path.is_empty() => Self::Sqlite(path.into()),
+                "parquet" if !path.is_empty() => Self::Parquet(path.into()),
+                _ if sink::is_registered(kind) => Self::Custom(kind.into(), Some(path.into())),
+                _ => anyhow::bail!("unknown output format '{}'", s),
+            });
+        }
+
+        Ok(match s.split_once('=') {
+            Some(("shell-array", name)) => Self::ShellArray(parse_shell_name(name)?),
+            None if s == "plain" => Self::Plain,
+            None if s == "long" => Self::Long,
+            None if s == "csv" => Self::Csv,
+            None if s == "json" => Self::Json,
+            None if s == "events" => Self::Events,
+            None if s == "quickfix" => Self::Quickfix,
+            None if s == "quickfix-json" => Self::QuickfixJson,
+            None if s == "shell-array" => Self::ShellArray("FILES".to_string()),
+            None if sink::is_registered(s) => Self::Custom(s.into(), None),
+            _ => anyhow::bail!("unknown output format '{}'", s),
+        })
+    }
+}
+
+fn parse_shell_name(s: &str) -> anyhow::Result<String> {
+    let mut chars = s.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if valid {
+        Ok(s.to_string())
+    } else {
+        Err(anyhow::anyhow!("invalid shell variable name '{}'", s))
+    }
+}
+
+fn format_mtime(mtime: u64) -> humantime::Rfc3339Timestamp {
+    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(mtime))
+}
+
+/// Quotes a CSV field as described by RFC 4180 if it
+/// contains a separator, quote or line break.
+fn csv_quote(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Wraps the given value in single quotes so that it is
+/// interpreted literally by POSIX shells.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv_quote, shell_quote, OutputFormat};
+    use crate::entry::FileKind;
+    use crate::filter::testing::*;
+    use crate::found::FoundEntry;
+    use anyhow::{anyhow, Result};
+    use test_case::test_case;
+
+    #[test_case("plain", Ok(OutputFormat::Plain) ; "plain")]
+    #[test_case("shell-array", Ok(OutputFormat::ShellArray("FILES".into())) ; "shell array default name")]
+    #[test_case("shell-array=_found1", Ok(OutputFormat::ShellArray("_found1".into())) ; "shell array named")]
+    #[test_case("shell-array=1st", Err(anyhow!("")) ; "shell array name starting with digit")]
+    #[test_case("shell-array=", Err(anyhow!("")) ; "shell array empty name")]
+    #[test_case("plain=x", Err(anyhow!("")) ; "plain with value")]
+    #[test_case("sqlite:results.db", Ok(OutputFormat::Sqlite("results.db".into())) ; "sqlite")]
+    #[test_case("sqlite:", Err(anyhow!("")) ; "sqlite without path")]
+    #[test_case("parquet:results.parquet", Ok(OutputFormat::Parquet("results.parquet".into())) ; "parquet")]
+    #[test_case("parquet", Err(anyhow!("")) ; "parquet without path")]
+    #[test_case("xml", Err(anyhow!("")) ; "unknown format")]
+    fn from_str(s: &str, expected: Result<OutputFormat>) {
+        assert_from_str(s, expected)
+    }
+    #[test_case("plain" ; "plain")]
+    #[test_case("long" ; "long")]
+    #[test_case("csv" ; "csv")]
+    #[test_case("json" ; "json")]
+    #[test_case("events" ; "events")]
+    #[test_case("quickfix" ; "quickfix")]
+    #[test_case("quickfix-json" ; "quickfix json")]
+    #[test_case("shell-array=FILES" ; "shell array")]
+    #[test_case("sqlite:results.db" ; "sqlite")]
+    #[test_case("parquet:results.parquet" ; "parquet")]
+    fn display(s: &str) -> Result<()> {
+        assert_eq!(s, s.parse::<OutputFormat>()?.to_string());
+
+        Ok(())
+    }
+    #[test_case("a.txt", "'a.txt'" ; "plain path")]
+    #[test_case("a b.txt", "'a b.txt'" ; "path with space")]
+    #[test_case("it's.txt", r"'it'\''s.txt'" ; "path with single quote")]
+    #[test_case("$(rm -rf).txt", "'$(rm -rf).txt'" ; "path with command substitution")]
+    fn quote(s: &str, expected: &str) {
+        assert_eq!(expected, shell_quote(s))
+    }
+
+    #[test_case(
+        OutputFormat::Long,
+        "file 4755 1000 100 8 2023-03-01T12:00:00Z setuid,caps ./a.txt\n- - - - - - - ./b.txt\n"
+        ; "long"
+    )]
+    #[test_case(
+        OutputFormat::Csv,
+        "./a.txt,file,8,4755,1000,100,2023-03-01T12:00:00Z,\"setuid,caps\"\n./b.txt,,,,,,,\n"
+        ; "csv"
+    )]
+    #[test_case(
+        OutputFormat::Json,
+        concat!(
+            r#"{"path":"./a.txt","type":"file","size":8,"mode":"4755","uid":1000,"gid":100,"mtime":"2023-03-01T12:00:00Z","security":"setuid,caps"}"#,
+            "\n",
+            r#"{"path":"./b.txt"}"#,
+            "\n",
+        )
+        ; "json"
+    )]
+    fn write_entry(format: OutputFormat, expected: &str) -> Result<()> {
+        let found = FoundEntry {
+            path: "./a.txt".into(),
+            file_type: FileKind::File,
+            size: 8,
+            mode: 0o4755,
+            uid: 1000,
+            gid: 100,
+            mtime: 1677672000,
+            security: Some("setuid,caps".into()),
+        };
+        let mut out = Vec::new();
+
+        format.write_entry(&mut out, "./a.txt", Some(&found))?;
+        format.write_entry(&mut out, "./b.txt", None)?;
+
+        assert_eq!(expected, String::from_utf8(out)?);
+
+        Ok(())
+    }
+    #[test_case("a.txt", "a.txt" ; "plain field")]
+    #[test_case("a,b.txt", "\"a,b.txt\"" ; "field with separator")]
+    #[test_case("a \"b\".txt", "\"a \"\"b\"\".txt\"" ; "field with quotes")]
+    fn csv(s: &str, expected: &str) {
+        assert_eq!(expected, csv_quote(s))
+    }
+}
@@ -0,0 +1,318 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::ValueEnum;
+use memchr::memmem::Finder;
+use regex::{Regex, RegexBuilder};
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use unicode_normalization::{is_nfc, is_nfd, UnicodeNormalization};
+
+pub use fancy::FancyRegex;
+
+/// The regular expression engine '--pattern' is compiled
+/// with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum RegexEngine {
+    /// Guarantees matching in linear time but supports
+    /// neither look-around nor backreferences.
+    #[default]
+    Default,
+    /// Supports look-around and backreferences by falling
+    /// back to backtracking when they are used.
+    Fancy,
+}
+
+/// A compiled '--pattern'.
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Regex(Regex),
+    Fancy(FancyRegex),
+    /// A literal substring given with '--fixed-strings'
+    /// which is searched for without a regex engine.
+    Literal(Box<Finder<'static>>),
+}
+
+impl Pattern {
+    pub fn new(
+        pattern: &str,
+        fixed: bool,
+        ignore_case: bool,
+        engine: RegexEngine,
+    ) -> anyhow::Result<Self> {
+        Ok(match (fixed, ignore_case, engine) {
+            (true, false, _) => Self::Literal(Box::new(Finder::new(pattern).into_owned())),
+            (true, true, _) => Self::Regex(
+                RegexBuilder::new(&regex::escape(pattern))
+                    .case_insensitive(true)
+                    .build()?,
+            ),
+            (false, _, RegexEngine::Default) => Self::Regex(
+                RegexBuilder::new(pattern)
+                    .case_insensitive(ignore_case)
+                    .build()?,
+            ),
+            (false, _, RegexEngine::Fancy) => Self::Fancy(FancyRegex::new(pattern, ignore_case)?),
+        })
+    }
+    /// Fails if the fancy engine gives up on a match which
+    /// backtracks excessively.
+    pub fn is_match(&self, s: &str) -> anyhow::Result<bool> {
+        Ok(match self {
+            Self::Regex(r) => r.is_match(s),
+            Self::Fancy(r) => r.is_match(s)?,
+            Self::Literal(f) => f.find(s.as_bytes()).is_some(),
+        })
+    }
+}
+
+#[cfg(feature = "fancy-regex")]
+mod fancy {
+    /// A regular expression supporting look-around and
+    /// backreferences.
+    #[derive(Clone, Debug)]
+    pub struct FancyRegex(fancy_regex::Regex);
+
+    impl FancyRegex {
+        pub fn new(pattern: &str, ignore_case: bool) -> anyhow::Result<Self> {
+            Ok(Self(if ignore_case {
+                fancy_regex::Regex::new(&format!("(?i){pattern}"))?
+            } else {
+                fancy_regex::Regex::new(pattern)?
+            }))
+        }
+        pub fn is_match(&self, s: &str) -> anyhow::Result<bool> {
+            Ok(self.0.is_match(s)?)
+        }
+    }
+}
+
+#[cfg(not(feature = "fancy-regex"))]
+mod fancy {
+    /// Stands in for the fancy regex engine when findr is
+    /// built without the 'fancy-regex' feature.
+    #[derive(Clone, Debug)]
+    pub enum FancyRegex {}
+
+    impl FancyRegex {
+        pub fn new(_: &str, _: bool) -> anyhow::Result<Self> {
+            anyhow::bail!(
+                "the 'fancy' regex engine requires findr to be built with the 'fancy-regex' feature"
+            )
+        }
+        pub fn is_match(&self, _: &str) -> anyhow::Result<bool> {
+            match *self {}
+        }
+    }
+}
+
+/// A Unicode normalization form applied to both '--pattern'
+/// and the names it is matched against so that names which
+/// are canonically equivalent, e.g. composed on Linux and
+/// decomposed on macOS, match the same patterns.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Normalization {
+    Nfc,
+    Nfd,
+}
+
+impl Normalization {
+    pub fn apply(self, s: &str) -> Cow<'_, str> {
+        match self {
+            Self::Nfc if !is_nfc(s) => Cow::Owned(s.nfc().collect()),
+            Self::Nfd if !is_nfd(s) => Cow::Owned(s.nfd().collect()),
+            _ => Cow::Borrowed(s),
+        }
+    }
+}
+
+/// Reports whether the given pattern contains an uppercase
+/// letter for '--smart-case'. Letters within escape sequences
+/// of regular expressions such as '\W' or '\p{Greek}' are
+/// not considered.
+pub fn has_uppercase(pattern: &str, fixed: bool) -> bool {
+    if fixed {
+        return pattern.chars().any(char::is_uppercase);
+    }
+
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some('p' | 'P') = chars.next() {
+                    // skips the class name, e.g. 'L' or '{Greek}'
+                    if chars.next() == Some('{') {
+                        chars.find(|c| *c == '}');
+                    }
+                }
+            }
+            c if c.is_uppercase() => return true,
+            _ => (),
+        }
+    }
+
+    false
+}
+
+/// Reads one pattern per line from the given file skipping
+/// blank lines and comments starting with '#'.
+pub fn load(path: &Path) -> anyhow::Result<Vec<String>> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("invalid pattern file '{}': {}", path.display(), e))?;
+
+    Ok(parse(&data))
+}
+
+fn parse(data: &str) -> Vec<String> {
+    data.lines()
+        .map(|l| l.strip_suffix('\r').unwrap_or(l))
+        .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Returns the given path relative to the longest of the
+/// given roots containing it for '--relative-match'. Roots
+/// themselves are returned as '.' and paths outside of every
+/// root are returned unchanged.
+pub fn relative<'p>(path: &'p str, roots: &[PathBuf]) -> &'p str {
+    roots
+        .iter()
+        .filter_map(|r| Path::new(path).strip_prefix(r).ok())
+        .min_by_key(|p| p.as_os_str().len())
+        .and_then(|p| p.to_str())
+        .map(|p| if p.is_empty() { "." } else { p })
+        .unwrap_or(path)
+}
+
+/// The part of a result's path which '--pattern' is
+/// matched against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum MatchTarget {
+    #[default]
+    Path,
+    Basename,
+}
+
+impl MatchTarget {
+    /// Returns the part of the given path to match. Paths
+    /// without a file name such as '.' are matched whole.
+    pub fn subject(self, path: &str) -> &str {
+        match self {
+            Self::Path => path,
+            Self::Basename => Path::new(path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_uppercase, parse, relative, MatchTarget, Normalization, Pattern, RegexEngine};
+    use std::path::PathBuf;
+    use test_case::test_case;
+
+    #[test_case(r"\.md$", false, false, "./one/b.md", true ; "regex")]
+    #[test_case(r"\.MD$", false, true, "./one/b.md", true ; "case insensitive regex")]
+    #[test_case("a.b", false, false, "./axb", true ; "regex wildcard")]
+    #[test_case("a.b", true, false, "./axb", false ; "literal dot")]
+    #[test_case("a.b", true, false, "./a.b", true ; "literal")]
+    #[test_case("f(1)+", true, false, "./f(1)+.txt", true ; "literal metacharacters")]
+    #[test_case("A.B", true, true, "./a.b", true ; "case insensitive literal")]
+    #[test_case("A.B", true, true, "./axb", false ; "case insensitive literal dot")]
+    fn is_match(pattern: &str, fixed: bool, ignore_case: bool, s: &str, expected: bool) {
+        assert_eq!(
+            expected,
+            Pattern::new(pattern, fixed, ignore_case, RegexEngine::Default)
+                .unwrap()
+                .is_match(s)
+                .unwrap()
+        )
+    }
+
+    #[cfg(feature = "fancy-regex")]
+    #[test_case("foo(?!/bar)", false, "./foo/baz", true ; "negative look-ahead")]
+    #[test_case("foo(?!/bar)", false, "./foo/bar", false ; "negative look-ahead fails")]
+    #[test_case(r"(?<=\.)md$", false, "./b.md", true ; "look-behind")]
+    #[test_case(r"/(\w)\1$", false, "./aa", true ; "backreference")]
+    #[test_case(r"/(\w)\1$", false, "./ab", false ; "backreference fails")]
+    #[test_case(r"(?!x)\.MD$", true, "./b.md", true ; "case insensitive")]
+    fn fancy(pattern: &str, ignore_case: bool, s: &str, expected: bool) {
+        assert_eq!(
+            expected,
+            Pattern::new(pattern, false, ignore_case, RegexEngine::Fancy)
+                .unwrap()
+                .is_match(s)
+                .unwrap()
+        )
+    }
+
+    #[cfg(not(feature = "fancy-regex"))]
+    #[test]
+    fn fancy_without_feature() {
+        assert!(Pattern::new("a", false, false, RegexEngine::Fancy).is_err())
+    }
+
+    #[test_case("[", false, false ; "invalid regex")]
+    fn invalid(pattern: &str, fixed: bool, ignore_case: bool) {
+        assert!(Pattern::new(pattern, fixed, ignore_case, RegexEngine::Default).is_err())
+    }
+
+    #[test]
+    fn invalid_regex_as_literal() {
+        assert!(Pattern::new("[", true, false, RegexEngine::Default).is_ok())
+    }
+
+    #[test_case(Normalization::Nfc, "cafe\u{301}", "caf\u{e9}" ; "compose")]
+    #[test_case(Normalization::Nfc, "caf\u{e9}", "caf\u{e9}" ; "already composed")]
+    #[test_case(Normalization::Nfd, "caf\u{e9}", "cafe\u{301}" ; "decompose")]
+    #[test_case(Normalization::Nfd, "cafe", "cafe" ; "ascii")]
+    fn normalize(form: Normalization, s: &str, expected: &str) {
+        assert_eq!(expected, form.apply(s))
+    }
+
+    #[test_case("foo", false, false ; "lowercase")]
+    #[test_case("Foo", false, true ; "uppercase")]
+    #[test_case(r"\W\D\S", false, false ; "escaped classes")]
+    #[test_case(r"\p{Greek}\PL", false, false ; "unicode classes")]
+    #[test_case(r"\p{Greek}A", false, true ; "uppercase after class")]
+    #[test_case(r"\W", true, true ; "fixed string")]
+    #[test_case("ä", false, false ; "lowercase non-ascii")]
+    #[test_case("Ä", false, true ; "uppercase non-ascii")]
+    fn uppercase(pattern: &str, fixed: bool, expected: bool) {
+        assert_eq!(expected, has_uppercase(pattern, fixed))
+    }
+
+    #[test_case("", &[] ; "empty")]
+    #[test_case("/target$\n/\\.git$\n", &["/target$", r"/\.git$"] ; "one per line")]
+    #[test_case("# build output\n\n/target$\r\n  \n", &["/target$"] ; "comments and blank lines")]
+    #[test_case(" a \n", &[" a "] ; "whitespace is kept")]
+    fn parse_file(data: &str, expected: &[&str]) {
+        assert_eq!(expected, parse(data))
+    }
+
+    #[test_case("./one/b.md", &["."], "one/b.md" ; "current directory")]
+    #[test_case("/very/long/root/a/b", &["/very/long/root"], "a/b" ; "absolute root")]
+    #[test_case("/very/long/root/a/b", &["/very/long/root/"], "a/b" ; "trailing separator")]
+    #[test_case("/root", &["/root"], "." ; "root itself")]
+    #[test_case("./one/two/c.txt", &[".", "./one"], "two/c.txt" ; "nested roots")]
+    #[test_case("./one/b.md", &["./two"], "./one/b.md" ; "outside of roots")]
+    fn relative_to_root(path: &str, roots: &[&str], expected: &str) {
+        let roots: Vec<PathBuf> = roots.iter().map(PathBuf::from).collect();
+
+        assert_eq!(expected, relative(path, &roots))
+    }
+
+    #[test_case(MatchTarget::Path, "./one/b.md", "./one/b.md" ; "path")]
+    #[test_case(MatchTarget::Basename, "./one/b.md", "b.md" ; "basename")]
+    #[test_case(MatchTarget::Basename, "./one/two", "two" ; "directory basename")]
+    #[test_case(MatchTarget::Basename, ".", "." ; "root")]
+    fn subject(target: MatchTarget, path: &str, expected: &str) {
+        assert_eq!(expected, target.subject(path))
+    }
+}
@@ -0,0 +1,219 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::entry::Entry;
+use crate::escape::json_escape;
+use crate::filter::{parse_size, OwnerFilter, OwnerSpec, TypeSelector};
+use crate::nss::IdDatabase;
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    rules: Vec<RuleFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    name: Option<String>,
+    path: String,
+    owner: Option<String>,
+    mode: Option<String>,
+    max_size: Option<String>,
+    #[serde(default)]
+    forbidden_types: Vec<String>,
+}
+
+/// Policy as read from a file with owners left unresolved.
+#[derive(Debug)]
+pub struct PolicySpec {
+    rules: Vec<RuleSpec>,
+}
+
+#[derive(Debug)]
+struct RuleSpec {
+    name: String,
+    pattern: Regex,
+    owner: Option<OwnerSpec>,
+    mode: Option<u32>,
+    max_size: Option<u64>,
+    forbidden_types: Vec<TypeSelector>,
+}
+
+impl PolicySpec {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("invalid policy '{}': {}", path.display(), e))?;
+
+        Self::parse(&data)
+            .map_err(|e| anyhow::anyhow!("invalid policy '{}': {}", path.display(), e))
+    }
+    fn parse(data: &str) -> anyhow::Result<Self> {
+        let file: PolicyFile = serde_yaml::from_str(data)?;
+        let rules = file
+            .rules
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| {
+                Ok(RuleSpec {
+                    name: r.name.unwrap_or_else(|| format!("rule-{}", i + 1)),
+                    pattern: Regex::new(&r.path)?,
+                    owner: r.owner.map(|o| o.parse()).transpose()?,
+                    mode: r.mode.map(|m| u32::from_str_radix(&m, 8)).transpose()?,
+                    max_size: r.max_size.map(|s| parse_size(&s)).transpose()?,
+                    forbidden_types: r
+                        .forbidden_types
+                        .iter()
+                        .map(|t| t.parse())
+                        .collect::<anyhow::Result<Vec<TypeSelector>>>()?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<RuleSpec>>>()?;
+
+        Ok(Self { rules })
+    }
+    pub fn has_names(&self) -> bool {
+        self.rules
+            .iter()
+            .filter_map(|r| r.owner.as_ref())
+            .any(|o| o.has_names())
+    }
+    pub fn resolve(&self, db: &IdDatabase) -> anyhow::Result<Policy> {
+        let rules = self
+            .rules
+            .iter()
+            .map(|r| {
+                Ok(Rule {
+                    name: r.name.clone(),
+                    pattern: r.pattern.clone(),
+                    owner: r.owner.as_ref().map(|o| o.resolve(db)).transpose()?,
+                    mode: r.mode,
+                    max_size: r.max_size,
+                    forbidden_types: r.forbidden_types.clone(),
+                })
+            })
+            .collect::<anyhow::Result<Vec<Rule>>>()?;
+
+        Ok(Policy { rules })
+    }
+}
+
+/// Per-path expectations which entries are checked against.
+#[derive(Debug)]
+pub struct Policy {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    name: String,
+    pattern: Regex,
+    owner: Option<OwnerFilter>,
+    mode: Option<u32>,
+    max_size: Option<u64>,
+    forbidden_types: Vec<TypeSelector>,
+}
+
+impl Policy {
+    /// Returns the violations of every rule whose path
+    /// pattern matches the entry reading at most
+    /// 'read_limit' bytes of its contents.
+    pub fn check(
+        &self,
+        ent: &impl Entry,
+        read_limit: Option<u64>,
+    ) -> anyhow::Result<Vec<Violation>> {
+        let path = ent.path();
+        let mut violations = Vec::new();
+
+        for r in self.rules.iter().filter(|r| r.pattern.is_match(&path)) {
+            let mut violation = |check, expected: String, actual: String| {
+                violations.push(Violation {
+                    rule: r.name.clone(),
+                    check,
+                    expected,
+                    actual,
+                })
+            };
+
+            if let Some(owner) = &r.owner {
+                let (uid, gid) = (ent.uid()?, ent.gid()?);
+
+                if !owner.matches(uid, gid) {
+                    violation("owner", owner.to_string(), format!("{uid}:{gid}"));
+                }
+            }
+            if let Some(mode) = r.mode {
+                let actual = ent.mode()? & 0o7777;
+
+                if actual != mode {
+                    violation("mode", format!("{mode:04o}"), format!("{actual:04o}"));
+                }
+            }
+            if let Some(max_size) = r.max_size {
+                let size = ent.size()?;
+
+                if size > max_size {
+                    violation("max_size", max_size.to_string(), size.to_string());
+                }
+            }
+            for t in r.forbidden_types.iter() {
+                if t.matches(ent, read_limit)? {
+                    violation("forbidden_type", "absent".to_string(), t.to_string());
+                }
+            }
+        }
+
+        Ok(violations)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Violation {
+    rule: String,
+    check: &'static str,
+    expected: String,
+    actual: String,
+}
+
+impl Violation {
+    /// Formats the violation as a single line JSON object.
+    pub fn to_json(&self, path: &str) -> String {
+        format!(
+            r#"{{"path":{},"rule":{},"check":"{}","expected":{},"actual":{}}}"#,
+            json_escape(path),
+            json_escape(&self.rule),
+            self.check,
+            json_escape(&self.expected),
+            json_escape(&self.actual),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PolicySpec;
+    use test_case::test_case;
+
+    #[test_case("rules: []", true ; "empty")]
+    #[test_case(
+        "rules:\n- path: '^./etc/'\n  owner: '0:0'\n  mode: '0644'\n  max_size: 1MiB\n  forbidden_types: [sym-link, '!f']",
+        true
+        ; "all checks"
+    )]
+    #[test_case("rules:\n- path: '['", false ; "invalid pattern")]
+    #[test_case("rules:\n- path: '.'\n  mode: '0999'", false ; "invalid mode")]
+    #[test_case("rules:\n- path: '.'\n  max_size: huge", false ; "invalid size")]
+    #[test_case("rules:\n- path: '.'\n  forbidden_types: [j]", false ; "invalid type")]
+    #[test_case("rules:\n- path: '.'\n  color: red", false ; "unknown field")]
+    #[test_case("rules: {", false ; "invalid yaml")]
+    fn parse(data: &str, valid: bool) {
+        assert_eq!(valid, PolicySpec::parse(data).is_ok())
+    }
+}
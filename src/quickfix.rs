@@ -0,0 +1,168 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::escape::json_escape;
+use crate::found::FoundEntry;
+use crate::sink::{Budget, OutputSink, SinkEntry};
+use anyhow::Result;
+use std::io::Write;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Writes results as editor quickfix entries pointing at the
+/// first line of each file. 'Plain' writes 'path:1:1: text'
+/// lines as understood by Vim's default 'errorformat' and
+/// problem matchers while 'Json' writes an array of items
+/// accepted by Neovim's 'setqflist()'. Entries beyond
+/// '--max-output' are omitted while the array stays closed.
+pub struct QuickfixSink<W: Write> {
+    out: W,
+    json: bool,
+    entries: usize,
+    budget: Budget,
+}
+
+impl<W: Write> QuickfixSink<W> {
+    pub(crate) fn new(out: W, json: bool, budget: Budget) -> Self {
+        Self {
+            out,
+            json,
+            entries: 0,
+            budget,
+        }
+    }
+}
+
+impl<W: Write> OutputSink for QuickfixSink<W> {
+    fn needs_metadata(&self) -> bool {
+        true
+    }
+    fn begin(&mut self) -> Result<()> {
+        if self.json {
+            write!(self.out, "[")?;
+        }
+
+        Ok(())
+    }
+    fn write_entry(&mut self, ent: &SinkEntry) -> Result<bool> {
+        let text = match ent.score {
+            Some(score) => format!("cleanup score {}, {}", score, describe(ent.metadata)),
+            None => describe(ent.metadata),
+        };
+
+        let entry = if self.json {
+            format!(
+                r#"{}{{"filename":{},"lnum":1,"col":1,"text":{}}}"#,
+                if self.entries > 0 { "," } else { "" },
+                json_escape(ent.path),
+                json_escape(&text)
+            )
+        } else {
+            format!("{}:1:1: {}\n", ent.path, text)
+        };
+
+        if !self.budget.charge(entry.len()) {
+            return Ok(false);
+        }
+
+        self.out.write_all(entry.as_bytes())?;
+        self.entries += 1;
+
+        Ok(true)
+    }
+    fn finish(&mut self, _omitted: usize) -> Result<()> {
+        if self.json {
+            writeln!(self.out, "]")?;
+        }
+
+        Ok(self.out.flush()?)
+    }
+}
+
+/// Summarizes the metadata of a result in a single line,
+/// e.g. 'file, 8 bytes, mode 0644, modified 2023-03-01T12:00:00Z'.
+fn describe(metadata: Option<&FoundEntry>) -> String {
+    match metadata {
+        Some(m) => format!(
+            "{}, {} bytes, mode {:04o}, modified {}",
+            m.file_type.name(),
+            m.size,
+            m.mode,
+            humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(m.mtime))
+        ),
+        None => "missing".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QuickfixSink;
+    use crate::entry::FileKind;
+    use crate::found::FoundEntry;
+    use crate::sink::{Budget, OutputSink, SinkEntry};
+    use anyhow::Result;
+    use test_case::test_case;
+
+    #[test_case(
+        false,
+        None,
+        "./a.txt:1:1: file, 8 bytes, mode 0644, modified 2023-03-01T12:00:00Z\n./b.txt:1:1: missing\n"
+        ; "plain"
+    )]
+    #[test_case(
+        true,
+        None,
+        concat!(
+            r#"[{"filename":"./a.txt","lnum":1,"col":1,"text":"file, 8 bytes, mode 0644, modified 2023-03-01T12:00:00Z"},"#,
+            r#"{"filename":"./b.txt","lnum":1,"col":1,"text":"missing"}]"#,
+            "\n",
+        )
+        ; "json"
+    )]
+    #[test_case(
+        false,
+        Some(80),
+        "./a.txt:1:1: file, 8 bytes, mode 0644, modified 2023-03-01T12:00:00Z\n"
+        ; "plain limited"
+    )]
+    #[test_case(
+        true,
+        Some(120),
+        concat!(
+            r#"[{"filename":"./a.txt","lnum":1,"col":1,"text":"file, 8 bytes, mode 0644, modified 2023-03-01T12:00:00Z"}]"#,
+            "\n",
+        )
+        ; "json limited"
+    )]
+    fn write(json: bool, max_output: Option<u64>, expected: &str) -> Result<()> {
+        let found = FoundEntry {
+            path: "./a.txt".into(),
+            file_type: FileKind::File,
+            size: 8,
+            mode: 0o644,
+            uid: 0,
+            gid: 0,
+            mtime: 1677672000,
+            security: None,
+        };
+        let mut out = Vec::new();
+        let mut sink = QuickfixSink::new(&mut out, json, Budget::new(max_output));
+
+        sink.begin()?;
+        sink.write_entry(&SinkEntry {
+            path: "./a.txt",
+            metadata: Some(&found),
+            score: None,
+        })?;
+        sink.write_entry(&SinkEntry {
+            path: "./b.txt",
+            metadata: None,
+            score: None,
+        })?;
+        sink.finish(0)?;
+
+        assert_eq!(expected, String::from_utf8(out)?);
+
+        Ok(())
+    }
+}
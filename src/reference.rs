@@ -0,0 +1,93 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Component, Path, PathBuf};
+
+/// List of paths expected to exist, e.g. an artifact
+/// manifest, used to detect orphaned or missing entries.
+#[derive(Debug)]
+pub struct ReferenceList {
+    paths: Vec<PathBuf>,
+    index: HashSet<PathBuf>,
+}
+
+impl ReferenceList {
+    /// Reads paths separated by the given byte, either a
+    /// newline or NUL for lists of arbitrary paths.
+    pub fn load(path: impl AsRef<Path>, sep: u8) -> anyhow::Result<Self> {
+        let data = fs::read(path.as_ref()).map_err(|e| {
+            anyhow::anyhow!(
+                "invalid reference list '{}': {}",
+                path.as_ref().display(),
+                e
+            )
+        })?;
+
+        Ok(Self::parse(&data, sep))
+    }
+    fn parse(data: &[u8], sep: u8) -> Self {
+        let paths = data
+            .split(|b| *b == sep)
+            .filter(|p| !p.is_empty())
+            .map(|p| normalize(Path::new(OsStr::from_bytes(p))))
+            .collect::<Vec<PathBuf>>();
+        let index = paths.iter().cloned().collect();
+
+        Self { paths, index }
+    }
+    pub fn contains(&self, path: impl AsRef<Path>) -> bool {
+        self.index.contains(&normalize(path.as_ref()))
+    }
+    /// Returns the listed paths which are not in the given set
+    /// of normalized paths, in the order they were listed.
+    pub fn missing<'a>(&'a self, seen: &'a HashSet<PathBuf>) -> impl Iterator<Item = &'a PathBuf> {
+        self.paths.iter().filter(|p| !seen.contains(*p))
+    }
+}
+
+/// Drops '.' components so that e.g. './a' and 'a' compare equal.
+pub fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize, ReferenceList};
+    use std::collections::HashSet;
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+    use test_case::test_case;
+
+    #[test_case(b"a.txt\n./one/b.md\n", b'\n', b"a.txt", true ; "newline separated")]
+    #[test_case(b"a.txt\n./one/b.md\n", b'\n', b"./one/b.md", true ; "newline separated dot prefix")]
+    #[test_case(b"a\nb.txt\0c.txt\0", b'\0', b"a\nb.txt", true ; "nul separated with newline in name")]
+    #[test_case(b"a\nb.txt\0c.txt\0", b'\0', b"a", false ; "nul separated partial name")]
+    #[test_case(b"a\nb.txt\0", b'\n', b"a", true ; "newline separated with nul in list")]
+    #[test_case(b"caf\xe9\n", b'\n', b"./caf\xe9", true ; "non utf-8 name")]
+    #[test_case(b"caf\xe9\n", b'\n', "./caf\u{fffd}".as_bytes(), false ; "non utf-8 name replaced")]
+    #[test_case(b"", b'\n', b"a.txt", false ; "empty")]
+    fn contains(data: &[u8], sep: u8, path: &[u8], expected: bool) {
+        assert_eq!(
+            expected,
+            ReferenceList::parse(data, sep).contains(Path::new(OsStr::from_bytes(path)))
+        )
+    }
+    #[test]
+    fn missing() {
+        let list = ReferenceList::parse(b"c\na\nb\n", b'\n');
+        let seen = HashSet::from([normalize(Path::new("./a"))]);
+
+        assert_eq!(
+            vec![&PathBuf::from("c"), &PathBuf::from("b")],
+            list.missing(&seen).collect::<Vec<&PathBuf>>()
+        )
+    }
+}
@@ -0,0 +1,122 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::thread;
+use std::time;
+
+/// Retries operations which fail with errors that are
+/// likely to resolve themselves, e.g. ESTALE on busy
+/// NFS mounts.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RetryPolicy {
+    retries: u32,
+    delay: time::Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(retries: u32, delay: time::Duration) -> Self {
+        Self { retries, delay }
+    }
+    pub fn run<T>(&self, mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        match op() {
+            Err(e) => self.retry(&e, op).ok_or(e),
+            r => r,
+        }
+    }
+    /// Retries an operation which already failed with the
+    /// given error. Returns 'None' if the error was not
+    /// transient or every retry failed.
+    pub fn retry<T>(&self, err: &io::Error, mut op: impl FnMut() -> io::Result<T>) -> Option<T> {
+        let mut transient = is_transient(err);
+
+        for _ in 0..self.retries {
+            if !transient {
+                break;
+            }
+
+            thread::sleep(jitter(self.delay));
+
+            match op() {
+                Ok(v) => return Some(v),
+                Err(e) => transient = is_transient(&e),
+            }
+        }
+
+        None
+    }
+}
+
+fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock
+    ) || e.raw_os_error() == Some(libc::ESTALE)
+}
+
+// Spreads out retries from concurrent scans by adding
+// up to half of the delay again.
+fn jitter(delay: time::Duration) -> time::Duration {
+    let max_nanos = delay.as_nanos() as u64 / 2 + 1;
+    let rand = RandomState::new().build_hasher().finish();
+
+    delay + time::Duration::from_nanos(rand % max_nanos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use std::io;
+    use std::time::Duration;
+    use test_case::test_case;
+
+    #[test_case(0, io::ErrorKind::Interrupted, 1 ; "no retries")]
+    #[test_case(3, io::ErrorKind::Interrupted, 4 ; "retries interrupted")]
+    #[test_case(3, io::ErrorKind::WouldBlock, 4 ; "retries would block")]
+    #[test_case(3, io::ErrorKind::NotFound, 1 ; "does not retry not found")]
+    fn run(retries: u32, kind: io::ErrorKind, expected_attempts: u32) {
+        let mut attempts = 0;
+        let result: io::Result<()> = RetryPolicy::new(retries, Duration::ZERO).run(|| {
+            attempts += 1;
+
+            Err(io::Error::from(kind))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(expected_attempts, attempts)
+    }
+    #[test_case(2, libc::ESTALE, 2 ; "retries stale")]
+    #[test_case(0, libc::ESTALE, 0 ; "no retries")]
+    #[test_case(2, libc::ENOENT, 0 ; "does not retry missing")]
+    fn retry(retries: u32, errno: i32, expected_attempts: u32) {
+        let mut attempts = 0;
+        let result: Option<()> = RetryPolicy::new(retries, Duration::ZERO).retry(
+            &io::Error::from_raw_os_error(errno),
+            || {
+                attempts += 1;
+
+                Err(io::Error::from_raw_os_error(errno))
+            },
+        );
+
+        assert!(result.is_none());
+        assert_eq!(expected_attempts, attempts)
+    }
+    #[test]
+    fn run_stale() {
+        let mut attempts = 0;
+        let result = RetryPolicy::new(2, Duration::ZERO).run(|| {
+            attempts += 1;
+
+            match attempts {
+                1 => Err(io::Error::from_raw_os_error(libc::ESTALE)),
+                _ => Ok(attempts),
+            }
+        });
+
+        assert_eq!(2, result.unwrap())
+    }
+}
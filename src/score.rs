@@ -0,0 +1,195 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::entry::FileKind;
+use crate::found::FoundEntry;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::Path;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+// Each signal contributes up to its weight so that
+// scores range from 0 to 100.
+const AGE_WEIGHT: f64 = 35.0;
+const SIZE_WEIGHT: f64 = 25.0;
+const HINT_WEIGHT: u32 = 20;
+const DUPLICATE_WEIGHT: u32 = 15;
+const FILE_WEIGHT: u32 = 5;
+
+/// Entries untouched for this many days receive the full
+/// age weight.
+const FULL_AGE_DAYS: f64 = 365.0;
+
+/// Entries of this many bytes, 1GiB, receive the full size
+/// weight. Smaller entries are weighed logarithmically.
+const FULL_SIZE_BITS: f64 = 30.0;
+
+/// Path components which hint at disposable content.
+const DIR_HINTS: &[&str] = &[
+    "cache",
+    ".cache",
+    "tmp",
+    "temp",
+    ".tmp",
+    "trash",
+    ".trash",
+    ".Trash",
+    "node_modules",
+    "__pycache__",
+];
+
+/// File name suffixes which hint at disposable content.
+const NAME_HINTS: &[&str] = &[
+    ".tmp", ".temp", ".bak", ".old", ".log", ".swp", ".orig", "~",
+];
+
+/// Rates how likely a result is to be safe to clean up
+/// from how long ago it was modified, its size, whether its
+/// path hints at caches or temporary files, whether another
+/// result is likely a duplicate of it and whether it is a
+/// regular file.
+pub fn score(ent: &FoundEntry, now: u64, duplicate: bool) -> u32 {
+    let age_days = now.saturating_sub(ent.mtime) as f64 / SECS_PER_DAY as f64;
+    let age = (age_days / FULL_AGE_DAYS).min(1.0) * AGE_WEIGHT;
+    let size = ((ent.size as f64 + 1.0).log2() / FULL_SIZE_BITS).min(1.0) * SIZE_WEIGHT;
+
+    let mut score = (age + size).round() as u32;
+
+    if has_hint(&ent.path) {
+        score += HINT_WEIGHT;
+    }
+    if duplicate {
+        score += DUPLICATE_WEIGHT;
+    }
+    if ent.file_type == FileKind::File {
+        score += FILE_WEIGHT;
+    }
+
+    score
+}
+
+fn has_hint(path: &str) -> bool {
+    let path = Path::new(path);
+    let in_hinted_dir = path
+        .parent()
+        .into_iter()
+        .flat_map(|p| p.iter())
+        .any(|c| c.to_str().is_some_and(|c| DIR_HINTS.contains(&c)));
+    let hinted_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| NAME_HINTS.iter().any(|h| n.ends_with(h)));
+
+    in_hinted_dir || hinted_name
+}
+
+/// Scores every result and returns those scoring at least
+/// 'min' ordered from the highest score, keeping at most
+/// 'top' of them. Regular files sharing their name and size
+/// with another result are considered likely duplicates.
+pub fn rank(
+    entries: Vec<FoundEntry>,
+    now: u64,
+    min: u32,
+    top: Option<usize>,
+) -> Vec<(u32, FoundEntry)> {
+    let mut copies = HashMap::new();
+
+    for e in entries.iter().filter(|e| e.file_type == FileKind::File) {
+        *copies.entry(duplicate_key(e)).or_insert(0) += 1;
+    }
+
+    let duplicates: Vec<bool> = entries
+        .iter()
+        .map(|e| e.file_type == FileKind::File && copies.get(&duplicate_key(e)) > Some(&1))
+        .collect();
+
+    let mut ranked: Vec<(u32, FoundEntry)> = entries
+        .into_iter()
+        .zip(duplicates)
+        .map(|(e, duplicate)| (score(&e, now, duplicate), e))
+        .filter(|(s, _)| *s >= min)
+        .collect();
+
+    // ties keep the order results were found in
+    ranked.sort_by_key(|(s, _)| Reverse(*s));
+    ranked.truncate(top.unwrap_or(usize::MAX));
+
+    ranked
+}
+
+fn duplicate_key(ent: &FoundEntry) -> (Option<&str>, u64) {
+    (
+        Path::new(&ent.path).file_name().and_then(|n| n.to_str()),
+        ent.size,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_hint, rank, score};
+    use crate::entry::FileKind;
+    use crate::found::FoundEntry;
+    use test_case::test_case;
+
+    const NOW: u64 = 1_700_000_000;
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn found(path: &str, file_type: FileKind, size: u64, age_days: u64) -> FoundEntry {
+        FoundEntry {
+            path: path.into(),
+            file_type,
+            size,
+            mode: 0o644,
+            uid: 0,
+            gid: 0,
+            mtime: NOW - age_days * DAY,
+            security: None,
+        }
+    }
+
+    #[test_case("./src/main.rs", false ; "source file")]
+    #[test_case("./home/.cache/thumbs/a.png", true ; "cache directory")]
+    #[test_case("./tmp/a", true ; "temporary directory")]
+    #[test_case("./notes.txt~", true ; "editor backup")]
+    #[test_case("./db.bak", true ; "backup")]
+    #[test_case("./cache", false ; "directory itself")]
+    fn hint(path: &str, expected: bool) {
+        assert_eq!(expected, has_hint(path))
+    }
+
+    #[test_case(found("./a", FileKind::Dir, 0, 0), false, 0 ; "new empty directory")]
+    #[test_case(found("./a", FileKind::File, 0, 0), false, 5 ; "new empty file")]
+    #[test_case(found("./a", FileKind::File, 0, 730), false, 40 ; "old file")]
+    #[test_case(found("./a", FileKind::File, 1 << 30, 0), false, 30 ; "large file")]
+    #[test_case(found("./tmp/a", FileKind::File, 0, 0), true, 40 ; "duplicate temporary file")]
+    #[test_case(found("./tmp/a.bak", FileKind::File, 1 << 40, 1000), true, 100 ; "maximum")]
+    fn scores(ent: FoundEntry, duplicate: bool, expected: u32) {
+        assert_eq!(expected, score(&ent, NOW, duplicate))
+    }
+
+    #[test]
+    fn ranking() {
+        let ranked = rank(
+            vec![
+                found("./a/x", FileKind::File, 0, 0),
+                found("./b/x", FileKind::File, 0, 0),
+                found("./c", FileKind::Dir, 0, 0),
+                found("./d", FileKind::File, 0, 730),
+            ],
+            NOW,
+            1,
+            Some(2),
+        );
+
+        assert_eq!(
+            vec![(40, "./d"), (20, "./a/x")],
+            ranked
+                .iter()
+                .map(|(s, e)| (*s, e.path.as_str()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
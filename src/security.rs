@@ -0,0 +1,97 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use std::path::Path;
+
+/// Summarizes the security relevant attributes of an entry
+/// in one compact field: special permission bits, file
+/// capabilities, POSIX ACLs and, when built with the
+/// 'selinux' feature, the SELinux context, e.g.
+/// 'setuid,caps,selinux=system_u:object_r:bin_t:s0'. Entries
+/// without any such attributes are summarized as '-'.
+pub fn label(path: &Path, mode: u32) -> io::Result<String> {
+    let mut parts = [(0o4000, "setuid"), (0o2000, "setgid"), (0o1000, "sticky")]
+        .iter()
+        .filter(|(bit, _)| mode & bit != 0)
+        .map(|(_, name)| name.to_string())
+        .collect::<Vec<String>>();
+
+    if read_xattr(path, "security.capability")?.is_some() {
+        parts.push("caps".to_string());
+    }
+    if has_acl(path)? || read_xattr(path, "system.posix_acl_default")?.is_some() {
+        parts.push("acl".to_string());
+    }
+    #[cfg(feature = "selinux")]
+    if let Some(ctx) = read_xattr(path, "security.selinux")? {
+        let ctx = String::from_utf8_lossy(&ctx);
+
+        parts.push(format!("selinux={}", ctx.trim_end_matches('\0')));
+    }
+
+    Ok(if parts.is_empty() {
+        "-".to_string()
+    } else {
+        parts.join(",")
+    })
+}
+
+/// Reports whether an entry carries a POSIX access ACL.
+/// Filesystems only store one when it grants more than the
+/// basic mode bits.
+pub fn has_acl(path: &Path) -> io::Result<bool> {
+    Ok(read_xattr(path, "system.posix_acl_access")?.is_some())
+}
+
+/// Reads an extended attribute of the given path without
+/// following symlinks. Attributes which are not set or not
+/// supported by the filesystem are reported as absent.
+pub fn read_xattr(path: &Path, name: &str) -> io::Result<Option<Vec<u8>>> {
+    match xattr::get(path, name) {
+        Err(e) if e.raw_os_error() == Some(libc::ENOTSUP) => Ok(None),
+        r => r,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_acl, label};
+    use anyhow::Result;
+    use test_case::test_case;
+
+    #[test_case(0o644, "-" ; "no special bits")]
+    #[test_case(0o4755, "setuid" ; "setuid")]
+    #[test_case(0o6755, "setuid,setgid" ; "setuid and setgid")]
+    #[test_case(0o1777, "sticky" ; "sticky")]
+    fn mode_bits(mode: u32, expected: &str) -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        let label = label(file.path(), mode)?;
+
+        // SELinux enabled hosts label every file
+        let label = label
+            .split(',')
+            .filter(|p| !p.starts_with("selinux="))
+            .collect::<Vec<&str>>()
+            .join(",");
+
+        assert_eq!(expected, if label.is_empty() { "-" } else { &label });
+
+        Ok(file.close()?)
+    }
+
+    #[test]
+    fn without_acl() -> Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+
+        assert!(!has_acl(file.path())?);
+
+        Ok(file.close()?)
+    }
+
+    #[test]
+    fn missing_path() {
+        assert!(label("dne".as_ref(), 0o644).is_err())
+    }
+}
@@ -0,0 +1,637 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::events::EventSink;
+use crate::found::FoundEntry;
+use crate::i18n::Lang;
+use crate::options::Options;
+use crate::output::OutputFormat;
+use crate::quickfix::QuickfixSink;
+use crate::split::{Destination, SplitWriter};
+use crate::store::ResultStore;
+use crate::template::CommandTemplate;
+use crate::{Error, Summary};
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use std::io::{self, Write};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Output formats implemented by findr itself which cannot
+/// be replaced by registered sinks.
+const BUILTIN: &[&str] = &[
+    "plain",
+    "events",
+    "quickfix",
+    "quickfix-json",
+    "shell-array",
+    "sqlite",
+    "parquet",
+];
+
+/// Creates a sink from the argument following its name in
+/// '--output', e.g. 'ARG' in 'NAME:ARG'.
+pub type SinkFactory = fn(Option<&str>) -> Result<Box<dyn OutputSink>>;
+
+/// How often progress is reported to sinks.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+static REGISTRY: RwLock<Vec<(String, SinkFactory)>> = RwLock::new(Vec::new());
+
+/// Registers a sink selectable with '--output NAME' or
+/// '--output NAME:ARG'. Sinks must be registered before
+/// options are parsed and names cannot be registered twice.
+pub fn register(name: &str, factory: SinkFactory) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if !valid {
+        anyhow::bail!("invalid sink name '{}'", name);
+    }
+
+    let mut registry = REGISTRY.write().expect("sink registry is not poisoned");
+
+    if BUILTIN.contains(&name) || registry.iter().any(|(n, _)| n == name) {
+        anyhow::bail!("sink '{}' is already registered", name);
+    }
+
+    registry.push((name.to_string(), factory));
+
+    Ok(())
+}
+
+/// Reports whether a sink was registered with the given name.
+pub fn is_registered(name: &str) -> bool {
+    REGISTRY
+        .read()
+        .expect("sink registry is not poisoned")
+        .iter()
+        .any(|(n, _)| n == name)
+}
+
+fn create(name: &str, arg: Option<&str>) -> Result<Box<dyn OutputSink>> {
+    let factory = REGISTRY
+        .read()
+        .expect("sink registry is not poisoned")
+        .iter()
+        .find_map(|(n, f)| (n == name).then_some(*f));
+
+    match factory {
+        Some(f) => f(arg),
+        None => anyhow::bail!("unknown output format '{}'", name),
+    }
+}
+
+/// A single result handed to a sink.
+pub struct SinkEntry<'a> {
+    /// The path as it is displayed after '--transform'
+    /// and '--escape' were applied.
+    pub path: &'a str,
+    /// The metadata of the result if the sink asked for it.
+    /// Paths reported by '--missing' have no metadata.
+    pub metadata: Option<&'a FoundEntry>,
+    /// The cleanup score of the result if results are
+    /// ranked with '--score-cleanup'.
+    pub score: Option<u32>,
+}
+
+/// A destination for the results of a search.
+pub trait OutputSink {
+    /// Reports whether results must carry their metadata.
+    /// Reading metadata is skipped for sinks which only
+    /// need paths. Defaults to 'false'.
+    fn needs_metadata(&self) -> bool {
+        false
+    }
+    /// Reports whether metadata must include the security
+    /// summary which reads several extended attributes of
+    /// every result. Defaults to 'false'.
+    fn needs_security(&self) -> bool {
+        false
+    }
+    /// Called once before the first result.
+    fn begin(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Records a single result returning 'false' if it was
+    /// omitted, e.g. due to '--max-output'.
+    fn write_entry(&mut self, ent: &SinkEntry) -> Result<bool>;
+    /// Reports an error encountered during the search.
+    fn write_error(&mut self, _message: &str) -> Result<()> {
+        Ok(())
+    }
+    /// Reports a condition which does not fail the search
+    /// but may affect its results.
+    fn write_warning(&mut self, _message: &str) -> Result<()> {
+        Ok(())
+    }
+    /// Reports the number of entries visited so far. Called
+    /// at most once per second while searching.
+    fn write_progress(&mut self, _visited: usize) -> Result<()> {
+        Ok(())
+    }
+    /// Reports the totals of the search before it finishes.
+    fn write_summary(&mut self, _summary: &Summary) -> Result<()> {
+        Ok(())
+    }
+    /// Writes a line which is not a result such as a policy
+    /// violation or report returning 'false' if it was
+    /// omitted like 'write_entry'. Only text sinks support
+    /// them.
+    fn write_line(&mut self, _line: &str) -> Result<bool> {
+        anyhow::bail!("output format does not support reports")
+    }
+    /// Called once after the last result with the number
+    /// of results which were omitted.
+    fn finish(&mut self, _omitted: usize) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// How a sink which fails during a search is handled.
+/// 'abort' stops the search while 'disable' reports the
+/// failure and continues writing to the remaining sinks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum SinkErrorPolicy {
+    #[default]
+    Abort,
+    Disable,
+}
+
+struct Slot {
+    name: String,
+    sink: Box<dyn OutputSink>,
+    written: usize,
+    omitted: usize,
+    failure: Option<String>,
+}
+
+/// Dispatches results to every sink selected with '--output'.
+/// Each sink buffers its own output so a failing sink can be
+/// disabled without affecting the others. Failures are
+/// reported to the given warning output.
+pub(crate) struct SinkSet<W = io::Stderr> {
+    slots: Vec<Slot>,
+    policy: SinkErrorPolicy,
+    warnings: W,
+    last_progress: Instant,
+}
+
+impl SinkSet {
+    /// Opens the sinks selected by the given options. At most
+    /// one of them may be written as text to stdout.
+    pub fn open(options: &Options) -> Result<Self> {
+        let text = options.output.iter().filter(|f| f.is_text()).count();
+
+        if text > 1 {
+            anyhow::bail!("only one text output may be written at a time");
+        }
+
+        let slots = options
+            .output
+            .iter()
+            .map(|f| {
+                Ok(Slot {
+                    name: f.to_string(),
+                    sink: open(f, options)?,
+                    written: 0,
+                    omitted: 0,
+                    failure: None,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            slots,
+            policy: options.on_sink_error,
+            warnings: io::stderr(),
+            last_progress: Instant::now(),
+        })
+    }
+}
+
+impl<W: Write> SinkSet<W> {
+    pub fn needs_metadata(&self) -> bool {
+        self.active().any(|s| s.sink.needs_metadata())
+    }
+    pub fn needs_security(&self) -> bool {
+        self.active().any(|s| s.sink.needs_security())
+    }
+    pub fn begin(&mut self) -> Result<()> {
+        self.dispatch(|s| s.sink.begin())
+    }
+    /// Hands a result to every sink returning 'false' if any
+    /// of them omitted it.
+    pub fn write_entry(&mut self, ent: &SinkEntry) -> Result<bool> {
+        self.dispatch_counted(|s| s.write_entry(ent))
+    }
+    /// Hands a line to every sink returning 'false' if any
+    /// of them omitted it.
+    pub fn write_line(&mut self, line: &str) -> Result<bool> {
+        self.dispatch_counted(|s| s.write_line(line))
+    }
+    pub fn write_error(&mut self, message: &str) -> Result<()> {
+        self.dispatch(|s| s.sink.write_error(message))
+    }
+    /// Hands a warning to every sink and writes it to the
+    /// warning output.
+    pub fn write_warning(&mut self, message: &str) -> Result<()> {
+        self.dispatch(|s| s.sink.write_warning(message))?;
+
+        Ok(writeln!(
+            self.warnings,
+            "{}: warning: {}",
+            clap::crate_name!(),
+            message
+        )?)
+    }
+    /// Reports progress unless it was already reported
+    /// within the last second.
+    pub fn write_progress(&mut self, visited: usize) -> Result<()> {
+        if self.last_progress.elapsed() < PROGRESS_INTERVAL {
+            return Ok(());
+        }
+
+        self.last_progress = Instant::now();
+        self.dispatch(|s| s.sink.write_progress(visited))
+    }
+    pub fn write_summary(&mut self, summary: &Summary) -> Result<()> {
+        self.dispatch(|s| s.sink.write_summary(summary))
+    }
+    pub fn finish(&mut self) -> Result<()> {
+        self.dispatch(|s| s.sink.finish(s.omitted))
+    }
+    /// Returns the number of sinks which were disabled.
+    pub fn failures(&self) -> usize {
+        self.slots.iter().filter(|s| s.failure.is_some()).count()
+    }
+    /// Writes the number of results written and omitted by
+    /// each sink if several were used or any of them failed.
+    pub fn write_stats(&mut self) -> io::Result<()> {
+        if self.slots.len() < 2 && self.failures() == 0 {
+            return Ok(());
+        }
+
+        for s in &self.slots {
+            write!(
+                self.warnings,
+                "{}: output '{}': {} written, {} omitted",
+                clap::crate_name!(),
+                s.name,
+                s.written,
+                s.omitted
+            )?;
+
+            match &s.failure {
+                Some(e) => writeln!(self.warnings, ", failed: {e}")?,
+                None => writeln!(self.warnings)?,
+            }
+        }
+
+        Ok(())
+    }
+    fn active(&self) -> impl Iterator<Item = &Slot> {
+        self.slots.iter().filter(|s| s.failure.is_none())
+    }
+    /// Dispatches a result counting it as written or
+    /// omitted by each sink.
+    fn dispatch_counted(
+        &mut self,
+        mut f: impl FnMut(&mut dyn OutputSink) -> Result<bool>,
+    ) -> Result<bool> {
+        let mut written = true;
+
+        self.dispatch(|s| {
+            if f(s.sink.as_mut())? {
+                s.written += 1;
+            } else {
+                s.omitted += 1;
+                written = false;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(written)
+    }
+    fn dispatch(&mut self, mut f: impl FnMut(&mut Slot) -> Result<()>) -> Result<()> {
+        for s in self.slots.iter_mut().filter(|s| s.failure.is_none()) {
+            let Err(e) = f(s) else { continue };
+
+            if self.policy == SinkErrorPolicy::Abort {
+                return Err(anyhow!(Error::Output(anyhow!(
+                    "output '{}' failed: {}",
+                    s.name,
+                    e
+                ))));
+            }
+
+            writeln!(
+                self.warnings,
+                "{}: warning: disabling output '{}': {}",
+                clap::crate_name!(),
+                s.name,
+                e
+            )?;
+
+            s.failure = Some(e.to_string());
+        }
+
+        if self.active().next().is_none() {
+            return Err(anyhow!(Error::Output(anyhow!("every output failed"))));
+        }
+
+        Ok(())
+    }
+}
+
+fn open(format: &OutputFormat, options: &Options) -> Result<Box<dyn OutputSink>> {
+    if let Some(store) = ResultStore::open(format)? {
+        return Ok(Box::new(store));
+    }
+    match format {
+        OutputFormat::Custom(name, arg) => return create(name, arg.as_deref()),
+        OutputFormat::Events => {
+            return Ok(Box::new(EventSink::new(
+                io::stdout().lock(),
+                Budget::new(options.max_output),
+            )))
+        }
+        OutputFormat::Quickfix | OutputFormat::QuickfixJson => {
+            return Ok(Box::new(QuickfixSink::new(
+                io::stdout().lock(),
+                *format == OutputFormat::QuickfixJson,
+                Budget::new(options.max_output),
+            )))
+        }
+        _ => (),
+    }
+
+    let out = match &options.split_output {
+        Some(pattern) => Destination::Split(SplitWriter::new(
+            pattern.clone(),
+            options.split_every.map_or(usize::MAX, |n| n.get()),
+            format.clone(),
+        )),
+        None => Destination::Stdout(io::stdout().lock()),
+    };
+
+    Ok(Box::new(TextSink {
+        out,
+        format: format.clone(),
+        template: options.emit_cmd.clone(),
+        budget: Budget::new(options.max_output),
+        lang: options.lang(),
+    }))
+}
+
+/// Writes one line per result in a text format or as
+/// commands given with '--emit-cmd'.
+pub(crate) struct TextSink<'a> {
+    out: Destination<'a>,
+    format: OutputFormat,
+    template: Option<CommandTemplate>,
+    budget: Budget,
+    lang: Lang,
+}
+
+/// Tracks the number of bytes written against the limit
+/// given with '--max-output'.
+pub(crate) struct Budget {
+    max: Option<u64>,
+    written: u64,
+    exhausted: bool,
+}
+
+impl Budget {
+    pub fn new(max: Option<u64>) -> Self {
+        Self {
+            max,
+            written: 0,
+            exhausted: false,
+        }
+    }
+    /// Charges the given number of bytes against the budget
+    /// and returns false if they must be omitted instead.
+    pub fn charge(&mut self, len: usize) -> bool {
+        let written = self.written + len as u64;
+
+        // once a line is omitted every later one is as well
+        // so that the output is a prefix of the full results
+        if self.exhausted || self.max.is_some_and(|max| written > max) {
+            self.exhausted = true;
+
+            return false;
+        }
+
+        self.written = written;
+
+        true
+    }
+}
+
+impl TextSink<'_> {
+    /// Writes the given line unless it would exceed the
+    /// budget given with '--max-output'.
+    fn write_within_budget(&mut self, line: &[u8]) -> Result<bool> {
+        if !self.budget.charge(line.len()) {
+            return Ok(false);
+        }
+
+        self.out.write_all(line)?;
+        self.out.end_entry()?;
+
+        Ok(true)
+    }
+}
+
+impl OutputSink for TextSink<'_> {
+    fn needs_metadata(&self) -> bool {
+        self.format.has_metadata()
+    }
+    fn needs_security(&self) -> bool {
+        self.format.has_metadata()
+    }
+    fn begin(&mut self) -> Result<()> {
+        Ok(self.out.write_header(&self.format)?)
+    }
+    fn write_entry(&mut self, ent: &SinkEntry) -> Result<bool> {
+        let mut line = Vec::new();
+
+        match (&self.template, ent.score, &self.format) {
+            (Some(t), _, _) => writeln!(line, "{}", t.render(ent.path))?,
+            (None, Some(score), OutputFormat::Plain) => writeln!(line, "{score}\t{}", ent.path)?,
+            (None, _, _) => self.format.write_entry(&mut line, ent.path, ent.metadata)?,
+        }
+
+        self.write_within_budget(&line)
+    }
+    fn write_line(&mut self, line: &str) -> Result<bool> {
+        self.write_within_budget(format!("{line}\n").as_bytes())
+    }
+    fn finish(&mut self, omitted: usize) -> Result<()> {
+        if omitted > 0 {
+            self.format.write_truncated(
+                &mut self.out,
+                self.template.is_some(),
+                omitted,
+                self.lang,
+            )?;
+        }
+
+        Ok(self.out.finish(&self.format)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_registered, register, OutputSink, SinkEntry, SinkErrorPolicy, SinkSet, Slot};
+    use crate::output::OutputFormat;
+    use anyhow::{anyhow, Result};
+    use std::time::Instant;
+    use test_case::test_case;
+
+    struct Discard;
+
+    impl OutputSink for Discard {
+        fn write_entry(&mut self, _ent: &SinkEntry) -> Result<bool> {
+            Ok(true)
+        }
+    }
+
+    /// Fails once the given number of results were written.
+    struct Full(usize);
+
+    impl OutputSink for Full {
+        fn write_entry(&mut self, _ent: &SinkEntry) -> Result<bool> {
+            match self.0 {
+                0 => Err(anyhow!("no space left on device")),
+                _ => {
+                    self.0 -= 1;
+
+                    Ok(true)
+                }
+            }
+        }
+    }
+
+    fn sink_set(
+        sinks: Vec<(&str, Box<dyn OutputSink>)>,
+        policy: SinkErrorPolicy,
+    ) -> SinkSet<Vec<u8>> {
+        SinkSet {
+            slots: sinks
+                .into_iter()
+                .map(|(name, sink)| Slot {
+                    name: name.into(),
+                    sink,
+                    written: 0,
+                    omitted: 0,
+                    failure: None,
+                })
+                .collect(),
+            policy,
+            warnings: Vec::new(),
+            last_progress: Instant::now(),
+        }
+    }
+
+    fn write_entries(sinks: &mut SinkSet<Vec<u8>>, n: usize) -> Result<()> {
+        for _ in 0..n {
+            sinks.write_entry(&SinkEntry {
+                path: "a",
+                metadata: None,
+                score: None,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn failed_sink_disabled() -> Result<()> {
+        let mut sinks = sink_set(
+            vec![("plain", Box::new(Discard)), ("db", Box::new(Full(1)))],
+            SinkErrorPolicy::Disable,
+        );
+
+        write_entries(&mut sinks, 3)?;
+        sinks.finish()?;
+        sinks.write_stats()?;
+
+        assert_eq!(1, sinks.failures());
+        assert_eq!(
+            "findr: warning: disabling output 'db': no space left on device\n\
+             findr: output 'plain': 3 written, 0 omitted\n\
+             findr: output 'db': 1 written, 0 omitted, failed: no space left on device\n",
+            String::from_utf8(sinks.warnings)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn warning() -> Result<()> {
+        let mut sinks = sink_set(vec![("plain", Box::new(Discard))], SinkErrorPolicy::Abort);
+
+        sinks.write_warning("mount table changed during search")?;
+
+        assert_eq!(
+            "findr: warning: mount table changed during search\n",
+            String::from_utf8(sinks.warnings)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn failed_sink_aborts() {
+        let mut sinks = sink_set(
+            vec![("plain", Box::new(Discard)), ("db", Box::new(Full(1)))],
+            SinkErrorPolicy::Abort,
+        );
+
+        assert!(write_entries(&mut sinks, 2).is_err());
+    }
+
+    #[test]
+    fn every_sink_failed() {
+        let mut sinks = sink_set(vec![("db", Box::new(Full(0)))], SinkErrorPolicy::Disable);
+
+        assert!(write_entries(&mut sinks, 1).is_err());
+    }
+
+    #[test_case("discard-a", true ; "new name")]
+    #[test_case("plain", false ; "builtin name")]
+    #[test_case("", false ; "empty name")]
+    #[test_case("a:b", false ; "name with separator")]
+    fn registration(name: &str, expected: bool) {
+        assert_eq!(expected, register(name, |_| Ok(Box::new(Discard))).is_ok());
+        assert_eq!(expected, is_registered(name));
+    }
+
+    #[test]
+    fn duplicate_registration() {
+        assert!(register("discard-b", |_| Ok(Box::new(Discard))).is_ok());
+        assert!(register("discard-b", |_| Ok(Box::new(Discard))).is_err());
+    }
+
+    #[test]
+    fn registered_output_format() -> Result<()> {
+        register("discard-c", |_| Ok(Box::new(Discard)))?;
+
+        assert_eq!(
+            OutputFormat::Custom("discard-c".into(), None),
+            "discard-c".parse()?
+        );
+        assert_eq!(
+            OutputFormat::Custom("discard-c".into(), Some("arg".into())),
+            "discard-c:arg".parse()?
+        );
+
+        Ok(())
+    }
+}
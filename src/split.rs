@@ -0,0 +1,242 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::output::OutputFormat;
+use std::fs;
+use std::io::{self, BufWriter, StdoutLock, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A printf style file name with a single '%d' or '%0Nd'
+/// counter, e.g. 'results-%03d.txt'. '%%' is a literal '%'.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplitPattern {
+    prefix: String,
+    width: usize,
+    suffix: String,
+}
+
+impl SplitPattern {
+    /// Returns the path of the file with the given number.
+    pub fn path(&self, n: usize) -> PathBuf {
+        format!(
+            "{}{:0width$}{}",
+            self.prefix,
+            n,
+            self.suffix,
+            width = self.width
+        )
+        .into()
+    }
+}
+
+impl FromStr for SplitPattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = [String::new(), String::new()];
+        let mut width = None;
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                parts[usize::from(width.is_some())].push(c);
+                continue;
+            }
+            if chars.next_if_eq(&'%').is_some() {
+                parts[usize::from(width.is_some())].push('%');
+                continue;
+            }
+            if width.is_some() {
+                anyhow::bail!("split pattern '{}' has more than one counter", s);
+            }
+
+            let mut digits = String::new();
+
+            while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                digits.push(d);
+            }
+            if chars.next() != Some('d') || (!digits.is_empty() && !digits.starts_with('0')) {
+                anyhow::bail!("split pattern '{}' has an invalid counter", s);
+            }
+
+            width = Some(digits.parse().unwrap_or_default());
+        }
+
+        let [prefix, suffix] = parts;
+
+        match width {
+            Some(width) => Ok(Self {
+                prefix,
+                width,
+                suffix,
+            }),
+            None => anyhow::bail!("split pattern '{}' has no '%d' counter", s),
+        }
+    }
+}
+
+/// Writes results across numbered files starting a new file
+/// once the current one holds the given number of entries.
+/// Every file is a complete document of the output format.
+pub struct SplitWriter {
+    pattern: SplitPattern,
+    every: usize,
+    format: OutputFormat,
+    index: usize,
+    entries: usize,
+    file: Option<BufWriter<fs::File>>,
+}
+
+impl SplitWriter {
+    pub fn new(pattern: SplitPattern, every: usize, format: OutputFormat) -> Self {
+        Self {
+            pattern,
+            every,
+            format,
+            index: 0,
+            entries: 0,
+            file: None,
+        }
+    }
+    /// Marks the end of an entry rotating to the next
+    /// file if the current one is full.
+    pub fn end_entry(&mut self) -> io::Result<()> {
+        self.entries += 1;
+
+        if self.entries >= self.every {
+            self.finish()?;
+        }
+
+        Ok(())
+    }
+    /// Completes the current file if any.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if let Some(mut file) = self.file.take() {
+            self.format.write_footer(&mut file)?;
+            file.flush()?;
+        }
+
+        self.entries = 0;
+
+        Ok(())
+    }
+    fn current(&mut self) -> io::Result<&mut BufWriter<fs::File>> {
+        if self.file.is_none() {
+            self.index += 1;
+
+            let mut file = BufWriter::new(fs::File::create(self.pattern.path(self.index))?);
+
+            self.format.write_header(&mut file)?;
+            self.file = Some(file);
+        }
+
+        Ok(self.file.as_mut().expect("file was just opened"))
+    }
+}
+
+impl Write for SplitWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.current()?.write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some(f) => f.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Where results are written.
+pub enum Destination<'a> {
+    Stdout(StdoutLock<'a>),
+    Split(SplitWriter),
+}
+
+impl Destination<'_> {
+    pub fn write_header(&mut self, format: &OutputFormat) -> io::Result<()> {
+        match self {
+            Self::Stdout(out) => format.write_header(out),
+            // every file receives its own header when opened
+            Self::Split(_) => Ok(()),
+        }
+    }
+    pub fn end_entry(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout(_) => Ok(()),
+            Self::Split(w) => w.end_entry(),
+        }
+    }
+    pub fn finish(&mut self, format: &OutputFormat) -> io::Result<()> {
+        match self {
+            Self::Stdout(out) => format.write_footer(out),
+            Self::Split(w) => w.finish(),
+        }
+    }
+}
+
+impl Write for Destination<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Stdout(out) => out.write(buf),
+            Self::Split(w) => w.write(buf),
+        }
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout(out) => out.flush(),
+            Self::Split(w) => w.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SplitPattern, SplitWriter};
+    use crate::output::OutputFormat;
+    use anyhow::Result;
+    use std::fs;
+    use std::io::Write;
+    use test_case::test_case;
+
+    #[test_case("results-%03d.txt", 7, Some("results-007.txt") ; "padded")]
+    #[test_case("results-%d", 12, Some("results-12") ; "unpadded")]
+    #[test_case("%d%%.txt", 1, Some("1%.txt") ; "literal percent")]
+    #[test_case("results.txt", 1, None ; "no counter")]
+    #[test_case("%d-%d", 1, None ; "two counters")]
+    #[test_case("%s", 1, None ; "unsupported conversion")]
+    #[test_case("%3d", 1, None ; "space padding")]
+    fn pattern(s: &str, n: usize, expected: Option<&str>) {
+        assert_eq!(
+            expected.map(Into::into),
+            s.parse::<SplitPattern>().ok().map(|p| p.path(n))
+        )
+    }
+
+    #[test]
+    fn rotates() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let pattern = format!("{}/out-%d.sh", dir.path().display()).parse()?;
+        let mut w = SplitWriter::new(pattern, 2, OutputFormat::ShellArray("F".into()));
+
+        for p in ["a", "b", "c"] {
+            writeln!(w, "  '{p}'")?;
+            w.end_entry()?;
+        }
+
+        w.finish()?;
+
+        assert_eq!(
+            "F=(\n  'a'\n  'b'\n)\n",
+            fs::read_to_string(dir.path().join("out-1.sh"))?
+        );
+        assert_eq!(
+            "F=(\n  'c'\n)\n",
+            fs::read_to_string(dir.path().join("out-2.sh"))?
+        );
+        assert!(!dir.path().join("out-3.sh").exists());
+
+        Ok(dir.close()?)
+    }
+}
@@ -0,0 +1,286 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::found::FoundEntry;
+use anyhow::Result;
+use std::path::Path;
+
+pub use imp::{ResultDb, StoredEntries};
+
+#[cfg(feature = "sqlite")]
+mod imp {
+    use super::*;
+    use crate::entry::FileKind;
+    use anyhow::Context;
+    use rusqlite::{params, Connection, OpenFlags};
+    use std::collections::VecDeque;
+
+    /// Number of rows inserted per transaction.
+    const BATCH_SIZE: usize = 1000;
+
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS results (
+            path TEXT NOT NULL,
+            type TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            mode INTEGER NOT NULL,
+            uid INTEGER NOT NULL,
+            gid INTEGER NOT NULL,
+            mtime INTEGER NOT NULL,
+            security TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS results_path ON results (path);
+    ";
+
+    const INSERT: &str = "
+        INSERT INTO results (path, type, size, mode, uid, gid, mtime, security)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+    ";
+
+    /// Writes results into the 'results' table of a SQLite
+    /// database after the results of any previous search.
+    /// Existing tables are never dropped.
+    pub struct ResultDb {
+        conn: Connection,
+        pending: usize,
+    }
+
+    impl ResultDb {
+        pub fn create(path: &Path) -> Result<Self> {
+            // an existing table of another schema is rejected
+            // up front rather than on the first insert
+            let conn = Connection::open(path)
+                .and_then(|c| {
+                    c.execute_batch(SCHEMA)?;
+                    c.prepare_cached(INSERT)?;
+
+                    Ok(c)
+                })
+                .with_context(|| format!("invalid results database '{}'", path.display()))?;
+
+            Ok(Self { conn, pending: 0 })
+        }
+        pub fn insert(&mut self, ent: &FoundEntry) -> Result<()> {
+            if self.pending == 0 {
+                self.conn.execute_batch("BEGIN")?;
+            }
+
+            self.conn.prepare_cached(INSERT)?.execute(params![
+                ent.path,
+                ent.file_type.name(),
+                i64::try_from(ent.size)?,
+                ent.mode,
+                ent.uid,
+                ent.gid,
+                i64::try_from(ent.mtime)?,
+                ent.security,
+            ])?;
+
+            self.pending += 1;
+
+            if self.pending == BATCH_SIZE {
+                self.flush()?;
+            }
+
+            Ok(())
+        }
+        /// Commits any results not yet written.
+        pub fn flush(&mut self) -> Result<()> {
+            if self.pending > 0 {
+                self.conn.execute_batch("COMMIT")?;
+                self.pending = 0;
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Reads results previously written to a SQLite database
+    /// in the order they were found, fetching a batch of rows
+    /// at a time.
+    pub struct StoredEntries {
+        conn: Connection,
+        last_rowid: i64,
+        buf: VecDeque<(i64, FoundEntry)>,
+        done: bool,
+    }
+
+    impl StoredEntries {
+        pub fn open(path: &Path) -> Result<Self> {
+            let context = || format!("invalid results database '{}'", path.display());
+            let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .with_context(context)?;
+
+            // validates the schema up front rather than on the first read
+            conn.prepare(SELECT).with_context(context)?;
+
+            Ok(Self {
+                conn,
+                last_rowid: 0,
+                buf: VecDeque::new(),
+                done: false,
+            })
+        }
+        fn fetch(&mut self) -> Result<()> {
+            let mut stmt = self.conn.prepare_cached(SELECT)?;
+            let rows = stmt.query_map(params![self.last_rowid, BATCH_SIZE], |r| {
+                Ok((
+                    r.get(0)?,
+                    FoundEntry {
+                        path: r.get(1)?,
+                        file_type: FileKind::from_name(&r.get::<_, String>(2)?),
+                        size: r.get(3)?,
+                        mode: r.get(4)?,
+                        uid: r.get(5)?,
+                        gid: r.get(6)?,
+                        mtime: r.get(7)?,
+                        security: r.get(8)?,
+                    },
+                ))
+            })?;
+
+            for row in rows {
+                self.buf.push_back(row?);
+            }
+
+            self.done = self.buf.len() < BATCH_SIZE;
+
+            Ok(())
+        }
+    }
+
+    const SELECT: &str = "
+        SELECT rowid, path, type, size, mode, uid, gid, mtime, security
+        FROM results WHERE rowid > ?1 ORDER BY rowid LIMIT ?2
+    ";
+
+    impl Iterator for StoredEntries {
+        type Item = Result<FoundEntry>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.buf.is_empty() && !self.done {
+                if let Err(e) = self.fetch() {
+                    self.done = true;
+
+                    return Some(Err(e));
+                }
+            }
+
+            self.buf.pop_front().map(|(rowid, ent)| {
+                self.last_rowid = rowid;
+
+                Ok(ent)
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{ResultDb, StoredEntries};
+        use crate::entry::FileKind;
+        use crate::found::FoundEntry;
+        use anyhow::Result;
+        use rusqlite::Connection;
+
+        #[test]
+        fn insert() -> Result<()> {
+            let dir = tempfile::TempDir::new()?;
+            let path = dir.path().join("results.db");
+            let ent = FoundEntry {
+                path: "./a.txt".to_string(),
+                file_type: FileKind::File,
+                size: 8,
+                mode: 0o644,
+                uid: 1000,
+                gid: 1000,
+                mtime: 1677000000,
+                security: Some("setuid".to_string()),
+            };
+
+            for _ in 0..2 {
+                let mut db = ResultDb::create(&path)?;
+
+                for _ in 0..1001 {
+                    db.insert(&ent)?;
+                }
+
+                db.flush()?;
+            }
+
+            let (count, size): (i64, i64) = Connection::open(&path)?.query_row(
+                "SELECT COUNT(*), MAX(size) FROM results WHERE path = './a.txt'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )?;
+
+            // results of both searches are kept
+            assert_eq!((2002, 8), (count, size));
+
+            let stored = StoredEntries::open(&path)?.collect::<Result<Vec<FoundEntry>>>()?;
+
+            assert_eq!(2002, stored.len());
+            assert!(stored.iter().all(|e| *e == ent));
+
+            Ok(dir.close()?)
+        }
+
+        #[test]
+        fn foreign_table() -> Result<()> {
+            let dir = tempfile::TempDir::new()?;
+            let path = dir.path().join("results.db");
+
+            Connection::open(&path)?.execute_batch("CREATE TABLE results (id INTEGER)")?;
+
+            assert!(ResultDb::create(&path).is_err());
+            assert_eq!(
+                0,
+                Connection::open(&path)?
+                    .query_row("SELECT COUNT(*) FROM results", [], |r| r.get::<_, i64>(0))?
+            );
+
+            Ok(dir.close()?)
+        }
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+mod imp {
+    use super::*;
+
+    /// Stands in for the SQLite writer when findr is built
+    /// without the 'sqlite' feature.
+    pub enum ResultDb {}
+
+    /// Stands in for the SQLite reader when findr is built
+    /// without the 'sqlite' feature.
+    pub enum StoredEntries {}
+
+    impl StoredEntries {
+        pub fn open(_: &Path) -> Result<Self> {
+            anyhow::bail!(
+                "reading results databases requires findr to be built with the 'sqlite' feature"
+            )
+        }
+    }
+
+    impl Iterator for StoredEntries {
+        type Item = Result<FoundEntry>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            match *self {}
+        }
+    }
+
+    impl ResultDb {
+        pub fn create(_: &Path) -> Result<Self> {
+            anyhow::bail!("sqlite output requires findr to be built with the 'sqlite' feature")
+        }
+        pub fn insert(&mut self, _: &FoundEntry) -> Result<()> {
+            match *self {}
+        }
+        pub fn flush(&mut self) -> Result<()> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,61 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::columnar::ResultFile;
+use crate::found::FoundEntry;
+use crate::output::OutputFormat;
+use crate::sink::{OutputSink, SinkEntry};
+use crate::sqlite::ResultDb;
+use anyhow::Result;
+
+/// Structured destinations which record the metadata of
+/// each result instead of writing paths to stdout.
+pub enum ResultStore {
+    Sqlite(ResultDb),
+    Parquet(Box<ResultFile>),
+}
+
+impl ResultStore {
+    /// Opens the store for the given output format if
+    /// it is not written to stdout.
+    pub fn open(format: &OutputFormat) -> Result<Option<Self>> {
+        Ok(match format {
+            OutputFormat::Sqlite(path) => Some(Self::Sqlite(ResultDb::create(path)?)),
+            OutputFormat::Parquet(path) => Some(Self::Parquet(Box::new(ResultFile::create(path)?))),
+            _ => None,
+        })
+    }
+    pub fn insert(&mut self, ent: &FoundEntry) -> Result<()> {
+        match self {
+            Self::Sqlite(db) => db.insert(ent),
+            Self::Parquet(f) => f.insert(ent),
+        }
+    }
+    pub fn flush(&mut self) -> Result<()> {
+        match self {
+            Self::Sqlite(db) => db.flush(),
+            Self::Parquet(f) => f.flush(),
+        }
+    }
+}
+
+impl OutputSink for ResultStore {
+    fn needs_metadata(&self) -> bool {
+        true
+    }
+    fn needs_security(&self) -> bool {
+        true
+    }
+    /// Results without metadata are not recorded.
+    fn write_entry(&mut self, ent: &SinkEntry) -> Result<bool> {
+        if let Some(found) = ent.metadata {
+            self.insert(found)?;
+        }
+
+        Ok(true)
+    }
+    fn finish(&mut self, _omitted: usize) -> Result<()> {
+        self.flush()
+    }
+}
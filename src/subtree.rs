@@ -0,0 +1,83 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+use walkdir::DirEntry;
+
+/// Propagates modification times from entries to their
+/// ancestors. Entries must be observed in post-order,
+/// i.e. with the contents of a directory preceding it.
+#[derive(Default)]
+pub struct SubtreeTracker {
+    newest: HashMap<PathBuf, u64>,
+}
+
+impl SubtreeTracker {
+    /// Records the given entry and returns the newest
+    /// modification time within its subtree if it is
+    /// a directory.
+    pub fn observe(&mut self, ent: &DirEntry) -> Option<u64> {
+        let mtime: u64 = ent
+            .metadata()
+            .ok()
+            .and_then(|m| m.mtime().try_into().ok())
+            .unwrap_or_default();
+        let is_dir = ent.file_type().is_dir();
+        let newest = if is_dir {
+            mtime.max(self.newest.remove(ent.path()).unwrap_or_default())
+        } else {
+            mtime
+        };
+
+        if let Some(parent) = ent.path().parent().filter(|_| ent.depth() > 0) {
+            let parent_newest = self.newest.entry(parent.to_path_buf()).or_default();
+
+            *parent_newest = newest.max(*parent_newest);
+        }
+
+        is_dir.then_some(newest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SubtreeTracker;
+    use anyhow::Result;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use walkdir::WalkDir;
+
+    #[test]
+    fn observe_propagates_newest_mtime() -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let root = temp.path();
+        let old = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+
+        // root/a/b/c.txt is old while root/d.txt is new
+        fs::create_dir_all(root.join("a").join("b"))?;
+        fs::File::create(root.join("a").join("b").join("c.txt"))?.set_modified(old)?;
+        fs::File::create(root.join("d.txt"))?;
+
+        for dir in [root.join("a").join("b"), root.join("a")] {
+            fs::File::open(dir)?.set_modified(old)?;
+        }
+
+        let mut tracker = SubtreeTracker::default();
+        let newest = WalkDir::new(root)
+            .contents_first(true)
+            .into_iter()
+            .map(|r| r.map(|e| (e.path().to_path_buf(), tracker.observe(&e))))
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let old_secs = old.duration_since(UNIX_EPOCH)?.as_secs();
+
+        assert_eq!(None, newest[&root.join("d.txt")]);
+        assert_eq!(Some(old_secs), newest[&root.join("a")]);
+        assert!(newest[root] > Some(old_secs));
+
+        Ok(temp.close()?)
+    }
+}
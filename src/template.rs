@@ -0,0 +1,125 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::output::shell_quote;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Path,
+    NoExtension,
+    Basename,
+    BasenameNoExtension,
+    Parent,
+}
+
+// Longer placeholders must come first so that e.g. '{//}'
+// is not mistaken for '{/}'.
+const PLACEHOLDERS: [(&str, Token); 5] = [
+    ("{//}", Token::Parent),
+    ("{/.}", Token::BasenameNoExtension),
+    ("{/}", Token::Basename),
+    ("{.}", Token::NoExtension),
+    ("{}", Token::Path),
+];
+
+/// Shell command rendered once per result. Placeholders
+/// are replaced with the quoted path of the result.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandTemplate {
+    tokens: Vec<Token>,
+}
+
+impl CommandTemplate {
+    pub fn render(&self, path: &str) -> String {
+        let path = Path::new(path);
+
+        self.tokens
+            .iter()
+            .map(|t| match t {
+                Token::Text(s) => s.clone(),
+                Token::Path => quote(path),
+                Token::NoExtension => quote(&path.with_extension("")),
+                Token::Basename => quote(Path::new(path.file_name().unwrap_or_default())),
+                Token::BasenameNoExtension => {
+                    quote(Path::new(path.file_stem().unwrap_or_default()))
+                }
+                Token::Parent => match path.parent() {
+                    Some(p) if !p.as_os_str().is_empty() => quote(p),
+                    _ => quote(Path::new(".")),
+                },
+            })
+            .collect()
+    }
+}
+
+impl FromStr for CommandTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            anyhow::bail!("command template must not be empty");
+        }
+
+        let mut tokens = Vec::new();
+        let mut text = String::new();
+        let mut rest = s;
+
+        'outer: while let Some(c) = rest.chars().next() {
+            for (p, t) in PLACEHOLDERS.iter() {
+                if let Some(r) = rest.strip_prefix(p) {
+                    if !text.is_empty() {
+                        tokens.push(Token::Text(std::mem::take(&mut text)));
+                    }
+
+                    tokens.push(t.clone());
+                    rest = r;
+
+                    continue 'outer;
+                }
+            }
+
+            text.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+
+        if !text.is_empty() {
+            tokens.push(Token::Text(text));
+        }
+        if tokens.iter().all(|t| matches!(t, Token::Text(_))) {
+            tokens.extend([Token::Text(" ".to_string()), Token::Path]);
+        }
+
+        Ok(Self { tokens })
+    }
+}
+
+fn quote(p: &Path) -> String {
+    shell_quote(&p.to_string_lossy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommandTemplate;
+    use test_case::test_case;
+
+    #[test_case("mv {} {.}.bak", "./one/b.md", r"mv './one/b.md' './one/b'.bak" ; "path and no extension")]
+    #[test_case("echo {/} {/.}", "./one/b.md", r"echo 'b.md' 'b'" ; "basename")]
+    #[test_case("cd {//}", "./one/b.md", r"cd './one'" ; "parent")]
+    #[test_case("cd {//}", "b.md", r"cd '.'" ; "parent of relative file")]
+    #[test_case("rm", "./it's.txt", r"rm './it'\''s.txt'" ; "implicit path")]
+    #[test_case("echo {x}", "./a", r"echo {x} './a'" ; "unknown placeholder is literal")]
+    fn render(template: &str, path: &str, expected: &str) {
+        assert_eq!(
+            expected,
+            template.parse::<CommandTemplate>().unwrap().render(path)
+        )
+    }
+    #[test]
+    fn from_str_empty() {
+        assert!(" ".parse::<CommandTemplate>().is_err())
+    }
+}
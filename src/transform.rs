@@ -0,0 +1,168 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::escape::url_encode;
+use regex::Regex;
+use std::str::FromStr;
+
+/// Transformation applied to each path before it is output.
+#[derive(Clone, Debug)]
+pub enum PathTransform {
+    Substitute {
+        pattern: Regex,
+        replacement: String,
+        global: bool,
+    },
+    Prepend(String),
+    Append(String),
+    UrlEncode,
+}
+
+impl PathTransform {
+    pub fn apply(&self, path: &str) -> String {
+        match self {
+            Self::Substitute {
+                pattern,
+                replacement,
+                global: true,
+            } => pattern.replace_all(path, replacement.as_str()).into_owned(),
+            Self::Substitute {
+                pattern,
+                replacement,
+                global: false,
+            } => pattern.replace(path, replacement.as_str()).into_owned(),
+            Self::Prepend(s) => format!("{s}{path}"),
+            Self::Append(s) => format!("{path}{s}"),
+            Self::UrlEncode => url_encode(path),
+        }
+    }
+}
+
+impl FromStr for PathTransform {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if let Some(prefix) = s.strip_prefix("prepend:") {
+            Self::Prepend(prefix.to_string())
+        } else if let Some(suffix) = s.strip_prefix("append:") {
+            Self::Append(suffix.to_string())
+        } else if s == "urlencode" {
+            Self::UrlEncode
+        } else if let Some(expr) = s.strip_prefix('s') {
+            parse_substitution(expr)?
+        } else {
+            anyhow::bail!("unknown transformation '{}'", s)
+        })
+    }
+}
+
+// Parses the remainder of a sed 's<d>pattern<d>replacement<d>[g]'
+// expression where <d> is any delimiter character which may
+// appear escaped as '\<d>' in the pattern and replacement.
+fn parse_substitution(expr: &str) -> anyhow::Result<PathTransform> {
+    let invalid = || anyhow::anyhow!("invalid substitution 's{}'", expr);
+    let mut chars = expr.chars();
+    let delim = chars
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("missing delimiter in substitution"))?;
+    let mut parts = vec![String::new()];
+
+    while let Some(c) = chars.next() {
+        let in_pattern = parts.len() == 1;
+        let part = parts.last_mut().expect("parts is never empty");
+
+        match c {
+            '\\' => match chars.next() {
+                // only the pattern is a regular expression
+                Some(c) if c == delim && in_pattern => {
+                    part.push_str(&regex::escape(c.encode_utf8(&mut [0; 4])))
+                }
+                Some(c) => {
+                    part.push('\\');
+                    part.push(c);
+                }
+                None => return Err(invalid()),
+            },
+            c if c == delim => parts.push(String::new()),
+            c => part.push(c),
+        }
+    }
+
+    match &parts[..] {
+        [pattern, replacement, flags] if flags.is_empty() || flags == "g" => {
+            let pattern = Regex::new(pattern)?;
+            let replacement = parse_replacement(replacement, pattern.captures_len() - 1)?;
+
+            Ok(PathTransform::Substitute {
+                pattern,
+                replacement,
+                global: flags == "g",
+            })
+        }
+        _ => Err(invalid()),
+    }
+}
+
+// Converts a sed replacement where '\1' to '\9' refer to
+// groups and '&' to the whole match into the syntax of
+// 'Regex::replace'.
+fn parse_replacement(replacement: &str, groups: usize) -> anyhow::Result<String> {
+    let mut out = String::new();
+    let mut chars = replacement.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(d @ '1'..='9') => {
+                    let group = d as usize - '0' as usize;
+
+                    if group > groups {
+                        anyhow::bail!("invalid reference \\{} in substitution", d);
+                    }
+
+                    out.push_str(&format!("${{{group}}}"))
+                }
+                Some('n') => out.push('\n'),
+                Some('$') => out.push_str("$$"),
+                Some(c) => out.push(c),
+                None => out.push('\\'),
+            },
+            '&' => out.push_str("${0}"),
+            '$' => out.push_str("$$"),
+            c => out.push(c),
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PathTransform;
+    use test_case::test_case;
+
+    #[test_case("s#^/srv/www/##", "/srv/www/a/b.html", Some("a/b.html") ; "strip prefix")]
+    #[test_case("s/a/x/", "./a/a.txt", Some("./x/a.txt") ; "substitute first")]
+    #[test_case("s/a/x/g", "./a/a.txt", Some("./x/x.txt") ; "substitute all")]
+    #[test_case(r"s|\.(\w+)$|.\1.bak|", "./a.txt", Some("./a.txt.bak") ; "substitute with capture group")]
+    #[test_case("s/[0-9]+/<&>/g", "./1/22.txt", Some("./<1>/<22>.txt") ; "substitute with whole match")]
+    #[test_case(r"s/a/\&$1/", "a", Some("&$1") ; "substitute with literals")]
+    #[test_case(r"s/\/srv\//\/www\//", "/srv/a", Some("/www/a") ; "escaped delimiter")]
+    #[test_case(r"s|a\|b|c|g", "a|b", Some("c") ; "escaped regex delimiter")]
+    #[test_case(r"s/(a)/\2/", "a", None ; "reference to missing group")]
+    #[test_case(r"s/a/b\", "a", None ; "trailing backslash")]
+    #[test_case("prepend:https://cdn.example.com/", "a.txt", Some("https://cdn.example.com/a.txt") ; "prepend")]
+    #[test_case("append:.bak", "a.txt", Some("a.txt.bak") ; "append")]
+    #[test_case("urlencode", "./a b.txt", Some("./a%20b.txt") ; "url encode")]
+    #[test_case("s/a/x", "a", None ; "unterminated substitution")]
+    #[test_case("s/a/x/q", "a", None ; "unknown flag")]
+    #[test_case("s/[/x/", "a", None ; "invalid regex")]
+    #[test_case("s", "a", None ; "missing delimiter")]
+    #[test_case("upper", "a", None ; "unknown transformation")]
+    fn apply(s: &str, path: &str, expected: Option<&str>) {
+        let result = s.parse::<PathTransform>().ok().map(|t| t.apply(path));
+
+        assert_eq!(expected.map(String::from), result)
+    }
+}
@@ -0,0 +1,152 @@
+// SPDX-FileCopyrightText: 2023 Andrew Pantuso <ajpantuso@gmail.com>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::{self, Write};
+
+const TARGET: &str = env!("FINDR_BUILD_TARGET");
+const PROFILE: &str = env!("FINDR_BUILD_PROFILE");
+const FEATURES: &str = env!("FINDR_BUILD_FEATURES");
+
+/// Writes the version and, when verbose, the build
+/// information and platform capabilities of the binary.
+pub fn write_version(out: &mut impl Write, verbose: bool) -> io::Result<()> {
+    writeln!(out, "{} {}", clap::crate_name!(), clap::crate_version!())?;
+
+    if !verbose {
+        return Ok(());
+    }
+
+    let features = if FEATURES.is_empty() {
+        "none"
+    } else {
+        FEATURES
+    };
+
+    writeln!(out, "target: {TARGET}")?;
+    writeln!(out, "profile: {PROFILE}")?;
+    writeln!(out, "features: {features}")?;
+
+    for (name, available) in Capabilities::probe().iter() {
+        let status = match available {
+            Some(true) => "available",
+            Some(false) => "unavailable",
+            None => "unknown",
+        };
+
+        writeln!(out, "{name}: {status}")?;
+    }
+
+    Ok(())
+}
+
+/// Kernel interfaces available to the running binary.
+/// Capabilities which cannot be probed on the current
+/// platform are reported as unknown.
+#[derive(Debug)]
+struct Capabilities {
+    statx: Option<bool>,
+    io_uring: Option<bool>,
+}
+
+impl Capabilities {
+    fn probe() -> Self {
+        Self {
+            statx: probe::statx(),
+            io_uring: probe::io_uring(),
+        }
+    }
+    fn iter(&self) -> impl Iterator<Item = (&'static str, Option<bool>)> {
+        [("statx", self.statx), ("io_uring", self.io_uring)].into_iter()
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+mod probe {
+    use std::io;
+    use std::ptr;
+
+    // Each probe issues the syscall with arguments the kernel
+    // rejects before doing any work. The syscall is available
+    // unless it is unimplemented or blocked, e.g. by seccomp.
+    pub fn statx() -> Option<bool> {
+        // SAFETY: a NULL path is rejected with EFAULT.
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_statx,
+                libc::AT_FDCWD,
+                ptr::null::<libc::c_char>(),
+                0,
+                0,
+                ptr::null_mut::<libc::statx>(),
+            )
+        };
+
+        Some(available(ret))
+    }
+    pub fn io_uring() -> Option<bool> {
+        // SAFETY: zero entries are rejected with EINVAL.
+        let ret =
+            unsafe { libc::syscall(libc::SYS_io_uring_setup, 0, ptr::null_mut::<libc::c_void>()) };
+
+        Some(available(ret))
+    }
+    fn available(ret: libc::c_long) -> bool {
+        ret >= 0
+            || !matches!(
+                io::Error::last_os_error().raw_os_error(),
+                Some(libc::ENOSYS | libc::EPERM)
+            )
+    }
+}
+
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+mod probe {
+    pub fn statx() -> Option<bool> {
+        None
+    }
+    pub fn io_uring() -> Option<bool> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_version;
+    use anyhow::Result;
+
+    #[test]
+    fn version() -> Result<()> {
+        let mut out = Vec::new();
+
+        write_version(&mut out, false)?;
+
+        assert_eq!(
+            format!("findr {}\n", env!("CARGO_PKG_VERSION")),
+            String::from_utf8(out)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn verbose_version() -> Result<()> {
+        let mut out = Vec::new();
+
+        write_version(&mut out, true)?;
+
+        let out = String::from_utf8(out)?;
+        let keys = out
+            .lines()
+            .skip(1)
+            .filter_map(|l| l.split_once(": ").map(|(k, _)| k))
+            .collect::<Vec<&str>>();
+
+        assert_eq!(
+            vec!["target", "profile", "features", "statx", "io_uring"],
+            keys
+        );
+
+        Ok(())
+    }
+}
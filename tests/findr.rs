@@ -10,18 +10,78 @@ mod findr {
     use std::collections::HashMap;
     use std::fs;
     use std::io::Write;
-    use std::os::unix::fs::OpenOptionsExt;
-    use tempfile;
+    use std::os::unix::fs::{MetadataExt, OpenOptionsExt, PermissionsExt};
+    use std::time::{Duration, SystemTime};
     use test_case::test_case;
 
     #[test_case(&[r"--pattern=\.md$"], &["./one/b.md"] ; ".md files")]
+    #[test_case(&["--glob=*.txt"], &["./a.txt", "./one/two/c.txt", "./three/d.txt"] ; "glob on file names")]
+    #[test_case(&["--ignore-case", r"--pattern=\.MD$"], &["./one/b.md"] ; "case insensitive pattern")]
+    #[test_case(&["-i", "--glob=A.*"], &["./a.txt"] ; "case insensitive glob")]
+    #[test_case(&["--smart-case", r"--pattern=\.MD$"], &[] ; "smart case pattern with uppercase")]
+    #[test_case(&["-S", "--glob=A.*"], &[] ; "smart case glob with uppercase")]
+    #[test_case(&["--ext=md", "-e", ".txt"], &["./a.txt", "./one/b.md", "./one/two/c.txt", "./three/d.txt"] ; "any of several extensions")]
+    #[test_case(&["--lname=/one/a\\.txt$"], &["./three/d.txt"] ; "symlink target")]
+    #[test_case(&["--lname=^./a"], &[] ; "symlink target only")]
+    #[test_case(&["--contains=^# Head"], &["./one/b.md"] ; "file contents")]
+    #[test_case(&["--contains=."], &["./one/b.md"] ; "non-empty files only")]
+    #[test_case(&["--hash=md5=d41d8cd98f00b204e9800998ecf8427e"], &["./a.txt", "./one/two/c.txt"] ; "empty file digest")]
+    #[test_case(&["--hash=sha256=e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855", "--hash=md5=3ba8e8a5e5bc7fd2e4c1a4e3dbf3b9f8"], &["./a.txt", "./one/two/c.txt"] ; "any digest")]
+    #[test_case(&["--nouser"], &[] ; "owner without user")]
+    #[test_case(&["--nogroup"], &[] ; "owner without group")]
+    #[test_case(&["--executable", "--type=f"], &["./a.txt"] ; "executable by user")]
+    #[test_case(&["--readable", "--type=f"], &["./a.txt", "./one/b.md", "./one/two/c.txt"] ; "readable by user")]
+    #[test_case(&["--has-caps"], &[] ; "carrying capabilities")]
+    #[test_case(&["--caps=cap_net_raw+ep"], &[] ; "carrying capability")]
+    #[test_case(&["--attr=i"], &[] ; "immutable")]
+    #[test_case(&["--type=mount-point"], &[] ; "mount points")]
+    #[test_case(&["--project-id=4242"], &[] ; "project quota id")]
+    #[test_case(&["--entries=+1"], &[".", "./one"] ; "directories with several entries")]
+    #[test_case(&["--entries=1..1"], &["./one/two", "./three"] ; "directories with one entry")]
+    #[test_case(&["--has-acl"], &[] ; "carrying ACLs")]
+    #[test_case(&["--no-acl", "--type=f"], &["./a.txt", "./one/b.md", "./one/two/c.txt"] ; "without ACLs")]
+    #[test_case(&["--in-dir=two"], &["./one/two/c.txt"] ; "beneath directory")]
+    #[test_case(&["--in-dir=one", "--in-dir=three"], &["./one/b.md", "./one/two", "./one/two/c.txt", "./three/d.txt"] ; "beneath any of several directories")]
+    #[test_case(&["--mtime=-2000-01-01", "--type=f"], &["./a.txt", "./one/b.md", "./one/two/c.txt"] ; "modified after date")]
+    #[test_case(&["--mtime=+2000-01-01T00:00:00Z"], &[] ; "modified before timestamp")]
+    #[test_case(&["--mtime=2000-01-01..1d", "--type=f"], &[] ; "modified outside of range")]
+    #[test_case(&["--mtime=0s..1h", "--type=f"], &["./a.txt", "./one/b.md", "./one/two/c.txt"] ; "modified within recent range")]
+    #[test_case(&["--size=1..8", "--type=f"], &["./one/b.md"] ; "size range")]
+    #[test_case(&["--match-on=basename", "--pattern=^t"], &["./one/two", "./three"] ; "pattern on basename")]
+    #[test_case(&["--pattern=^t"], &[] ; "pattern on path")]
+    #[test_case(&["--relative-match", "--pattern=^one/"], &["./one/b.md", "./one/two", "./one/two/c.txt"] ; "pattern relative to root")]
+    #[test_case(&["--relative-match", "--pattern=^one/", "one"], &[] ; "pattern relative to nested root")]
+    #[test_case(&["--fixed-strings", "--pattern=b.md"], &["./one/b.md"] ; "fixed string")]
+    #[test_case(&["-F", "--pattern=."], &[".", "./a.txt", "./one", "./one/b.md", "./one/two", "./one/two/c.txt", "./three", "./three/d.txt"] ; "fixed string dot")]
+    #[test_case(&["-F", "--pattern=(", "--type=f"], &[] ; "fixed string with regex metacharacter")]
+    #[test_case(&[r"--pattern=\.md$", "-p", "^./a"], &["./a.txt", "./one/b.md"] ; "any of several patterns")]
+    #[test_case(&["--exclude=/one$"], &[".", "./a.txt", "./three", "./three/d.txt"] ; "excluded directory is pruned")]
+    #[test_case(&["--exclude=/one$", r"--exclude=\.txt$"], &[".", "./three"] ; "any of several excludes")]
+    #[test_case(&[r"--exclude=\."], &["."] ; "root is never excluded")]
+    #[test_case(&["-g", "./one/**"], &["./one/b.md", "./one/two", "./one/two/c.txt"] ; "glob on paths")]
+    #[test_case(&["-g", "./one/*"], &["./one/b.md", "./one/two"] ; "glob on paths without nested entries")]
     #[test_case(&["--type=d"], &[".", "./one", "./one/two", "./three"] ; "directories")]
     #[test_case(&["--type=x"], &["./a.txt"] ; "executables")]
     #[test_case(&["--type=l"], &["./three/d.txt"] ; "symlink")]
+    #[test_case(&["--type=text"], &["./a.txt", "./one/b.md", "./one/two/c.txt"] ; "text files")]
+    #[test_case(&["--type=binary"], &[] ; "binary files")]
+    #[test_case(&["--type=!d"], &["./a.txt", "./one/b.md", "./one/two/c.txt", "./three/d.txt"] ; "not directories")]
+    #[test_case(&["--type=f,l"], &["./a.txt", "./one/b.md", "./one/two/c.txt", "./three/d.txt"] ; "files or symlinks")]
+    #[test_case(&["--type=f,!x"], &["./one/b.md", "./one/two/c.txt"] ; "non-executable files")]
+    #[test_case(&["--type=non-empty-file"], &["./one/b.md"] ; "non-empty files")]
+    #[test_case(&["--type=non-empty-dir"], &[".", "./one", "./one/two", "./three"] ; "non-empty directories")]
+    #[test_case(&["--empty"], &["./a.txt", "./one/two/c.txt"] ; "empty files and directories")]
+    #[test_case(&["--empty", "--type=x"], &["./a.txt"] ; "empty executables")]
+    #[test_case(&["--type=empty"], &["./a.txt", "./one/two/c.txt"] ; "empty type")]
     #[test_case(&["--mode=444"], &["./one/two/c.txt"] ; "readonly")]
+    #[test_case(&["--mode=a=r"], &["./one/two/c.txt"] ; "readonly symbolic")]
+    #[test_case(&["--mode=u+x", "--type=f"], &["./a.txt"] ; "symbolic owner executable")]
     #[test_case(&["--size=0"], &["./a.txt", "./one/two/c.txt"] ; "size equals 0")]
     #[test_case(&["--type=f", "--type=l", "--size=+0"], &["./one/b.md", "./three/d.txt"] ; "files/symlinks with size greater than 0")]
     #[test_case(&["--size=8"], &["./one/b.md"] ; "size equals 8")]
+    #[test_case(&["--one-file-system", "--type=f"], &["./a.txt", "./one/b.md", "./one/two/c.txt"] ; "one file system")]
+    #[test_case(&["--since=boot", "--type=f"], &["./a.txt", "./one/b.md", "./one/two/c.txt"] ; "files modified since boot")]
+    #[test_case(&["--estimate=100%", "--type=f"], &["estimated matches: 3 ± 0 (95% confidence)", "estimated size: 8 ± 0 bytes (95% confidence)", "sampled 2 of 2 directories"] ; "estimate with every directory sampled")]
     #[test_case(&["--max-depth=1"], &[".", "./a.txt", "./one", "./three"] ; "max-depth equals 0")]
     #[test_case(&["--min-depth=3"], &["./one/two/c.txt"] ; "min-depth equals 3")]
     fn valid(args: &[&str], expected: &[&str]) -> Result<()> {
@@ -43,6 +103,1073 @@ mod findr {
         Ok(dir.close()?)
     }
 
+    #[test_case(&[], &[".", "./cache", "./cache/CACHEDIR.TAG", "./cache/e.bin", "./cache/nested", "./cache/nested/f.bin"] ; "caches included by default")]
+    #[test_case(&["--exclude-caches"], &[".", "./cache", "./cache/CACHEDIR.TAG"] ; "caches excluded")]
+    fn exclude_caches(args: &[&str], expected: &[&str]) -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let cache = temp.path().join("cache");
+
+        // root/cache/nested
+        fs::create_dir_all(cache.join("nested"))?;
+
+        // root/cache/CACHEDIR.TAG
+        fs::write(
+            cache.join("CACHEDIR.TAG"),
+            "Signature: 8a477f597d28d172789f06886806bc55",
+        )?;
+
+        // root/cache/e.bin
+        fs::File::create(cache.join("e.bin"))?;
+
+        // root/cache/nested/f.bin
+        fs::File::create(cache.join("nested").join("f.bin"))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(temp.path())
+            .args(args)
+            .assert()
+            .stdout(predicate::function(|out: &str| {
+                let stdout_lines = out.lines().collect::<Vec<&str>>();
+
+                count_lines(&stdout_lines) == count_lines(expected)
+            }))
+            .success();
+
+        Ok(temp.close()?)
+    }
+
+    #[test_case(&["--type=l", "--target-size=+5"], &["./link"] ; "links to files larger than 5 bytes")]
+    #[test_case(&["--type=l", "--target-size=-5"], &[] ; "links to files smaller than 5 bytes")]
+    #[test_case(&["--target-size=10"], &["./big.bin", "./link"] ; "links and files of 10 bytes")]
+    #[test_case(&["--type=l", "--target-mtime=-1d"], &["./link"] ; "links to recently modified files")]
+    fn target_filters(args: &[&str], expected: &[&str]) -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+
+        // root/big.bin
+        fs::write(temp.path().join("big.bin"), "0123456789")?;
+
+        // root/link
+        std::os::unix::fs::symlink("big.bin", temp.path().join("link"))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(temp.path())
+            .args(args)
+            .assert()
+            .stdout(predicate::function(|out: &str| {
+                let stdout_lines = out.lines().collect::<Vec<&str>>();
+
+                count_lines(&stdout_lines) == count_lines(expected)
+            }))
+            .success();
+
+        Ok(temp.close()?)
+    }
+
+    #[test_case(&["--duplicates"], "./big/a.bin\n./big/b.bin\n\n./a.txt\n./c.txt\n" ; "plain")]
+    #[test_case(&["--duplicates=json"], concat!(
+        r#"{"size":5,"blake3":"df19b1f105ff929191ce49d0bbfdc5b4edc2a71a40f502dc955359eb33649e24","paths":["./big/a.bin","./big/b.bin"]}"#,
+        "\n",
+        r#"{"size":0,"blake3":"af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262","paths":["./a.txt","./c.txt"]}"#,
+        "\n",
+    ) ; "json")]
+    #[test_case(&["--duplicates", "--max-output=24"], "./big/a.bin\n./big/b.bin\n… truncated (3 more matches)\n" ; "max output")]
+    #[test_case(&["--duplicates", "--map=s#^\\./##", "--escape=json"], "\"big/a.bin\"\n\"big/b.bin\"\n\n\"a.txt\"\n\"c.txt\"\n" ; "mapped and escaped")]
+    fn duplicates(args: &[&str], expected: &'static str) -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+
+        fs::create_dir(temp.path().join("big"))?;
+        fs::write(temp.path().join("big").join("a.bin"), "bytes")?;
+        fs::write(temp.path().join("big").join("b.bin"), "bytes")?;
+        fs::write(temp.path().join("big").join("c.bin"), "other")?;
+        fs::hard_link(
+            temp.path().join("big").join("b.bin"),
+            temp.path().join("big").join("d.bin"),
+        )?;
+        fs::write(temp.path().join("a.txt"), "")?;
+        fs::write(temp.path().join("c.txt"), "")?;
+        fs::write(temp.path().join("d.txt"), "unique")?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(temp.path())
+            .args(args)
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(temp.close()?)
+    }
+
+    #[test_case("--mtime-days=1", "./b.txt\n" ; "between one and two days")]
+    #[test_case("--mtime-days=+1", "./c.txt\n" ; "more than one day")]
+    #[test_case("--mtime-days=-1", "./a.txt\n" ; "less than one day")]
+    #[test_case("--mtime=1d", "./b.txt\n./c.txt\n" ; "strict duration")]
+    fn mtime_days(filter: &str, expected: &'static str) -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(60 * 60);
+
+        fs::File::create(temp.path().join("a.txt"))?.set_modified(now - 12 * hour)?;
+        fs::File::create(temp.path().join("b.txt"))?.set_modified(now - 36 * hour)?;
+        fs::File::create(temp.path().join("c.txt"))?.set_modified(now - 49 * hour)?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(temp.path())
+            .args([filter, "--type=f", "--visit-order=name"])
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(temp.close()?)
+    }
+
+    #[test_case("--newer", "./new/b.txt\n" ; "newer")]
+    #[test_case("--older", "./old/a.txt\n" ; "older")]
+    fn reference_file(flag: &str, expected: &'static str) -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let now = SystemTime::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        fs::create_dir(temp.path().join("old"))?;
+        fs::File::create(temp.path().join("old").join("a.txt"))?.set_modified(now - 2 * day)?;
+        fs::File::create(temp.path().join("reference"))?.set_modified(now - day)?;
+        fs::create_dir(temp.path().join("new"))?;
+        fs::File::create(temp.path().join("new").join("b.txt"))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(temp.path())
+            .arg(format!("{flag}=reference"))
+            .arg("--type=f")
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(temp.close()?)
+    }
+
+    #[test_case(&["--hash=md5=fae11b18928bb9cf0c36d8d7135f29cc"], "" ; "hash beyond default limit")]
+    #[test_case(&["--hash=md5=fae11b18928bb9cf0c36d8d7135f29cc", "--max-read=1MiB"], "./big.txt\n" ; "hash within limit")]
+    #[test_case(&["--hash=md5=fae11b18928bb9cf0c36d8d7135f29cc", "--full-content"], "./big.txt\n" ; "hash full content")]
+    #[test_case(&["--contains=needle"], "" ; "contents beyond default limit")]
+    #[test_case(&["--contains=needle", "--full-content"], "./big.txt\n" ; "contents full content")]
+    #[test_case(&["--type=binary", "--max-read=4"], "" ; "binary beyond limit")]
+    #[test_case(&["--type=binary"], "./big.txt\n" ; "binary within limit")]
+    fn max_read(args: &[&str], expected: &str) -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let mut contents = vec![b'x'; 128 * 1024];
+
+        contents[8] = 0;
+        contents.extend_from_slice(b"\nneedle\n");
+        fs::write(temp.path().join("big.txt"), &contents)?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(temp.path())
+            .args(args)
+            .assert()
+            .stdout(expected.to_string())
+            .success();
+
+        Ok(temp.close()?)
+    }
+
+    #[test_case("--type=empty-dir", "./empty\n" ; "empty directories")]
+    #[test_case("--type=non-empty-dir", ".\n./full\n" ; "non-empty directories")]
+    #[test_case("--type=empty", "./empty\n./full/a.txt\n" ; "empty files and directories")]
+    fn empty_dirs(filter: &str, expected: &str) -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+
+        fs::create_dir(temp.path().join("empty"))?;
+        fs::create_dir(temp.path().join("full"))?;
+        fs::write(temp.path().join("full").join("a.txt"), "")?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(temp.path())
+            .args([filter, "--visit-order=name"])
+            .assert()
+            .stdout(expected.to_string())
+            .success();
+
+        Ok(temp.close()?)
+    }
+
+    #[test_case(&["--readable", "--type=f"], "./caf\u{fffd}\n" ; "readable")]
+    #[test_case(&["--type=text"], "./caf\u{fffd}\n" ; "text")]
+    #[test_case(&["--type=empty-dir"], "./dir\u{fffd}\n" ; "empty directory")]
+    fn non_utf8_names(args: &[&str], expected: &str) -> Result<()> {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp = tempfile::TempDir::new()?;
+
+        fs::write(temp.path().join(OsStr::from_bytes(b"caf\xe9")), "text")?;
+        fs::create_dir(temp.path().join(OsStr::from_bytes(b"dir\xe9")))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(temp.path())
+            .args(args)
+            .assert()
+            .stdout(expected.to_string())
+            .success();
+
+        Ok(temp.close()?)
+    }
+
+    #[test_case(&[], "./release/b.txt\n" ; "newer than parent")]
+    #[test_case(&["release/b.txt"], "" ; "root never matches")]
+    fn newer_than_parent(roots: &[&str], expected: &str) -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let old = SystemTime::now() - Duration::from_secs(24 * 60 * 60);
+
+        fs::create_dir(temp.path().join("release"))?;
+        fs::File::create(temp.path().join("release").join("a.txt"))?.set_modified(old)?;
+        fs::File::create(temp.path().join("release").join("b.txt"))?;
+        fs::File::open(temp.path().join("release"))?.set_modified(old)?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(temp.path())
+            .arg("--newer-than-parent")
+            .args(roots)
+            .assert()
+            .stdout(expected.to_string())
+            .success();
+
+        Ok(temp.close()?)
+    }
+
+    #[test_case(&["--subtree-modified-within=7d"], &[".", "./new"] ; "subtrees modified within 7 days")]
+    #[test_case(&["--subtree-modified-within=60d"], &[".", "./new", "./old"] ; "subtrees modified within 60 days")]
+    fn subtree_modified_within(args: &[&str], expected: &[&str]) -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let old = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+
+        // root/old/a.txt
+        fs::create_dir(temp.path().join("old"))?;
+        fs::File::create(temp.path().join("old").join("a.txt"))?.set_modified(old)?;
+        fs::File::open(temp.path().join("old"))?.set_modified(old)?;
+
+        // root/new/b.txt
+        fs::create_dir(temp.path().join("new"))?;
+        fs::File::create(temp.path().join("new").join("b.txt"))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(temp.path())
+            .args(args)
+            .assert()
+            .stdout(predicate::function(|out: &str| {
+                let stdout_lines = out.lines().collect::<Vec<&str>>();
+
+                count_lines(&stdout_lines) == count_lines(expected)
+            }))
+            .success();
+
+        Ok(temp.close()?)
+    }
+
+    #[test_case(&["--type=f"], &["./one/b.md"] ; "orphaned files")]
+    #[test_case(&["--type=f", "--missing"], &["gone.txt"] ; "missing files")]
+    fn reference(args: &[&str], expected: &[&str]) -> Result<()> {
+        let dir = setup_root_dir()?;
+        let mut manifest = tempfile::NamedTempFile::new()?;
+
+        // lists one file which does not exist
+        write!(manifest, "a.txt\n./one/two/c.txt\ngone.txt\n")?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .arg("--reference")
+            .arg(manifest.path())
+            .args(args)
+            .assert()
+            .stdout(predicate::function(|out: &str| {
+                let stdout_lines = out.lines().collect::<Vec<&str>>();
+
+                count_lines(&stdout_lines) == count_lines(expected)
+            }))
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case(&["orphans", "-0", "--type=f"], "./new\u{fffd}\n" ; "orphaned files")]
+    #[test_case(&["orphans", "-0", "--type=f", "--missing"], "gone\nline\nbreak\n" ; "missing files")]
+    #[test_case(&["-0", "--type=f"], "./new\u{fffd}\n" ; "without subcommand")]
+    fn orphans(args: &[&str], expected: &str) -> Result<()> {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp = tempfile::TempDir::new()?;
+
+        fs::write(temp.path().join(OsStr::from_bytes(b"caf\xe9")), "")?;
+        fs::write(temp.path().join(OsStr::from_bytes(b"new\xe9")), "")?;
+        // lists a name which is not valid UTF-8 and one
+        // containing a newline which does not exist
+        fs::write(
+            temp.path().join("manifest"),
+            b"./caf\xe9\0manifest\0gone\nline\nbreak\0",
+        )?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(temp.path())
+            .args(args)
+            .arg("--reference=manifest")
+            .assert()
+            .stdout(expected.to_string())
+            .success();
+
+        Ok(temp.close()?)
+    }
+
+    #[test_case(&["--expect=mode=444"], &["./a.txt", "./one/b.md"], "audited 3 entries, 2 deviating (mode: 2)" ; "mode")]
+    #[test_case(&["--expect=owner=4242"], &["./a.txt", "./one/b.md", "./one/two/c.txt"], "audited 3 entries, 3 deviating (owner: 3)" ; "owner")]
+    #[test_case(&["--expect=mode=744", "--expect=owner=4242"], &["./a.txt", "./one/b.md", "./one/two/c.txt"], "3 deviating (mode: 2, owner: 3)" ; "mode and owner")]
+    fn audit(args: &[&str], expected: &[&str], expected_summary: &str) -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["--audit=ownership", "--type=f"])
+            .args(args)
+            .assert()
+            .stdout(predicate::function(|out: &str| {
+                let stdout_lines = out.lines().collect::<Vec<&str>>();
+
+                count_lines(&stdout_lines) == count_lines(expected)
+            }))
+            .stderr(predicate::str::contains(expected_summary))
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case(&["--policy"] ; "option")]
+    #[test_case(&["policy", "check"] ; "subcommand")]
+    fn policy(args: &[&str]) -> Result<()> {
+        let dir = setup_root_dir()?;
+        let mut policy = tempfile::NamedTempFile::new()?;
+
+        write!(
+            policy,
+            r#"
+rules:
+- name: readonly
+  path: '^\./one/two/'
+  mode: '0644'
+- path: '\.md$'
+  max_size: 4
+- name: no-links
+  path: '.'
+  forbidden_types: [sym-link]
+"#
+        )?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(args)
+            .arg(policy.path())
+            .args(["--type=f", "--type=l"])
+            .assert()
+            .stdout(predicate::function(|out: &str| {
+                let stdout_lines = out.lines().collect::<Vec<&str>>();
+
+                count_lines(&stdout_lines) == count_lines(&[
+                    r#"{"path":"./one/two/c.txt","rule":"readonly","check":"mode","expected":"0644","actual":"0444"}"#,
+                    r#"{"path":"./one/b.md","rule":"rule-2","check":"max_size","expected":"4","actual":"8"}"#,
+                    r#"{"path":"./three/d.txt","rule":"no-links","check":"forbidden_type","expected":"absent","actual":"sym-link"}"#,
+                ])
+            }))
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_output() -> Result<()> {
+        let dir = setup_root_dir()?;
+        let db = tempfile::NamedTempFile::new()?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .arg("--type=f")
+            .arg(format!("--output=sqlite:{}", db.path().display()))
+            .assert()
+            .stdout("")
+            .success();
+
+        let conn = rusqlite::Connection::open(db.path())?;
+        let mut stmt = conn.prepare("SELECT path, size FROM results ORDER BY path")?;
+        let rows = stmt
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<(String, i64)>>>()?;
+
+        assert_eq!(
+            vec![
+                ("./a.txt".to_string(), 0),
+                ("./one/b.md".to_string(), 8),
+                ("./one/two/c.txt".to_string(), 0),
+            ],
+            rows
+        );
+
+        Ok(dir.close()?)
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_and_plain_output() -> Result<()> {
+        let dir = setup_root_dir()?;
+        let db = tempfile::NamedTempFile::new()?;
+        let db_output = format!("sqlite:{}", db.path().display());
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["--size=8", "--output=plain"])
+            .arg(format!("--output={db_output}"))
+            .assert()
+            .stdout("./one/b.md\n")
+            .stderr(format!(
+                "findr: output 'plain': 1 written, 0 omitted\n\
+                 findr: output '{db_output}': 1 written, 0 omitted\n"
+            ))
+            .success();
+
+        let conn = rusqlite::Connection::open(db.path())?;
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM results", [], |r| r.get(0))?;
+
+        assert_eq!(1, count);
+
+        Ok(dir.close()?)
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test_case(&["--size=8"], "./one/b.md\n" ; "size")]
+    #[test_case(&["--type=f", "--mode=444"], "./one/two/c.txt\n" ; "type and mode")]
+    #[test_case(&["--pattern=dne"], "" ; "no matches")]
+    fn from_db(args: &[&str], expected: &str) -> Result<()> {
+        let dir = setup_root_dir()?;
+        let db = tempfile::NamedTempFile::new()?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .arg(format!("--output=sqlite:{}", db.path().display()))
+            .assert()
+            .success();
+
+        fs::remove_dir_all(dir.path().join("one"))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .arg("--from-db")
+            .arg(db.path())
+            .args(args)
+            .assert()
+            .stdout(expected.to_string())
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    #[test]
+    fn from_db_without_feature() -> Result<()> {
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .args(["--from-db=results.db"])
+            .assert()
+            .stderr(predicate::str::contains(
+                "requires findr to be built with the 'sqlite' feature",
+            ))
+            .failure();
+
+        Ok(())
+    }
+
+    #[cfg(feature = "fancy-regex")]
+    #[test]
+    fn fancy_regex_engine() -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args([
+                "--regex-engine=fancy",
+                "--pattern=^./one(?!/two)/",
+                "--visit-order=name",
+            ])
+            .assert()
+            .stdout("./one/b.md\n")
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[cfg(not(feature = "fancy-regex"))]
+    #[test]
+    fn fancy_regex_engine_without_feature() -> Result<()> {
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .args(["--regex-engine=fancy", "--pattern=a(?!b)"])
+            .assert()
+            .stderr(predicate::str::contains(
+                "requires findr to be built with the 'fancy-regex' feature",
+            ))
+            .failure();
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "xattr"))]
+    #[test]
+    fn xattr_without_feature() -> Result<()> {
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .args(["--xattr=user.backup.exclude"])
+            .assert()
+            .stderr(predicate::str::contains(
+                "requires findr to be built with the 'xattr' feature",
+            ))
+            .failure();
+
+        Ok(())
+    }
+
+    #[cfg(not(all(feature = "selinux", target_os = "linux")))]
+    #[test]
+    fn context_without_feature() -> Result<()> {
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .args(["--context=:bin_t:"])
+            .assert()
+            .stderr(predicate::str::contains(
+                "requires findr to be built with the 'selinux' feature",
+            ))
+            .failure();
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "analytics"))]
+    #[test]
+    fn parquet_output_without_feature() -> Result<()> {
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .args(["--output=parquet:results.parquet"])
+            .assert()
+            .stderr(predicate::str::contains(
+                "requires findr to be built with the 'analytics' feature",
+            ))
+            .failure();
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    #[test]
+    fn sqlite_output_without_feature() -> Result<()> {
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .args(["--output=sqlite:results.db"])
+            .assert()
+            .stderr(predicate::str::contains(
+                "requires findr to be built with the 'sqlite' feature",
+            ))
+            .failure();
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "systemd"))]
+    #[test]
+    fn since_unit_without_feature() -> Result<()> {
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .args(["--since=nginx.service"])
+            .assert()
+            .stderr(predicate::str::contains(
+                "requires findr to be built with the 'systemd' feature",
+            ))
+            .failure();
+
+        Ok(())
+    }
+
+    #[test]
+    fn events_output() -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["--output=events", "--size=8"])
+            .assert()
+            .stdout(concat!(
+                "{\"type\":\"start\",\"version\":1}\n",
+                "{\"type\":\"match\",\"path\":\"./one/b.md\"}\n",
+                "{\"type\":\"summary\",\"matches\":1,\"errors\":0,\"truncated\":0}\n",
+            ))
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case("quickfix", "./one/b.md:1:1: file, 8 bytes, mode 0644, modified " ; "lines")]
+    #[test_case("quickfix-json", r#"[{"filename":"./one/b.md","lnum":1,"col":1,"text":"file, 8 bytes, mode 0644, modified "# ; "json")]
+    fn quickfix_output(format: &str, expected: &str) -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        fs::set_permissions(
+            dir.path().join("one").join("b.md"),
+            fs::Permissions::from_mode(0o644),
+        )?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .arg(format!("--output={format}"))
+            .arg("--size=8")
+            .assert()
+            .stdout(predicate::str::starts_with(expected))
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn score_cleanup() -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        fs::create_dir(dir.path().join("tmp"))?;
+        fs::write(dir.path().join("tmp").join("e.log"), "")?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["--score-cleanup", "--type=f", "--top=2"])
+            .assert()
+            .stdout("25\t./tmp/e.log\n8\t./one/b.md\n")
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case("--pattern=readme", "./README.md\n./readme.txt\n" ; "lowercase pattern ignores case")]
+    #[test_case("--pattern=README", "./README.md\n" ; "uppercase pattern is exact")]
+    #[test_case("--glob=readme*", "./README.md\n./readme.txt\n" ; "lowercase glob ignores case")]
+    fn smart_case(pattern: &str, expected: &'static str) -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+
+        fs::File::create(dir.path().join("README.md"))?;
+        fs::File::create(dir.path().join("readme.txt"))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["--smart-case", "--visit-order=name"])
+            .arg(pattern)
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case(&[], "" ; "not normalized")]
+    #[test_case(&["--normalize=nfc"], "./cafe\u{301}.txt\n" ; "composed")]
+    #[test_case(&["--normalize=nfd"], "./cafe\u{301}.txt\n" ; "decomposed")]
+    fn normalize(args: &[&str], expected: &'static str) -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+
+        fs::File::create(dir.path().join("cafe\u{301}.txt"))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(args)
+            .arg("--pattern=caf\u{e9}")
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case("--type=c", "/dev/null\n" ; "char device")]
+    #[test_case("--type=b", "" ; "block device")]
+    fn device_types(file_type: &str, expected: &'static str) -> Result<()> {
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .args(["/dev", "--max-depth=1", "--pattern=^/dev/null$", file_type])
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(())
+    }
+
+    #[test_case(&["--suid"], "./a.txt\n" ; "setuid")]
+    #[test_case(&["--sticky"], "./three\n" ; "sticky")]
+    #[test_case(&["--suid", "--sticky"], "" ; "setuid and sticky")]
+    #[test_case(&["--mode=4744"], "./a.txt\n" ; "exact mode with setuid")]
+    fn special_bits(args: &[&str], expected: &'static str) -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        fs::set_permissions(dir.path().join("a.txt"), fs::Permissions::from_mode(0o4744))?;
+        fs::set_permissions(dir.path().join("three"), fs::Permissions::from_mode(0o1777))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(args)
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case("--type=bl", "./dangling\n./loop\n./three/d.txt\n" ; "broken symlinks")]
+    #[test_case("--type=l,!broken-sym-link", "./resolvable\n" ; "resolvable symlinks")]
+    fn broken_symlinks(file_type: &str, expected: &'static str) -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        std::os::unix::fs::symlink("dne.txt", dir.path().join("dangling"))?;
+        std::os::unix::fs::symlink("loop", dir.path().join("loop"))?;
+        std::os::unix::fs::symlink("a.txt", dir.path().join("resolvable"))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args([file_type, "--visit-order=name"])
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case(&["--resolvable"], "./chained\n./resolvable\n" ; "resolvable symlinks")]
+    #[test_case(&["--resolvable=dangling"], "./dangling\n./three/d.txt\n" ; "dangling symlinks")]
+    #[test_case(&["--resolvable=cyclic"], "./loop\n" ; "cyclic symlinks")]
+    #[test_case(&["--resolvable", "--max-link-depth=1"], "./resolvable\n" ; "limited depth")]
+    #[test_case(&["--resolvable=cyclic", "--max-link-depth=1"], "./chained\n./loop\n" ; "too deep")]
+    fn link_chains(args: &[&str], expected: &'static str) -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        std::os::unix::fs::symlink("dne.txt", dir.path().join("dangling"))?;
+        std::os::unix::fs::symlink("loop", dir.path().join("loop"))?;
+        std::os::unix::fs::symlink("a.txt", dir.path().join("resolvable"))?;
+        std::os::unix::fs::symlink("resolvable", dir.path().join("chained"))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(args)
+            .arg("--visit-order=name")
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case("--fstype=proc", "/proc\n" ; "matching filesystem")]
+    #[test_case("--fstype=nfs", "" ; "other filesystem")]
+    fn fstype(fstype: &str, expected: &'static str) -> Result<()> {
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .args(["/proc", "--max-depth=0", fstype])
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(())
+    }
+
+    #[test_case("/proc", "/proc\n" ; "mount point")]
+    #[test_case("/proc/self", "" ; "within mount")]
+    fn mount_point(root: &str, expected: &'static str) -> Result<()> {
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .args([root, "--max-depth=0", "--type=mount-point"])
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(())
+    }
+
+    #[test]
+    fn links() -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        fs::hard_link(
+            dir.path().join("one").join("b.md"),
+            dir.path().join("three").join("e.md"),
+        )?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["--links=+1", "--type=f", "--visit-order=name"])
+            .assert()
+            .stdout("./one/b.md\n./three/e.md\n")
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn sparse() -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        fs::File::create(dir.path().join("one").join("image.raw"))?.set_len(1 << 20)?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .arg("--sparse")
+            .assert()
+            .stdout("./one/image.raw\n")
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case(&["--size=+1k", "--glob=*.raw"], "./one/image.raw:1:1: file, 1048576 bytes" ; "apparent size")]
+    #[test_case(&["--disk-usage", "--size=-1k", "--glob=*.raw"], "./one/image.raw:1:1: file, 0 bytes" ; "allocated size")]
+    fn disk_usage(args: &[&str], expected: &'static str) -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        fs::File::create(dir.path().join("one").join("image.raw"))?.set_len(1 << 20)?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(args)
+            .arg("--output=quickfix")
+            .assert()
+            .stdout(predicate::str::starts_with(expected))
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case(&["--owner=4242", "--owner={uid}"], "./a.txt\n./one/b.md\n./one/two/c.txt\n" ; "any of several owners")]
+    #[test_case(&["--owner=!{uid}"], "" ; "negated owner")]
+    #[test_case(&["--group-of={uid}"], "./a.txt\n./one/b.md\n./one/two/c.txt\n" ; "groups of user")]
+    #[test_case(&["--owner=:4242", "--group-members"], "" ; "members of group")]
+    #[test_case(&["--uid={uid}..4242", "--gid=-4242"], "./a.txt\n./one/b.md\n./one/two/c.txt\n" ; "id ranges")]
+    #[test_case(&["--uid=+{uid}"], "" ; "greater uid")]
+    #[test_case(&["--owner=!4242", "--owner=!:4242"], "./a.txt\n./one/b.md\n./one/two/c.txt\n" ; "none of several negated owners")]
+    fn owners(args: &[&str], expected: &'static str) -> Result<()> {
+        let dir = setup_root_dir()?;
+        let uid = users::get_current_uid().to_string();
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(args.iter().map(|a| a.replace("{uid}", &uid)))
+            .args(["--type=f", "--visit-order=name"])
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case(&[], "./.git/config\n./.profile\n./a.txt\n./one/b.md\n./one/two/c.txt\n" ; "searched by default")]
+    #[test_case(&["--no-hidden"], "./a.txt\n./one/b.md\n./one/two/c.txt\n" ; "skipped")]
+    #[test_case(&["--no-hidden", "--hidden"], "./.git/config\n./.profile\n./a.txt\n./one/b.md\n./one/two/c.txt\n" ; "included after skipped")]
+    #[test_case(&["--hidden", "--no-hidden"], "./a.txt\n./one/b.md\n./one/two/c.txt\n" ; "skipped after included")]
+    fn hidden(args: &[&str], expected: &'static str) -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        fs::create_dir(dir.path().join(".git"))?;
+        fs::write(dir.path().join(".git").join("config"), "")?;
+        fs::write(dir.path().join(".profile"), "")?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(args)
+            .args(["--type=f", "--visit-order=name"])
+            .assert()
+            .stdout(expected)
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn open() -> Result<()> {
+        let dir = setup_root_dir()?;
+        let _held = fs::File::open(dir.path().join("one").join("b.md"))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["--open", "--type=f"])
+            .assert()
+            .stdout("./one/b.md\n")
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn unique_inodes() -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        fs::hard_link(
+            dir.path().join("one").join("b.md"),
+            dir.path().join("three").join("e.md"),
+        )?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["--unique-inodes", "--type=f", "--visit-order=name"])
+            .assert()
+            .stdout("./a.txt\n./one/b.md\n./one/two/c.txt\n")
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn samefile() -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        fs::hard_link(
+            dir.path().join("one").join("b.md"),
+            dir.path().join("three").join("e.md"),
+        )?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["--samefile=one/b.md", "--visit-order=name"])
+            .assert()
+            .stdout("./one/b.md\n./three/e.md\n")
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn inum() -> Result<()> {
+        let dir = setup_root_dir()?;
+        let ino = fs::metadata(dir.path().join("one").join("b.md"))?.ino();
+
+        fs::hard_link(
+            dir.path().join("one").join("b.md"),
+            dir.path().join("three").join("e.md"),
+        )?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .arg(format!("--inum={ino}"))
+            .arg("--visit-order=name")
+            .assert()
+            .stdout("./one/b.md\n./three/e.md\n")
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn pattern_file() -> Result<()> {
+        let dir = setup_root_dir()?;
+        let patterns = tempfile::NamedTempFile::new()?;
+
+        fs::write(patterns.path(), "# markdown\n\\.md$\n\n")?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .arg(format!("--pattern-file={}", patterns.path().display()))
+            .args(["--pattern=^./a", "--visit-order=name"])
+            .assert()
+            .stdout("./a.txt\n./one/b.md\n")
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn exclude_from() -> Result<()> {
+        let dir = setup_root_dir()?;
+        let excludes = tempfile::NamedTempFile::new()?;
+
+        fs::write(excludes.path(), "# build output\n/one$\n\n")?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .arg(format!("--exclude-from={}", excludes.path().display()))
+            .arg(r"--exclude=\.txt$")
+            .assert()
+            .stdout(".\n./three\n")
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn split_output() -> Result<()> {
+        let dir = setup_root_dir()?;
+        let out = tempfile::TempDir::new()?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .arg(format!(
+                "--split-output={}/results-%02d.txt",
+                out.path().display()
+            ))
+            .args(["--split-every=2", "--type=d"])
+            .assert()
+            .stdout("")
+            .success();
+
+        let mut lines = Vec::new();
+
+        for n in ["01", "02"] {
+            let chunk = fs::read_to_string(out.path().join(format!("results-{n}.txt")))?;
+
+            assert_eq!(2, chunk.lines().count());
+
+            lines.extend(chunk.lines().map(String::from));
+        }
+
+        assert!(!out.path().join("results-03.txt").exists());
+        assert_eq!(
+            count_lines(&lines.iter().map(String::as_str).collect::<Vec<&str>>()),
+            count_lines(&[".", "./one", "./one/two", "./three"])
+        );
+
+        out.close()?;
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn jobs() -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        fs::write(
+            dir.path().join("jobs.toml"),
+            r#"
+[[job]]
+name = "markdown"
+roots = ["one"]
+args = ["--pattern=\\.md$"]
+
+[[job]]
+name = "none"
+args = ["--pattern=dne", "--strict-exit"]
+
+[[job]]
+name = "readonly"
+roots = ["one/two"]
+args = ["--mode=444"]
+"#,
+        )?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["run-jobs", "jobs.toml", "--job=readonly", "--job=markdown"])
+            .assert()
+            .stdout("one/two/c.txt\none/b.md\n")
+            .stderr(
+                "findr: job 'readonly' finished with exit code 0\n\
+                 findr: job 'markdown' finished with exit code 0\n",
+            )
+            .success();
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["run-jobs", "jobs.toml", "--job=none", "--job=markdown"])
+            .assert()
+            .stdout("one/b.md\n")
+            .stderr(predicate::str::contains(
+                "job 'none' finished with exit code 1",
+            ))
+            .code(1);
+
+        Ok(dir.close()?)
+    }
+
     fn count_lines<'a>(lines: &'a [&str]) -> HashMap<&'a str, usize> {
         let mut counts: HashMap<&str, usize> = HashMap::new();
 
@@ -55,9 +1182,70 @@ mod findr {
 
     #[test_case(&["dne"], "IO error for operation on dne: No such file or directory" ; "non-existent root directory")]
     #[test_case(&["--pattern", "["], "regex parse error" ; "invalid pattern")]
+    #[test_case(&["--glob", "a["], "invalid value 'a[' for '--glob" ; "invalid glob")]
     #[test_case(&["--type", "j"], "invalid value 'j' for '--type" ; "unknown type")]
     #[test_case(&["--size", "one"], "invalid value 'one' for '--size" ; "non-numeric size")]
     #[test_case(&["--mtime", "13p"], "invalid value '13p' for '--mtime" ; "invalid mtime duration")]
+    #[test_case(&["--map", "upper"], "invalid value 'upper' for '--map" ; "unknown map transformation")]
+    #[test_case(&["--audit", "ownership"], "--expect <EXPECTATIONS>" ; "audit without expectations")]
+    #[test_case(&["--audit", "ownership", "--expect", "size=1"], "invalid value 'size=1' for '--expect" ; "unknown expectation")]
+    #[test_case(&["--policy", "dne.yaml"], "invalid policy 'dne.yaml'" ; "non-existent policy")]
+    #[test_case(&["--reference", "dne.txt"], "invalid reference list 'dne.txt'" ; "non-existent reference list")]
+    #[test_case(&["--missing"], "--reference <REFERENCE>" ; "missing without reference")]
+    #[test_case(&["orphans", "--type=f"], "'orphans' requires '--reference <REFERENCE>'" ; "orphans without reference")]
+    #[test_case(&["-0"], "--reference <REFERENCE>" ; "nul separated without reference")]
+    #[test_case(&["policy", "check"], "<POLICY>" ; "policy check without file")]
+    #[test_case(&["policy", "check", "a.yaml", "--policy=b.yaml"], "cannot be used multiple times" ; "policy check with policy")]
+    #[test_case(&["--owner", "dne_user"], "invalid user 'dne_user'" ; "non-existent owner")]
+    #[test_case(&["--samefile", "dne"], "invalid reference file 'dne'" ; "missing samefile reference")]
+    #[test_case(&["--btime-missing", "never"], "invalid value 'never' for '--btime-missing" ; "unknown btime policy")]
+    #[test_case(&["--attr", "iz"], "invalid inode flag 'z'" ; "unknown inode flag")]
+    #[test_case(&["--caps", "cap_dne"], "invalid capability 'cap_dne'" ; "unknown capability")]
+    #[test_case(&["--group-of", "dne_user"], "invalid user 'dne_user'" ; "groups of unknown user")]
+    #[test_case(&["--group-members"], "--owner <OWNER>" ; "group members without owner")]
+    #[test_case(&["--uid", "root"], "invalid value 'root' for '--uid" ; "uid name")]
+    #[test_case(&["--owner", "!!root"], "invalid owner '!!root'" ; "double negated owner")]
+    #[test_case(&["--owner", "root", "--no-nss"], "invalid user 'root'" ; "owner name without nss")]
+    #[test_case(&["--emit-cmd=rm", "--output=plain"], "cannot be used with" ; "emit command with output")]
+    #[test_case(&["--emit-cmd=rm", "--escape=json"], "cannot be used with" ; "emit command with escape")]
+    #[test_case(&["--missing", "--reference=dne.txt", "--audit=ownership", "--expect=mode=644"], "cannot be used with" ; "missing with audit")]
+    #[test_case(&["run-jobs", "dne.toml"], "invalid jobs file 'dne.toml'" ; "non-existent jobs file")]
+    #[test_case(&["run-jobs", "--job", "a"], "<JOBS>" ; "job without jobs file")]
+    #[test_case(&["run-jobs", "jobs.toml", "--type=f"], "unexpected argument '--type'" ; "run jobs with search options")]
+    #[test_case(&["run-jobs", "jobs.toml", "/tmp"], "unexpected argument '/tmp'" ; "run jobs with roots")]
+    #[test_case(&["--on-locked", "wait"], "--lock <LOCK>" ; "lock policy without lock")]
+    #[test_case(&["--metadata-only", "--exclude-caches"], "cannot be used with" ; "metadata only reading contents")]
+    #[test_case(&["--metadata-only", "--contains", "hello"], "cannot be used with" ; "metadata only matching contents")]
+    #[test_case(&["--metadata-only", "--hash", "md5=d41d8cd98f00b204e9800998ecf8427e"], "cannot be used with" ; "metadata only hashing contents")]
+    #[test_case(&["--full-content", "--max-read=1MiB"], "cannot be used with" ; "full content with max read")]
+    #[test_case(&["--metadata-only", "--duplicates"], "cannot be used with" ; "metadata only finding duplicates")]
+    #[test_case(&["--metadata-only", "--type", "text"], "cannot be used with" ; "metadata only text type")]
+    #[test_case(&["--metadata-only", "--type", "f,!binary"], "cannot be used with" ; "metadata only negated binary type")]
+    #[test_case(&["--split-output", "results.txt"], "has no '%d' counter" ; "split output without counter")]
+    #[test_case(&["--split-output", "results-%d.txt", "--output=events"], "cannot be used with" ; "split output with events")]
+    #[test_case(&["--split-output", "o-%d", "--split-every", "1", "--output=quickfix"], "cannot be used with" ; "split output with quickfix")]
+    #[test_case(&["--split-output", "results-%d.txt", "--split-every", "0"], "invalid value '0' for '--split-every" ; "split every zero")]
+    #[test_case(&["--estimate", "0"], "must be greater than 0" ; "estimate without sampling")]
+    #[test_case(&["--estimate", "0.5", "--output=plain"], "cannot be used with" ; "estimate with output")]
+    #[test_case(&["--output", "plain", "--output", "shell-array"], "only one text output" ; "several text outputs")]
+    #[test_case(&["--disk-usage", "--from-db=results.db"], "cannot be used with" ; "disk usage from database")]
+    #[test_case(&["--nouser", "--no-nss"], "cannot be used with" ; "nouser without nss")]
+    #[test_case(&["--no-hidden", "--from-db=results.db"], "cannot be used with" ; "no hidden from database")]
+    #[test_case(&["--newer-than-parent", "--from-db=results.db"], "cannot be used with" ; "newer than parent from database")]
+    #[test_case(&["--type=empty-dir", "--from-db=results.db"], "cannot be used with" ; "empty dir from database")]
+    #[test_case(&["--has-acl", "--no-acl"], "cannot be used with" ; "has acl with no acl")]
+    #[test_case(&["--smart-case", "--ignore-case"], "cannot be used with" ; "smart case with ignore case")]
+    #[test_case(&["--ext", "."], "invalid value '.' for '--ext" ; "empty extension")]
+    #[test_case(&["--links", "1k"], "invalid value '1k' for '--links" ; "link count with suffix")]
+    #[test_case(&["--newer", "dne.txt"], "invalid reference file 'dne.txt'" ; "non-existent newer reference")]
+    #[test_case(&["--mtime", "2024-13-01"], "invalid duration or timestamp '2024-13-01'" ; "invalid mtime timestamp")]
+    #[test_case(&["--mode", "u=rw,g+r"], "mixes operators" ; "symbolic mode with mixed operators")]
+    #[cfg_attr(feature = "xattr", test_case(&["--xattr", "=1"], "invalid extended attribute name ''" ; "xattr without name"))]
+    #[test_case(&["--exclude", "["], "invalid value '[' for '--exclude" ; "invalid exclude")]
+    #[test_case(&["--pattern-file", "dne.txt"], "invalid pattern file 'dne.txt'" ; "non-existent pattern file")]
+    #[test_case(&["--exclude-from", "dne.txt"], "invalid pattern file 'dne.txt'" ; "non-existent exclude file")]
+    #[test_case(&["--top", "3"], "--score-cleanup" ; "top without scoring")]
+    #[test_case(&["--on-filter-error", "ignore"], "invalid value 'ignore' for '--on-filter-error" ; "unknown filter error policy")]
     fn invalid(args: &[&str], expected: &str) -> Result<()> {
         Command::cargo_bin(env!("CARGO_PKG_NAME"))?
             .args(args)
@@ -68,6 +1256,144 @@ mod findr {
         Ok(())
     }
 
+    #[test_case(&["--type=f"], "./a.txt\n" ; "plain")]
+    #[test_case(&["--type=f", "--output=shell-array"], "FILES=(\n  './a.txt'\n)\n" ; "shell array")]
+    #[test_case(&["--type=f", "--output=shell-array=found"], "found=(\n  './a.txt'\n)\n" ; "named shell array")]
+    #[test_case(&["--pattern=dne", "--output=shell-array"], "FILES=(\n)\n" ; "empty shell array")]
+    #[test_case(&["--max-output=8"], ".\n… truncated (1 more match)\n" ; "max output")]
+    #[test_case(&["--max-output=8", "--output=shell-array"], "FILES=(\n  '.'\n  # … truncated (1 more match)\n)\n" ; "max output shell array")]
+    #[test_case(&["--max-output=8", "--lang=es"], ".\n… truncado (1 coincidencia más)\n" ; "max output in spanish")]
+    #[test_case(&["--max-output=1kB"], ".\n./a.txt\n" ; "max output not reached")]
+    #[test_case(
+        &["--max-output=40", "--output=events"],
+        concat!(
+            "{\"type\":\"start\",\"version\":1}\n",
+            "{\"type\":\"match\",\"path\":\".\"}\n",
+            "{\"type\":\"summary\",\"matches\":2,\"errors\":0,\"truncated\":1}\n",
+        )
+        ; "max output events"
+    )]
+    #[test_case(&["--type=f", "--map=s#^\\./##", "--map=prepend:https://cdn.example.com/"], "https://cdn.example.com/a.txt\n" ; "map")]
+    #[test_case(&["--type=f", "--escape=json"], "\"./a.txt\"\n" ; "json escaped")]
+    #[test_case(&["--type=f", "--map=append: b", "--escape=url"], "./a.txt%20b\n" ; "url escaped")]
+    #[test_case(&["--type=f", "--emit-cmd=mv {} {.}.bak"], "mv './a.txt' './a'.bak\n" ; "emit command")]
+    fn output(args: &[&str], expected: &str) -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+
+        fs::File::create(dir.path().join("a.txt"))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(args)
+            .assert()
+            .stdout(expected.to_string())
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case("long", " setuid ./a.txt\n" ; "long")]
+    #[test_case("csv", ",setuid\n" ; "csv")]
+    #[test_case("json", r#","security":"setuid"}"# ; "json")]
+    fn security_field(format: &str, expected: &str) -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let path = dir.path().join("a.txt");
+
+        fs::File::create(&path)?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o4755))?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(["--type=f", &format!("--output={format}")])
+            .assert()
+            .stdout(predicate::str::contains(expected))
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case(&["one/two", "three"], "one/two\none/two/c.txt\nthree\nthree/d.txt\n" ; "sequential roots")]
+    #[test_case(&["--interleave-roots", "one/two", "three"], "one/two\nthree\none/two/c.txt\nthree/d.txt\n" ; "interleaved roots")]
+    #[test_case(&["--visit-order=name", "one"], "one\none/b.md\none/two\none/two/c.txt\n" ; "visit by name")]
+    fn roots(args: &[&str], expected: &str) -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(args)
+            .assert()
+            .stdout(expected.to_string())
+            .success();
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case(&["--strict-exit"], 0 ; "matches found")]
+    #[test_case(&["--strict-exit", "--pattern=dne"], 1 ; "no matches")]
+    #[test_case(&["--strict-exit", "--type=j"], 2 ; "invalid argument")]
+    #[test_case(&["--strict-exit", "dne"], 2 ; "non-existent root directory")]
+    #[test_case(&["--strict-exit", "--owner=dne_user"], 2 ; "non-existent owner")]
+    #[test_case(&["--strict-exit", "--no-nss", "--owner=4242"], 1 ; "numeric owner without nss")]
+    #[test_case(&["--strict-exit", "--max-output=1"], 4 ; "truncated output")]
+    #[test_case(&["--strict-exit", "--max-output=1", "--output=events"], 4 ; "truncated events")]
+    #[test_case(&["--strict-exit", "--max-output=1", "--output=quickfix-json"], 4 ; "truncated quickfix")]
+    #[test_case(&["--pattern=dne"], 0 ; "no matches without strict exit")]
+    fn strict_exit(args: &[&str], expected: i32) -> Result<()> {
+        let dir = setup_root_dir()?;
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args(args)
+            .assert()
+            .code(expected);
+
+        Ok(dir.close()?)
+    }
+
+    #[test]
+    fn lock() -> Result<()> {
+        use std::os::unix::io::AsRawFd;
+
+        let dir = setup_root_dir()?;
+        let lock = tempfile::NamedTempFile::new()?;
+        let lock_arg = format!("--lock={}", lock.path().display());
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args([&lock_arg, "--strict-exit", "--type=f", "--mode=444"])
+            .assert()
+            .stdout("./one/two/c.txt\n")
+            .code(0);
+
+        let held = fs::File::open(lock.path())?;
+
+        assert_eq!(0, unsafe {
+            libc::flock(held.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB)
+        });
+
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .current_dir(dir.path())
+            .args([&lock_arg, "--strict-exit", "--on-locked=wait=200ms"])
+            .assert()
+            .stdout("")
+            .stderr(predicate::str::contains("is locked by another search"))
+            .code(5);
+
+        Ok(dir.close()?)
+    }
+
+    #[test_case("json", r#"{"code":1,"name":"no-matches""# ; "json")]
+    #[test_case("text", "3\tpartial-errors\t" ; "text")]
+    fn exit_codes(format: &str, expected: &str) -> Result<()> {
+        Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+            .args(["--exit-codes", format])
+            .assert()
+            .stdout(predicate::str::contains(expected))
+            .success();
+
+        Ok(())
+    }
+
     fn setup_root_dir() -> Result<tempfile::TempDir> {
         let temp = tempfile::TempDir::new()?;
         let root = temp.path();
@@ -81,6 +1407,7 @@ mod findr {
         fs::OpenOptions::new()
             .write(true)
             .create(true)
+            .truncate(true)
             .mode(0o744)
             .open(root.join("a.txt"))?;
 
@@ -92,6 +1419,7 @@ mod findr {
         fs::OpenOptions::new()
             .write(true)
             .create(true)
+            .truncate(true)
             .mode(0o444)
             .open(root.join("one").join("two").join("c.txt"))?;
 